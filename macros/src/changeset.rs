@@ -0,0 +1,183 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Field, Ident, Path, Result, Token,
+    parse::{Parse, ParseStream},
+};
+
+use crate::utils::{
+    FieldAttr, column_enum_path, ident_to_upper_camel, is_option, keywords, option_inner,
+};
+
+/// `#[changeset(table = User, column_enum_name = UserColumn)]`
+pub struct ChangesetAttr {
+    pub table: Path,
+    /// Defaults to `{Table}Column` just like the TableType derive
+    pub column_enum: Option<Path>,
+}
+impl Parse for ChangesetAttr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut table: Option<Path> = None;
+        let mut column_enum: Option<Path> = None;
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            if lookahead.peek(keywords::table) {
+                let _: keywords::table = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                table = Some(input.parse()?);
+            } else if lookahead.peek(keywords::column_enum_name) {
+                let _: keywords::column_enum_name = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                column_enum = Some(input.parse()?);
+            } else {
+                return Err(lookahead.error());
+            }
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        let table = table.ok_or_else(|| syn::Error::new(input.span(), "table is required"))?;
+        Ok(Self { table, column_enum })
+    }
+}
+impl ChangesetAttr {
+    fn column_enum(&self) -> Path {
+        column_enum_path(&self.table, self.column_enum.as_ref())
+    }
+}
+enum ChangeKind {
+    /// Always set
+    Required,
+    /// `Option<T>`. Set if Some
+    Optional,
+    /// `Option<Option<T>>`. `Some(None)` sets NULL
+    Nullable,
+}
+struct ChangesetField {
+    ident: Ident,
+    variant: Ident,
+    kind: ChangeKind,
+}
+impl ChangesetField {
+    fn new(field: Field) -> Result<Option<Self>> {
+        let attr = FieldAttr::from_field(&field)?;
+        let ident = field
+            .ident
+            .ok_or_else(|| syn::Error::new_spanned(&field.ty, "expected named field"))?;
+        if attr.default {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                "`default` is not supported by Changeset. Use `skip`",
+            ));
+        }
+        if attr.skip {
+            return Ok(None);
+        }
+        let kind = match option_inner(&field.ty) {
+            Some(inner) if is_option(inner) => ChangeKind::Nullable,
+            Some(_) => ChangeKind::Optional,
+            None => ChangeKind::Required,
+        };
+        let variant = attr
+            .enum_variant
+            .unwrap_or_else(|| ident_to_upper_camel(&ident));
+        Ok(Some(Self {
+            ident,
+            variant,
+            kind,
+        }))
+    }
+    fn apply(&self, column_enum: &Path) -> TokenStream {
+        let Self {
+            ident,
+            variant,
+            kind,
+        } = self;
+        match kind {
+            ChangeKind::Required => quote! {
+                query.set(#column_enum::#variant, self.#ident.value());
+            },
+            ChangeKind::Optional => quote! {
+                if let Some(value) = self.#ident {
+                    query.set(#column_enum::#variant, value.value());
+                }
+            },
+            ChangeKind::Nullable => quote! {
+                match self.#ident {
+                    Some(Some(value)) => {
+                        query.set(#column_enum::#variant, value.value());
+                    }
+                    Some(None) => {
+                        query.set_null(#column_enum::#variant);
+                    }
+                    None => {}
+                }
+            },
+        }
+    }
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        data,
+        attrs,
+        generics,
+        ..
+    } = input;
+    let Data::Struct(data_struct) = data else {
+        return Err(syn::Error::new_spanned(ident, "expected struct"));
+    };
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("changeset"))
+        .map(|attr| attr.parse_args::<ChangesetAttr>())
+        .ok_or_else(|| syn::Error::new_spanned(&ident, "changeset attribute is required"))??;
+    let syn::Fields::Named(fields) = data_struct.fields else {
+        return Err(syn::Error::new_spanned(ident, "expected named fields"));
+    };
+    let fields = fields
+        .named
+        .into_iter()
+        .filter_map(|field| ChangesetField::new(field).transpose())
+        .collect::<Result<Vec<_>>>()?;
+    let column_enum = attr.column_enum();
+    let applies: Vec<_> = fields
+        .iter()
+        .map(|field| field.apply(&column_enum))
+        .collect();
+    let has_changes = if fields.is_empty() {
+        quote! { false }
+    } else if fields
+        .iter()
+        .any(|field| matches!(field.kind, ChangeKind::Required))
+    {
+        quote! { true }
+    } else {
+        let idents = fields.iter().map(|field| &field.ident);
+        quote! { #(self.#idents.is_some())||* }
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let result = quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Adds a `SET` for every field that is not `None`
+            ///
+            /// `Some(None)` for `Option<Option<T>>` fields sets the column to NULL.
+            ///
+            /// Check [Self::has_changes] first. An `UPDATE` without any `SET` is invalid SQL.
+            /// Execute the query with `try_query` to get an `Error::MissingClause` instead of a database error
+            pub fn apply_to<'args>(self, query: &mut UpdateQueryBuilder<'args>)
+            where
+                Self: 'args,
+            {
+                #(#applies)*
+            }
+            /// If [Self::apply_to] would set any column
+            pub fn has_changes(&self) -> bool {
+                #has_changes
+            }
+        }
+    };
+    Ok(result)
+}
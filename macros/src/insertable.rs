@@ -0,0 +1,290 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Field, Ident, Path, Result, Token,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
+
+use crate::utils::{FieldAttr, column_enum_path, ident_to_upper_camel, is_option, keywords};
+
+/// `#[insertable(table = User, column_enum_name = UserColumn, conflict(email))]`
+pub struct InsertableAttr {
+    pub table: Path,
+    /// Defaults to `{Table}Column` just like the TableType derive
+    pub column_enum: Option<Path>,
+    /// The fields used as the conflict target. Turns the insert into an upsert
+    pub conflict: Vec<Ident>,
+}
+impl Parse for InsertableAttr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut table: Option<Path> = None;
+        let mut column_enum: Option<Path> = None;
+        let mut conflict = Vec::new();
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            if lookahead.peek(keywords::table) {
+                let _: keywords::table = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                table = Some(input.parse()?);
+            } else if lookahead.peek(keywords::column_enum_name) {
+                let _: keywords::column_enum_name = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                column_enum = Some(input.parse()?);
+            } else if lookahead.peek(keywords::conflict) {
+                let _: keywords::conflict = input.parse()?;
+                let content;
+                syn::parenthesized!(content in input);
+                let fields = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                conflict.extend(fields);
+            } else {
+                return Err(lookahead.error());
+            }
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        let table = table.ok_or_else(|| syn::Error::new(input.span(), "table is required"))?;
+        Ok(Self {
+            table,
+            column_enum,
+            conflict,
+        })
+    }
+}
+impl InsertableAttr {
+    fn column_enum(&self) -> Path {
+        column_enum_path(&self.table, self.column_enum.as_ref())
+    }
+}
+struct InsertableField {
+    ident: Ident,
+    variant: Ident,
+    optional: bool,
+}
+impl InsertableField {
+    fn new(field: Field) -> Result<Option<Self>> {
+        let attr = FieldAttr::from_field(&field)?;
+        let ident = field
+            .ident
+            .ok_or_else(|| syn::Error::new_spanned(&field.ty, "expected named field"))?;
+        // Always use DEFAULT. The value of the field is ignored
+        if attr.default || attr.skip {
+            return Ok(None);
+        }
+        let variant = attr
+            .enum_variant
+            .unwrap_or_else(|| ident_to_upper_camel(&ident));
+        Ok(Some(Self {
+            ident,
+            variant,
+            optional: is_option(&field.ty),
+        }))
+    }
+    /// `builder.insert(..)` or `builder.insert_option(..)` if the field is an Option
+    fn insert_row(&self, column_enum: &Path) -> TokenStream {
+        let Self {
+            ident,
+            variant,
+            optional,
+        } = self;
+        if *optional {
+            quote! {
+                builder.insert_option(#column_enum::#variant, row.#ident.map(|value| value.value()));
+            }
+        } else {
+            quote! {
+                builder.insert(#column_enum::#variant, row.#ident.value());
+            }
+        }
+    }
+    /// Inserts the field into a single row insert.
+    ///
+    /// Columns that are inserted are pushed to `update_columns` if they are not part of the conflict target
+    fn insert_single(&self, column_enum: &Path, update_on_conflict: bool) -> TokenStream {
+        let Self {
+            ident,
+            variant,
+            optional,
+        } = self;
+        let update = update_on_conflict.then(|| {
+            quote! {
+                update_columns.push(#column_enum::#variant);
+            }
+        });
+        if *optional {
+            quote! {
+                if let Some(value) = self.#ident {
+                    builder.insert(#column_enum::#variant, value.value());
+                    #update
+                }
+            }
+        } else {
+            quote! {
+                builder.insert(#column_enum::#variant, self.#ident.value());
+                #update
+            }
+        }
+    }
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        data,
+        attrs,
+        generics,
+        ..
+    } = input;
+    let Data::Struct(data_struct) = data else {
+        return Err(syn::Error::new_spanned(ident, "expected struct"));
+    };
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("insertable"))
+        .map(|attr| attr.parse_args::<InsertableAttr>())
+        .ok_or_else(|| syn::Error::new_spanned(&ident, "insertable attribute is required"))??;
+    let syn::Fields::Named(fields) = data_struct.fields else {
+        return Err(syn::Error::new_spanned(ident, "expected named fields"));
+    };
+    let fields = fields
+        .named
+        .into_iter()
+        .filter_map(|field| InsertableField::new(field).transpose())
+        .collect::<Result<Vec<_>>>()?;
+
+    let table = &attr.table;
+    let column_enum = attr.column_enum();
+    let mut conflict_columns = Vec::new();
+    for conflict in &attr.conflict {
+        let field = fields
+            .iter()
+            .find(|field| &field.ident == conflict)
+            .ok_or_else(|| syn::Error::new_spanned(conflict, "unknown field"))?;
+        conflict_columns.push(&field.variant);
+    }
+    let upsert = !conflict_columns.is_empty();
+    let single_inserts: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let update_on_conflict = upsert && !conflict_columns.contains(&&field.variant);
+            field.insert_single(&column_enum, update_on_conflict)
+        })
+        .collect();
+    let row_inserts: Vec<_> = fields
+        .iter()
+        .map(|field| field.insert_row(&column_enum))
+        .collect();
+    let variants: Vec<_> = fields.iter().map(|field| &field.variant).collect();
+    let target = quote! {
+        ConflictTarget::columns(vec![#(#column_enum::#conflict_columns),*])
+    };
+    // Single row inserts only update the columns that were inserted
+    let (single_update_columns, single_on_conflict) = if upsert {
+        (
+            quote! {
+                #[allow(unused_mut)]
+                let mut update_columns: Vec<#column_enum> = Vec::new();
+            },
+            quote! {
+                if update_columns.is_empty() {
+                    builder.on_conflict_do_nothing(#target);
+                } else {
+                    builder.on_conflict_set_excluded(#target, update_columns);
+                }
+            },
+        )
+    } else {
+        (TokenStream::default(), TokenStream::default())
+    };
+    // Multi row inserts only update the columns that have a value in every row.
+    // `EXCLUDED` of a column that used DEFAULT is the default and would overwrite the existing value
+    let (many_update_flags, many_track_defaults, many_on_conflict) = if upsert {
+        let update_fields: Vec<_> = fields
+            .iter()
+            .filter(|field| !conflict_columns.contains(&&field.variant))
+            .collect();
+        let always_set: Vec<_> = update_fields
+            .iter()
+            .filter(|field| !field.optional)
+            .map(|field| &field.variant)
+            .collect();
+        let optional: Vec<_> = update_fields
+            .iter()
+            .filter(|field| field.optional)
+            .collect();
+        let optional_idents: Vec<_> = optional.iter().map(|field| &field.ident).collect();
+        let optional_variants: Vec<_> = optional.iter().map(|field| &field.variant).collect();
+        let flags: Vec<_> = optional
+            .iter()
+            .map(|field| format_ident!("update_{}", field.ident))
+            .collect();
+        (
+            quote! {
+                #(let mut #flags = true;)*
+            },
+            quote! {
+                #(#flags &= row.#optional_idents.is_some();)*
+            },
+            quote! {
+                #[allow(unused_mut)]
+                let mut update_columns: Vec<#column_enum> = vec![#(#column_enum::#always_set),*];
+                #(
+                    if #flags {
+                        update_columns.push(#column_enum::#optional_variants);
+                    }
+                )*
+                if update_columns.is_empty() {
+                    builder.on_conflict_do_nothing(#target);
+                } else {
+                    builder.on_conflict_set_excluded(#target, update_columns);
+                }
+            },
+        )
+    } else {
+        Default::default()
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let result = quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Builds an `INSERT` for this row
+            ///
+            /// `None` values use DEFAULT and are not updated on conflict
+            pub fn into_insert_builder<'args>(self) -> InsertQueryBuilder<'args>
+            where
+                Self: 'args,
+            {
+                let mut builder = InsertQueryBuilder::new(<#table as TableType>::qualified_table_name());
+                #single_update_columns
+                #(#single_inserts)*
+                #single_on_conflict
+                builder
+            }
+            /// Builds a single `INSERT` for all of the rows
+            ///
+            /// `None` values use DEFAULT. On conflict the columns are set to `EXCLUDED`.
+            /// `Option` columns that are `None` in any row are not updated
+            pub fn insert_many<'args, I>(rows: I) -> InsertManyBuilder<'args, #column_enum>
+            where
+                I: IntoIterator<Item = Self>,
+                Self: 'args,
+            {
+                let mut builder = InsertManyBuilder::new(
+                    <#table as TableType>::qualified_table_name(),
+                    vec![#(#column_enum::#variants),*],
+                );
+                #many_update_flags
+                for row in rows {
+                    #many_track_defaults
+                    builder.insert_row(|builder| {
+                        #(#row_inserts)*
+                    });
+                }
+                #many_on_conflict
+                builder
+            }
+        }
+    };
+    Ok(result)
+}
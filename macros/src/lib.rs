@@ -1,5 +1,7 @@
 use proc_macro::TokenStream;
 use syn::parse::Parse;
+pub(crate) mod changeset;
+pub(crate) mod insertable;
 pub(crate) mod table_type;
 pub(crate) mod utils;
 pub(crate) mod value_expr_type;
@@ -22,6 +24,43 @@ pub fn table_type(item: TokenStream) -> TokenStream {
         Err(err) => err.to_compile_error().into(),
     }
 }
+/// Derive macro for "new row" structs
+/// ```rust,ignore
+/// #[derive(Insertable)]
+/// #[insertable(table = User, conflict(email))]
+/// pub struct NewUser {
+///     pub name: String,
+///     pub email: String,
+///     pub bio: Option<String>,
+/// }
+/// ```
+#[proc_macro_derive(Insertable, attributes(column, insertable))]
+pub fn insertable(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+
+    match insertable::expand(input) {
+        Ok(result) => result.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+/// Derive macro for partial updates
+/// ```rust,ignore
+/// #[derive(Changeset)]
+/// #[changeset(table = User)]
+/// pub struct UpdateUser {
+///     pub name: Option<String>,
+///     pub bio: Option<Option<String>>,
+/// }
+/// ```
+#[proc_macro_derive(Changeset, attributes(column, changeset))]
+pub fn changeset(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+
+    match changeset::expand(input) {
+        Ok(result) => result.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
 #[proc_macro_derive(ValueExprType)]
 pub fn derive_value_expr_type(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
@@ -44,11 +44,15 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
             .collect::<Result<Vec<_>>>()?,
         _ => return Err(syn::Error::new_spanned(ident, "expected named fields")),
     };
-    let primary_key = fields
-        .iter()
-        .find(|field| field.primary_key)
+    let primary_key_field = fields.iter().find(|field| field.primary_key);
+    let primary_key = primary_key_field
         .map(|field| has_primary_key(field, &ident, &column_enum_name))
         .unwrap_or_default();
+    let crud = if table_attr.crud {
+        crud_methods(primary_key_field, &ident, &column_enum_name)
+    } else {
+        TokenStream::default()
+    };
     let enum_variants: Vec<_> = fields
         .iter()
         .map(|field| field.enum_variant_def())
@@ -57,16 +61,47 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
         .iter()
         .map(|field| field.display_match_arm())
         .collect();
+    let quoted_column_match_arms: Vec<_> = fields
+        .iter()
+        .map(|field| field.quoted_column_name())
+        .collect();
     let column_type_match_arms: Vec<_> = fields
         .iter()
         .map(|field| field.column_type_name())
         .collect();
     let formatted_column: Vec<_> = fields
         .iter()
-        .map(|field| field.formatted_column(&table_attr.name))
+        .map(|field| field.formatted_column(&table_attr.quoted_name()))
+        .collect();
+    let column_info_match_arms: Vec<_> = fields
+        .iter()
+        .map(|field| field.column_info_match_arm())
         .collect();
     let column_type_all: Vec<_> = fields.iter().map(|field| field.column_type_all()).collect();
-    let TableAttr { name: table_name } = table_attr;
+    let insertable_columns: Vec<_> = fields
+        .iter()
+        .filter(|field| field.is_insertable())
+        .map(|field| field.column_type_all())
+        .collect();
+    let updatable_columns: Vec<_> = fields
+        .iter()
+        .filter(|field| field.is_updatable())
+        .map(|field| field.column_type_all())
+        .collect();
+    let typed_column_consts: Vec<_> = fields
+        .iter()
+        .map(|field| field.typed_column_const())
+        .collect();
+    let qualified_table_name = table_attr.qualified_name();
+    let TableAttr {
+        name: table_name,
+        schema,
+        ..
+    } = table_attr;
+    let schema_name = match schema {
+        Some(schema) => quote! { Some(#schema) },
+        None => quote! { None },
+    };
     let expr_type = expr_type(column_type_attr.impl_expr, &column_enum_name);
     let result = quote! {
         impl TableType for #ident {
@@ -74,6 +109,12 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
             fn table_name() -> &'static str {
                 #table_name
             }
+            fn schema_name() -> Option<&'static str> {
+                #schema_name
+            }
+            fn qualified_table_name() -> &'static str {
+                #qualified_table_name
+            }
         }
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub enum #column_enum_name {
@@ -81,6 +122,11 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
                 #enum_variants
             ),*
         }
+        impl #column_enum_name {
+            #(
+                #typed_column_consts
+            )*
+        }
         const _: () = {
             impl std::fmt::Display for #column_enum_name {
                 fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -102,6 +148,16 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
                 fn table_name(&self) -> &'static str {
                     <#ident as TableType>::table_name()
                 }
+                fn schema_name(&self) -> Option<&'static str> {
+                    <#ident as TableType>::schema_name()
+                }
+                fn quoted_column_name(&self) -> std::borrow::Cow<'static, str> {
+                    match self {
+                        #(
+                            #quoted_column_match_arms
+                        ),*
+                    }
+                }
                 fn full_name(&self) -> std::borrow::Cow<'static, str> {
                     match self {
                         #(
@@ -109,6 +165,14 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
                         ),*
                     }
                 }
+                fn column_info(&self) -> Option<ColumnInfo> {
+                    let info = match self {
+                        #(
+                            #column_info_match_arms
+                        ),*
+                    };
+                    Some(info)
+                }
             }
             impl From<#column_enum_name> for DynColumn{
                 fn from(column: #column_enum_name) -> Self {
@@ -139,11 +203,29 @@ pub fn expand(input: DeriveInput) -> Result<TokenStream> {
                         ),*
                     ]
                 }
+                fn insertable_columns() -> std::vec::Vec<Self>
+                    where
+                        Self: Sized {
+                    std::vec![
+                        #(
+                            #insertable_columns
+                        ),*
+                    ]
+                }
+                fn updatable_columns() -> std::vec::Vec<Self>
+                    where
+                        Self: Sized {
+                    std::vec![
+                        #(
+                            #updatable_columns
+                        ),*
+                    ]
+                }
             }
             #primary_key
         };
         #expr_type
-
+        #crud
     };
 
     Ok(result)
@@ -162,6 +244,78 @@ fn has_primary_key(
         }
     }
 }
+/// Generated by `#[table(crud)]`
+///
+/// The `*_by_id` methods are only generated if the table has a primary key
+fn crud_methods(
+    primary_key: Option<&ColumnField>,
+    table_type: &syn::Ident,
+    column_enum_name: &syn::Ident,
+) -> TokenStream {
+    let by_id = primary_key.map(|field| {
+        let ty = &field.ty;
+        let column_name = &field.ident_as_upper_camel;
+        quote! {
+            /// Selects the row with the given primary key
+            pub async fn find_by_id<'c, E>(executor: E, id: #ty) -> Result<Option<Self>, sqlx::Error>
+            where
+                E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+            {
+                let mut query = SelectQueryBuilder::with_columns(
+                    <Self as TableType>::qualified_table_name(),
+                    <#column_enum_name as AllColumns>::all(),
+                );
+                query.filter(#column_enum_name::#column_name.equals(id.value()));
+                query.try_query_as()?.fetch_optional(executor).await
+            }
+            /// Deletes the row with the given primary key.
+            ///
+            /// Returns true if a row was deleted
+            pub async fn delete_by_id<'c, E>(executor: E, id: #ty) -> Result<bool, sqlx::Error>
+            where
+                E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+            {
+                let mut query = DeleteQueryBuilder::new(<Self as TableType>::qualified_table_name());
+                query.filter(#column_enum_name::#column_name.equals(id.value()));
+                let result = query.try_query()?.execute(executor).await?;
+                Ok(result.rows_affected() > 0)
+            }
+            /// Checks if a row with the given primary key exists
+            pub async fn exists_by_id<'c, E>(executor: E, id: #ty) -> Result<bool, sqlx::Error>
+            where
+                E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+            {
+                let mut query = SelectExists::new(<Self as TableType>::qualified_table_name());
+                query.filter(#column_enum_name::#column_name.equals(id.value()));
+                query.try_query_scalar()?.fetch_one(executor).await
+            }
+        }
+    });
+    quote! {
+        impl #table_type {
+            /// Selects every row of the table
+            pub async fn find_all<'c, E>(executor: E) -> Result<Vec<Self>, sqlx::Error>
+            where
+                E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+            {
+                let mut query = SelectQueryBuilder::with_columns(
+                    <Self as TableType>::qualified_table_name(),
+                    <#column_enum_name as AllColumns>::all(),
+                );
+                query.try_query_as()?.fetch_all(executor).await
+            }
+            /// Counts the rows of the table
+            pub async fn count<'c, E>(executor: E) -> Result<i64, sqlx::Error>
+            where
+                E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+            {
+                let mut query = SelectCount::new(<Self as TableType>::qualified_table_name());
+                query.try_query_scalar()?.fetch_one(executor).await
+            }
+            #by_id
+        }
+    }
+}
 fn expr_type(implement: bool, column_enum_name: &syn::Ident) -> TokenStream {
     if !implement {
         return TokenStream::default();
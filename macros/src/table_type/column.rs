@@ -1,11 +1,13 @@
+use heck::ToShoutySnakeCase;
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
-    Field, Ident, LitBool, LitStr, Result,
+    Field, Ident, LitBool, LitStr, Result, Type,
+    ext::IdentExt,
     parse::{Parse, ParseStream},
 };
 
-use crate::utils::ident_to_upper_camel;
+use crate::utils::{ident_to_upper_camel, is_option, quote_ident};
 
 use crate::utils::keywords;
 #[derive(Debug, Default)]
@@ -22,6 +24,26 @@ pub struct ColumnAttribute {
     pub skip: bool,
 
     pub primary_key: bool,
+    /// Forces the column name to always be quoted
+    pub quoted: bool,
+    /// The SQL type used in DDL. Overrides the type from `sqlx::Type`
+    pub sql_type: Option<LitStr>,
+    /// The Rust type whose `sqlx::Type` is used for `ColumnInfo`. For fields that do not implement `sqlx::Type`
+    pub type_info: Option<Type>,
+    /// The default value expression used in DDL
+    pub default: Option<LitStr>,
+    /// Adds a UNIQUE constraint in DDL
+    pub unique: bool,
+    /// A check constraint expression used in DDL
+    pub check: Option<LitStr>,
+    /// Identity or computed column. Never inserted or updated
+    pub generated: bool,
+    /// Never inserted or updated
+    pub read_only: bool,
+    /// Inserted but never updated
+    pub insert_only: bool,
+    /// Redacted in debug output
+    pub sensitive: bool,
 }
 impl Parse for ColumnAttribute {
     fn parse(input: ParseStream) -> Result<Self> {
@@ -29,6 +51,16 @@ impl Parse for ColumnAttribute {
         let mut enum_variant: Option<Ident> = None;
         let mut skip = false;
         let mut primary_key = false;
+        let mut quoted = false;
+        let mut sql_type: Option<LitStr> = None;
+        let mut type_info: Option<Type> = None;
+        let mut default: Option<LitStr> = None;
+        let mut unique = false;
+        let mut check: Option<LitStr> = None;
+        let mut generated = false;
+        let mut read_only = false;
+        let mut insert_only = false;
+        let mut sensitive = false;
         while !input.is_empty() {
             let lookahead = input.lookahead1();
             if lookahead.peek(keywords::name) {
@@ -45,12 +77,59 @@ impl Parse for ColumnAttribute {
             } else if lookahead.peek(keywords::primary_key) {
                 let _: keywords::primary_key = input.parse()?;
                 primary_key = true;
+            } else if lookahead.peek(keywords::quoted) {
+                let _: keywords::quoted = input.parse()?;
+                quoted = true;
+            } else if lookahead.peek(keywords::sql_type) {
+                let _: keywords::sql_type = input.parse()?;
+                let _: syn::Token![=] = input.parse()?;
+                sql_type = Some(input.parse()?);
+            } else if lookahead.peek(keywords::type_info) {
+                let _: keywords::type_info = input.parse()?;
+                let _: syn::Token![=] = input.parse()?;
+                type_info = Some(input.parse()?);
+            } else if lookahead.peek(keywords::default) {
+                let _: keywords::default = input.parse()?;
+                let _: syn::Token![=] = input.parse()?;
+                default = Some(input.parse()?);
+            } else if lookahead.peek(keywords::unique) {
+                let _: keywords::unique = input.parse()?;
+                unique = true;
+            } else if lookahead.peek(keywords::check) {
+                let _: keywords::check = input.parse()?;
+                let _: syn::Token![=] = input.parse()?;
+                check = Some(input.parse()?);
+            } else if lookahead.peek(keywords::generated) {
+                let _: keywords::generated = input.parse()?;
+                generated = true;
+            } else if lookahead.peek(keywords::read_only) {
+                let _: keywords::read_only = input.parse()?;
+                read_only = true;
+            } else if lookahead.peek(keywords::insert_only) {
+                let _: keywords::insert_only = input.parse()?;
+                insert_only = true;
+            } else if lookahead.peek(keywords::sensitive) {
+                let _: keywords::sensitive = input.parse()?;
+                sensitive = true;
             } else {
                 return Err(lookahead.error());
             }
+            if input.peek(syn::Token![,]) {
+                input.parse::<syn::Token![,]>()?;
+            }
         }
         Ok(Self {
             primary_key,
+            quoted,
+            sql_type,
+            type_info,
+            default,
+            unique,
+            check,
+            generated,
+            read_only,
+            insert_only,
+            sensitive,
             column_name,
             enum_variant,
             skip,
@@ -65,13 +144,25 @@ pub struct ColumnField {
     pub name: syn::LitStr,
     pub ident_as_upper_camel: syn::Ident,
     pub primary_key: bool,
+    pub quoted: bool,
+    pub sql_type: Option<LitStr>,
+    pub default: Option<LitStr>,
+    pub unique: bool,
+    pub check: Option<LitStr>,
+    pub generated: bool,
+    pub read_only: bool,
+    pub insert_only: bool,
+    pub sensitive: bool,
+    pub ty: Type,
+    /// The type used for `ColumnInfo`. See [type_info_type]
+    pub info_ty: Type,
 }
 
 impl ColumnField {
     pub fn new(field: Field, struct_name: Ident) -> Result<Option<Self>> {
         let ident = field
             .ident
-            .ok_or_else(|| syn::Error::new_spanned(field.ty, "expected named field"))?;
+            .ok_or_else(|| syn::Error::new_spanned(&field.ty, "expected named field"))?;
 
         let column_attr = field
             .attrs
@@ -99,12 +190,27 @@ impl ColumnField {
         } else {
             ident_to_upper_camel(&ident)
         };
+        let info_ty = match column_attr.type_info {
+            Some(ty) => ty,
+            None => type_info_type(&field.attrs, &field.ty)?,
+        };
         let result = Self {
             struct_name,
             ident,
             name,
             ident_as_upper_camel,
             primary_key: column_attr.primary_key,
+            quoted: column_attr.quoted,
+            sql_type: column_attr.sql_type,
+            default: column_attr.default,
+            unique: column_attr.unique,
+            check: column_attr.check,
+            generated: column_attr.generated,
+            read_only: column_attr.read_only,
+            insert_only: column_attr.insert_only,
+            sensitive: column_attr.sensitive,
+            ty: field.ty,
+            info_ty,
         };
         Ok(Some(result))
     }
@@ -120,12 +226,26 @@ impl ColumnField {
     pub fn formatted_column(&self, table_name: &LitStr) -> TokenStream {
         let ident = &self.ident_as_upper_camel;
         let name = &self.name;
-        let full_name = format!("{}.{}", table_name.value(), name.value());
+        let full_name = format!(
+            "{}.{}",
+            table_name.value(),
+            quote_ident(&name.value(), self.quoted)
+        );
         let full_name_lit = LitStr::new(full_name.as_str(), name.span());
         quote! {
             Self::#ident => std::borrow::Cow::Borrowed(#full_name_lit)
         }
     }
+    pub fn quoted_column_name(&self) -> TokenStream {
+        let ident = &self.ident_as_upper_camel;
+        let quoted = LitStr::new(
+            &quote_ident(&self.name.value(), self.quoted),
+            self.name.span(),
+        );
+        quote! {
+            Self::#ident => std::borrow::Cow::Borrowed(#quoted)
+        }
+    }
     pub fn display_match_arm(&self) -> TokenStream {
         let ident = &self.ident_as_upper_camel;
         let name = &self.name;
@@ -140,6 +260,67 @@ impl ColumnField {
             Self::#ident => #name
         }
     }
+    /// A `TypedExpr` constant for the column. Named after the field in SCREAMING_SNAKE_CASE
+    pub fn typed_column_const(&self) -> TokenStream {
+        let ident = &self.ident_as_upper_camel;
+        let const_ident = format_ident!(
+            "{}",
+            self.ident.unraw().to_string().to_shouty_snake_case(),
+            span = self.ident.span()
+        );
+        let ty = &self.ty;
+        let doc_str = format!(
+            "Typed version of [Self::{ident}]. Comparisons, `insert` and `set` will require a value compatible with `{}`",
+            quote!(#ty).to_string().replace(' ', "")
+        );
+        quote! {
+            #[doc = #doc_str]
+            pub const #const_ident: TypedExpr<'static, #ty, Self> = TypedExpr::new(Self::#ident);
+        }
+    }
+    /// Match arm for `ColumnType::column_info`
+    pub fn column_info_match_arm(&self) -> TokenStream {
+        let ident = &self.ident_as_upper_camel;
+        let name = &self.name;
+        let ty = &self.ty;
+        let info_ty = &self.info_ty;
+        let rust_type = quote!(#ty).to_string().replace(' ', "");
+        let nullable = is_option(ty);
+        let primary_key = self.primary_key;
+        let sql_type = self
+            .sql_type
+            .as_ref()
+            .map(|sql_type| quote!(.with_sql_type(#sql_type)));
+        let default = self
+            .default
+            .as_ref()
+            .map(|default| quote!(.with_default(#default)));
+        let unique = self.unique.then(|| quote!(.with_unique()));
+        let check = self.check.as_ref().map(|check| quote!(.with_check(#check)));
+        let generated = self.generated.then(|| quote!(.with_generated()));
+        let read_only = self.read_only.then(|| quote!(.with_read_only()));
+        let insert_only = self.insert_only.then(|| quote!(.with_insert_only()));
+        let sensitive = self.sensitive.then(|| quote!(.with_sensitive()));
+        quote! {
+            Self::#ident => ColumnInfo::new::<#info_ty>(#name, #rust_type, #nullable, #primary_key)
+                #sql_type
+                #default
+                #unique
+                #check
+                #generated
+                #read_only
+                #insert_only
+                #sensitive
+        }
+    }
+    /// Generated and read only columns are never inserted
+    pub fn is_insertable(&self) -> bool {
+        !self.generated && !self.read_only
+    }
+    /// Insert only columns are also never updated
+    pub fn is_updatable(&self) -> bool {
+        self.is_insertable() && !self.insert_only
+    }
     pub fn column_type_all(&self) -> TokenStream {
         let ident = &self.ident_as_upper_camel;
         quote! {
@@ -147,6 +328,33 @@ impl ColumnField {
         }
     }
 }
+/// The type whose `sqlx::Type` describes the column.
+///
+/// Follows the `#[sqlx(try_from = "T")]` and `#[sqlx(json)]` attributes of `sqlx::FromRow`.
+/// Otherwise the type of the field
+fn type_info_type(attrs: &[syn::Attribute], ty: &Type) -> Result<Type> {
+    let mut info_ty = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("sqlx")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("try_from") {
+                let try_from: LitStr = meta.value()?.parse()?;
+                info_ty = Some(try_from.parse()?);
+            } else if meta.path.is_ident("json") {
+                info_ty = Some(syn::parse_quote!(sqlx::types::Json<#ty>));
+            }
+            // Skip the arguments of every other attribute
+            if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let _: TokenStream = content.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(info_ty.unwrap_or_else(|| ty.clone()))
+}
 
 pub struct ColumnTypeAttribute {
     /// By Default true, if set to false, the column type will not implement ExprType
@@ -179,6 +387,9 @@ impl Parse for ColumnTypeAttribute {
             } else {
                 return Err(lookahead.error());
             }
+            if input.peek(syn::Token![,]) {
+                input.parse::<syn::Token![,]>()?;
+            }
         }
         Ok(Self {
             impl_expr: impl_expr.is_none_or(|lit| lit.value),
@@ -3,27 +3,76 @@ use syn::{
     parse::{Parse, ParseStream},
 };
 
-use crate::utils::keywords;
+use crate::utils::{keywords, quote_ident};
 
 pub struct TableAttr {
     pub name: LitStr,
+    /// The Postgres schema the table is in. i.e. `auth`
+    pub schema: Option<LitStr>,
+    /// Forces the table name and schema to always be quoted
+    pub quoted: bool,
+    /// Generates `find_all`, `count` and the `*_by_id` methods
+    pub crud: bool,
+}
+impl TableAttr {
+    /// The table name without the schema. Quoted if required
+    ///
+    /// Columns are prefixed with this so they work with both the plain and the qualified table name
+    pub fn quoted_name(&self) -> LitStr {
+        LitStr::new(
+            &quote_ident(&self.name.value(), self.quoted),
+            self.name.span(),
+        )
+    }
+    /// `{schema}.{name}` or just `{name}` if no schema is set
+    ///
+    /// Each part is quoted if required
+    pub fn qualified_name(&self) -> LitStr {
+        let name = self.quoted_name().value();
+        let qualified = match &self.schema {
+            Some(schema) => format!("{}.{}", quote_ident(&schema.value(), self.quoted), name),
+            None => name,
+        };
+        LitStr::new(&qualified, self.name.span())
+    }
 }
 
 impl Parse for TableAttr {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut table_name: Option<LitStr> = None;
+        let mut schema: Option<LitStr> = None;
+        let mut quoted = false;
+        let mut crud = false;
         while !input.is_empty() {
             let lookahead = input.lookahead1();
             if lookahead.peek(keywords::name) {
                 let _: keywords::name = input.parse()?;
                 let _: syn::Token![=] = input.parse()?;
                 table_name = Some(input.parse()?);
+            } else if lookahead.peek(keywords::schema) {
+                let _: keywords::schema = input.parse()?;
+                let _: syn::Token![=] = input.parse()?;
+                schema = Some(input.parse()?);
+            } else if lookahead.peek(keywords::quoted) {
+                let _: keywords::quoted = input.parse()?;
+                quoted = true;
+            } else if lookahead.peek(keywords::crud) {
+                let _: keywords::crud = input.parse()?;
+                crud = true;
             } else {
                 return Err(lookahead.error());
             }
+            if input.peek(syn::Token![,]) {
+                input.parse::<syn::Token![,]>()?;
+            }
         }
         let name =
             table_name.ok_or_else(|| syn::Error::new(input.span(), "table name is required"))?;
-        Ok(Self { name })
+        Ok(Self {
+            name,
+            schema,
+            quoted,
+            crud,
+        })
     }
 }
@@ -1,10 +1,227 @@
 use heck::ToUpperCamelCase;
-use syn::spanned::Spanned;
+use quote::format_ident;
+use syn::{
+    Field, Ident, Path, Result, Token,
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+};
 
 pub fn ident_to_upper_camel(ident: &syn::Ident) -> syn::Ident {
     let ident = ident.to_string().to_upper_camel_case();
     syn::Ident::new(&ident, ident.span())
 }
+/// Keywords that are reserved in Postgres.
+///
+/// Copy of `pg_extended_sqlx_queries::table_layout::RESERVED_KEYWORDS`.
+/// A test in that module checks that the lists are equal
+const RESERVED_KEYWORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+/// Same rules as `pg_extended_sqlx_queries::table_layout::needs_quoting`
+pub fn needs_quoting(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    let Some(first) = chars.next() else {
+        return true;
+    };
+    if !(first.is_ascii_lowercase() || first == '_') {
+        return true;
+    }
+    if !chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$') {
+        return true;
+    }
+    RESERVED_KEYWORDS.binary_search(&ident).is_ok()
+}
+/// Quotes the identifier if `force` is true or [needs_quoting] returns true
+pub fn quote_ident(ident: &str, force: bool) -> String {
+    if force || needs_quoting(ident) {
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    } else {
+        ident.to_owned()
+    }
+}
+/// If the type is an `Option<T>`
+pub fn is_option(ty: &syn::Type) -> bool {
+    let syn::Type::Path(type_path) = ty else {
+        return false;
+    };
+    type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Option")
+}
+/// The `T` of an `Option<T>`
+pub fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    if !is_option(ty) {
+        return None;
+    }
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+/// `{Table}Column` just like the TableType derive. Unless `column_enum_name` was set
+pub fn column_enum_path(table: &Path, column_enum: Option<&Path>) -> Path {
+    if let Some(column_enum) = column_enum {
+        return column_enum.clone();
+    }
+    let mut path = table.clone();
+    if let Some(last) = path.segments.last_mut() {
+        last.ident = format_ident!("{}Column", last.ident);
+    }
+    path
+}
+/// The `#[column(...)]` attribute of an Insertable or Changeset field
+#[derive(Default)]
+pub struct FieldAttr {
+    pub enum_variant: Option<Ident>,
+    pub skip: bool,
+    /// `#[column(default)]`
+    pub default: bool,
+}
+impl FieldAttr {
+    pub fn from_field(field: &Field) -> Result<Self> {
+        field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("column"))
+            .map(|attr| attr.parse_args::<Self>())
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+}
+impl Parse for FieldAttr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut result = Self::default();
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            if lookahead.peek(keywords::column_variant_name) {
+                let _: keywords::column_variant_name = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                result.enum_variant = Some(input.parse()?);
+            } else if lookahead.peek(keywords::skip) {
+                let _: keywords::skip = input.parse()?;
+                result.skip = true;
+            } else if lookahead.peek(keywords::default) {
+                let _: keywords::default = input.parse()?;
+                result.default = true;
+            } else {
+                return Err(lookahead.error());
+            }
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(result)
+    }
+}
 pub mod keywords {
     syn::custom_keyword!(name);
     syn::custom_keyword!(skip);
@@ -12,4 +229,18 @@ pub mod keywords {
     syn::custom_keyword!(column_enum_name);
     syn::custom_keyword!(impl_expr);
     syn::custom_keyword!(primary_key);
+    syn::custom_keyword!(schema);
+    syn::custom_keyword!(quoted);
+    syn::custom_keyword!(default);
+    syn::custom_keyword!(unique);
+    syn::custom_keyword!(check);
+    syn::custom_keyword!(sql_type);
+    syn::custom_keyword!(type_info);
+    syn::custom_keyword!(crud);
+    syn::custom_keyword!(generated);
+    syn::custom_keyword!(read_only);
+    syn::custom_keyword!(sensitive);
+    syn::custom_keyword!(insert_only);
+    syn::custom_keyword!(table);
+    syn::custom_keyword!(conflict);
 }
@@ -0,0 +1,108 @@
+//! Compares schema snapshots and prints the migration script
+//!
+//! ```text
+//! schema-diff diff --desired desired.json (--current current.json | --database-url URL) [--schema public] [--fail-on-destructive]
+//! schema-diff dump --database-url URL [--schema public]
+//! ```
+//!
+//! The desired snapshot is generated by the application with [SchemaSnapshot::to_json]
+use std::process::ExitCode;
+
+use pg_extended_sqlx_queries::schema::{DEFAULT_SCHEMA, SchemaDiff, SchemaSnapshot};
+use sqlx::PgPool;
+
+const USAGE: &str = "Usage:
+  schema-diff diff --desired <FILE> (--current <FILE> | --database-url <URL>) [--schema <SCHEMA>]... [--fail-on-destructive]
+  schema-diff dump --database-url <URL> [--schema <SCHEMA>]...";
+
+#[derive(Debug, Default)]
+struct Args {
+    command: String,
+    desired: Option<String>,
+    current: Option<String>,
+    database_url: Option<String>,
+    schemas: Vec<String>,
+    fail_on_destructive: bool,
+}
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = std::env::args().skip(1);
+        let mut result = Args {
+            command: args.next().ok_or("Missing command")?,
+            ..Default::default()
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+            match arg.as_str() {
+                "--desired" => result.desired = Some(value()?),
+                "--current" => result.current = Some(value()?),
+                "--database-url" => result.database_url = Some(value()?),
+                "--schema" => result.schemas.push(value()?),
+                "--fail-on-destructive" => result.fail_on_destructive = true,
+                other => return Err(format!("Unknown argument {other}")),
+            }
+        }
+        if result.schemas.is_empty() {
+            result.schemas.push(DEFAULT_SCHEMA.to_owned());
+        }
+        Ok(result)
+    }
+    async fn load_database(&self) -> Result<SchemaSnapshot, String> {
+        let url = self
+            .database_url
+            .as_deref()
+            .ok_or("Missing --database-url")?;
+        let pool = PgPool::connect(url).await.map_err(|err| err.to_string())?;
+        let schemas: Vec<&str> = self.schemas.iter().map(String::as_str).collect();
+        SchemaSnapshot::load(&pool, &schemas)
+            .await
+            .map_err(|err| err.to_string())
+    }
+}
+fn read_snapshot(path: &str) -> Result<SchemaSnapshot, String> {
+    let json = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    SchemaSnapshot::from_json(&json).map_err(|err| format!("{path}: {err}"))
+}
+async fn run(args: Args) -> Result<ExitCode, String> {
+    match args.command.as_str() {
+        "diff" => {
+            let desired = read_snapshot(args.desired.as_deref().ok_or("Missing --desired")?)?;
+            let current = match &args.current {
+                Some(path) => read_snapshot(path)?,
+                None => args.load_database().await?,
+            };
+            let diff = SchemaDiff::between(&current, &desired);
+            print!("{}", diff.to_script());
+            if diff.has_steps_requiring_empty_tables() {
+                eprintln!(
+                    "The migration adds NOT NULL columns without a default. It fails if the tables have rows"
+                );
+            }
+            if args.fail_on_destructive && diff.has_destructive_steps() {
+                eprintln!("The migration contains destructive steps");
+                return Ok(ExitCode::FAILURE);
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        "dump" => {
+            let snapshot = args.load_database().await?;
+            println!("{}", snapshot.to_json().map_err(|err| err.to_string())?);
+            Ok(ExitCode::SUCCESS)
+        }
+        other => Err(format!("Unknown command {other}")),
+    }
+}
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let result = match Args::parse() {
+        Ok(args) => run(args).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
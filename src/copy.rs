@@ -0,0 +1,450 @@
+//! Bulk loading with `COPY ... FROM STDIN` and exporting with `COPY ... TO STDOUT`
+//!
+//! Much faster than an [InsertManyBuilder](crate::insert::many::InsertManyBuilder) for large amounts of rows
+//! because no placeholders are used.
+//!
+//! ```
+//! use pg_extended_sqlx_queries::{copy::*, prelude::*};
+//! use sqlx::error::BoxDynError;
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     #[column(primary_key, generated)]
+//!     pub id: i32,
+//!     pub name: String,
+//!     pub email: Option<String>,
+//! }
+//! impl CopyRow<UserColumn> for User {
+//!     fn write_column(&self, column: &UserColumn, field: CopyField<'_>) -> Result<(), BoxDynError> {
+//!         match column {
+//!             UserColumn::Id => field.value(&self.id),
+//!             UserColumn::Name => field.value(&self.name),
+//!             UserColumn::Email => field.value(&self.email),
+//!         }
+//!     }
+//! }
+//! let mut copy = CopyIn::<User>::new(UserColumn::all());
+//! copy.format(CopyFormat::Text);
+//! assert_eq!(copy.statement(), "COPY users (name, email) FROM STDIN (FORMAT text)");
+//!
+//! let mut encoder = copy.encoder();
+//! encoder
+//!     .write_row(&User { id: 0, name: "Wyatt\tH".to_owned(), email: None })
+//!     .unwrap();
+//! assert_eq!(encoder.finish(), b"Wyatt\\tH\t\\N\n");
+//! // With a connection
+//! // let rows = copy.execute(&mut conn, users).await?;
+//! ```
+use std::{borrow::Cow, fmt::Debug, marker::PhantomData};
+
+use sqlx::{
+    Encode, PgConnection, Postgres, Type, encode::IsNull, error::BoxDynError,
+    postgres::PgArgumentBuffer,
+};
+use tracing::debug;
+
+use crate::{
+    error::Error,
+    expr::text_value,
+    prelude::{ColumnType, TableType},
+    table_layout::{concat_columns_no_table_name, quote_table_reference},
+    telemetry::trace_query,
+};
+mod out;
+pub use out::*;
+
+/// Signature of the binary format
+const BINARY_SIGNATURE: &[u8; 11] = b"PGCOPY\n\xff\r\n\0";
+/// The buffered data is sent once it is larger than this
+const SEND_SIZE: usize = 64 * 1024;
+
+/// The data format of a `COPY`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyFormat {
+    /// Tab separated text. Values are written with the text output of their type.
+    ///
+    /// Only builtin types, one dimensional arrays of builtin types and types declared by name can be written
+    Text,
+    /// The binary encoding sqlx uses for bound values. The value types must match the column types exactly
+    #[default]
+    Binary,
+}
+impl CopyFormat {
+    fn sql(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Binary => "binary",
+        }
+    }
+}
+/// Writes the values of a row for [CopyIn]
+///
+/// Called once per column in the order of the columns of the `COPY`
+pub trait CopyRow<C: ColumnType> {
+    fn write_column(&self, column: &C, field: CopyField<'_>) -> Result<(), BoxDynError>;
+}
+impl<C, R> CopyRow<C> for &R
+where
+    C: ColumnType,
+    R: CopyRow<C>,
+{
+    fn write_column(&self, column: &C, field: CopyField<'_>) -> Result<(), BoxDynError> {
+        (*self).write_column(column, field)
+    }
+}
+/// A single value of a row
+pub struct CopyField<'a> {
+    format: CopyFormat,
+    defaults: bool,
+    buffer: &'a mut Vec<u8>,
+    written: &'a mut bool,
+}
+impl CopyField<'_> {
+    /// Writes the value. `None` is written as NULL
+    pub fn value<'q, T>(self, value: &T) -> Result<(), BoxDynError>
+    where
+        T: Encode<'q, Postgres> + Type<Postgres>,
+    {
+        let mut encoded = PgArgumentBuffer::default();
+        let is_null = value.encode_by_ref(&mut encoded)?;
+        if let IsNull::Yes = is_null {
+            return self.null();
+        }
+        match self.format {
+            CopyFormat::Binary => {
+                let length = i32::try_from(encoded.len())?;
+                self.buffer.extend_from_slice(&length.to_be_bytes());
+                self.buffer.extend_from_slice(&encoded);
+            }
+            CopyFormat::Text => {
+                let type_info = value.produces().unwrap_or_else(T::type_info);
+                let text = text_value(&type_info, &encoded).ok_or_else(|| {
+                    format!("{type_info} can not be written in the text COPY format")
+                })?;
+                escape_text(&text, self.buffer);
+            }
+        }
+        *self.written = true;
+        Ok(())
+    }
+    pub fn null(self) -> Result<(), BoxDynError> {
+        match self.format {
+            CopyFormat::Binary => self.buffer.extend_from_slice(&(-1i32).to_be_bytes()),
+            CopyFormat::Text => self.buffer.extend_from_slice(b"\\N"),
+        }
+        *self.written = true;
+        Ok(())
+    }
+    /// Uses the default of the column.
+    ///
+    /// Requires [CopyIn::allow_defaults] and the text format
+    pub fn default(self) -> Result<(), BoxDynError> {
+        if self.format != CopyFormat::Text || !self.defaults {
+            return Err("DEFAULT values require the text format and allow_defaults()".into());
+        }
+        self.buffer.extend_from_slice(b"\\D");
+        *self.written = true;
+        Ok(())
+    }
+}
+/// Escapes the text for the text format
+fn escape_text(text: &str, buffer: &mut Vec<u8>) {
+    for byte in text.bytes() {
+        match byte {
+            b'\\' => buffer.extend_from_slice(b"\\\\"),
+            b'\n' => buffer.extend_from_slice(b"\\n"),
+            b'\r' => buffer.extend_from_slice(b"\\r"),
+            b'\t' => buffer.extend_from_slice(b"\\t"),
+            byte => buffer.push(byte),
+        }
+    }
+}
+/// `COPY {table} ({columns}) FROM STDIN`
+pub struct CopyIn<T: TableType> {
+    columns: Vec<T::Columns>,
+    format: CopyFormat,
+    defaults: bool,
+    table: PhantomData<T>,
+}
+impl<T: TableType> Debug for CopyIn<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyIn")
+            .field("table", &T::qualified_table_name())
+            .field("columns", &self.columns)
+            .field("format", &self.format)
+            .field("defaults", &self.defaults)
+            .finish()
+    }
+}
+impl<T: TableType> CopyIn<T> {
+    /// Generated and read only columns are removed from `columns`.
+    /// The database uses the default for every column that is not copied
+    pub fn new(columns: impl Into<Vec<T::Columns>>) -> Self {
+        let mut columns = columns.into();
+        columns.retain(|column| column.column_info().is_none_or(|info| info.is_insertable()));
+        Self {
+            columns,
+            format: CopyFormat::default(),
+            defaults: false,
+            table: PhantomData,
+        }
+    }
+    pub fn format(&mut self, format: CopyFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+    /// Adds `DEFAULT '\D'` so [CopyField::default] can be used. Requires Postgres 16 and the text format
+    pub fn allow_defaults(&mut self) -> &mut Self {
+        self.defaults = true;
+        self
+    }
+    fn table_name(&self) -> Cow<'static, str> {
+        quote_table_reference(T::qualified_table_name())
+    }
+    pub fn statement(&self) -> String {
+        let mut options = format!("FORMAT {}", self.format.sql());
+        if self.defaults && self.format == CopyFormat::Text {
+            options.push_str(", DEFAULT '\\D'");
+        }
+        format!(
+            "COPY {} ({}) FROM STDIN ({options})",
+            self.table_name(),
+            concat_columns_no_table_name(&self.columns)
+        )
+    }
+    pub fn encoder(&self) -> CopyEncoder<'_, T::Columns> {
+        CopyEncoder::new(&self.columns, self.format, self.defaults)
+    }
+    /// Copies the rows. Returns the number of rows that were inserted
+    ///
+    /// The `COPY` is aborted if a row fails to encode
+    pub async fn execute<R, I>(&self, conn: &mut PgConnection, rows: I) -> Result<u64, sqlx::Error>
+    where
+        R: CopyRow<T::Columns>,
+        I: IntoIterator<Item = R>,
+    {
+        let statement = self.statement();
+        let table = self.table_name();
+        let copy = async {
+            let mut copy = conn.copy_in_raw(&statement).await?;
+            let mut encoder = self.encoder();
+            for row in rows {
+                if let Err(err) = encoder.write_row(&row) {
+                    copy.abort(err.to_string()).await?;
+                    return Err(err.into());
+                }
+                if encoder.buffered() >= SEND_SIZE {
+                    copy.send(encoder.take_buffer()).await?;
+                }
+            }
+            debug!(rows = encoder.rows(), "CopyIn::execute");
+            copy.send(encoder.finish()).await?;
+            copy.finish().await
+        };
+        trace_query(Some("COPY"), Some(&table), &statement, copy, |rows| *rows).await
+    }
+}
+/// Encodes rows into the data of a `COPY FROM STDIN`
+#[derive(Debug)]
+pub struct CopyEncoder<'c, C: ColumnType> {
+    columns: &'c [C],
+    format: CopyFormat,
+    defaults: bool,
+    buffer: Vec<u8>,
+    rows: u64,
+}
+impl<'c, C: ColumnType> CopyEncoder<'c, C> {
+    pub fn new(columns: &'c [C], format: CopyFormat, defaults: bool) -> Self {
+        let mut buffer = Vec::new();
+        if format == CopyFormat::Binary {
+            buffer.extend_from_slice(BINARY_SIGNATURE);
+            // Flags and the length of the header extension
+            buffer.extend_from_slice(&0i32.to_be_bytes());
+            buffer.extend_from_slice(&0i32.to_be_bytes());
+        }
+        Self {
+            columns,
+            format,
+            defaults,
+            buffer,
+            rows: 0,
+        }
+    }
+    /// Encodes the row. Nothing is written if the row fails
+    pub fn write_row<R>(&mut self, row: &R) -> Result<(), Error>
+    where
+        R: CopyRow<C>,
+    {
+        let start = self.buffer.len();
+        let result = self.write_row_inner(row);
+        if result.is_err() {
+            self.buffer.truncate(start);
+        } else {
+            self.rows += 1;
+        }
+        result
+    }
+    fn write_row_inner<R>(&mut self, row: &R) -> Result<(), Error>
+    where
+        R: CopyRow<C>,
+    {
+        if self.format == CopyFormat::Binary {
+            // Postgres tables have at most 1600 columns
+            let count = self.columns.len() as i16;
+            self.buffer.extend_from_slice(&count.to_be_bytes());
+        }
+        for (index, column) in self.columns.iter().enumerate() {
+            if index > 0 && self.format == CopyFormat::Text {
+                self.buffer.push(b'\t');
+            }
+            let mut written = false;
+            let field = CopyField {
+                format: self.format,
+                defaults: self.defaults,
+                buffer: &mut self.buffer,
+                written: &mut written,
+            };
+            let result = row.write_column(column, field).and_then(|_| {
+                if written {
+                    Ok(())
+                } else {
+                    Err("No value was written".into())
+                }
+            });
+            result.map_err(|error| Error::CopyValue {
+                column: column.column_name(),
+                error,
+            })?;
+        }
+        if self.format == CopyFormat::Text {
+            self.buffer.push(b'\n');
+        }
+        Ok(())
+    }
+    /// The number of rows written
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+    /// Bytes waiting to be sent
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+    /// Takes the encoded data. The encoder can keep writing rows afterwards
+    pub fn take_buffer(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buffer)
+    }
+    /// The remaining data including the end of the binary format
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.format == CopyFormat::Binary {
+            self.buffer.extend_from_slice(&(-1i16).to_be_bytes());
+        }
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset};
+    use sqlx::error::BoxDynError;
+
+    use super::*;
+    use crate::{
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    impl CopyRow<TestTableColumn> for TestTable {
+        fn write_column(
+            &self,
+            column: &TestTableColumn,
+            field: CopyField<'_>,
+        ) -> Result<(), BoxDynError> {
+            match column {
+                TestTableColumn::Id => field.value(&self.id),
+                TestTableColumn::FirstName => field.value(&self.first_name),
+                TestTableColumn::LastName => field.value(&self.last_name),
+                TestTableColumn::Age => field.value(&self.age),
+                TestTableColumn::Email => field.value(&self.email),
+                TestTableColumn::Phone => field.value(&self.phone),
+                TestTableColumn::AnotherTableId => field.value(&self.another_table_id),
+                TestTableColumn::UpdatedAt => field.value(&self.updated_at),
+                TestTableColumn::CreatedAt => field.default(),
+            }
+        }
+    }
+    fn row() -> TestTable {
+        let time = DateTime::<FixedOffset>::parse_from_rfc3339("2024-02-29T10:00:00Z").unwrap();
+        TestTable {
+            id: 1,
+            first_name: "Wyatt".to_owned(),
+            last_name: "Back\\slash\nNew line".to_owned(),
+            age: 30,
+            email: "wyatt@example.com".to_owned(),
+            phone: "555-0100".to_owned(),
+            another_table_id: None,
+            updated_at: time,
+            created_at: time,
+        }
+    }
+    #[test]
+    fn statement() {
+        let mut copy = CopyIn::<TestTable>::new(TestTableColumn::all());
+        assert_eq!(
+            copy.statement(),
+            "COPY test_table (first_name, last_name, age, email, phone, another_table_id, updated_at, created_at) FROM STDIN (FORMAT binary)"
+        );
+        copy.format(CopyFormat::Text).allow_defaults();
+        assert!(
+            copy.statement()
+                .ends_with("FROM STDIN (FORMAT text, DEFAULT '\\D')")
+        );
+    }
+    #[test]
+    fn text_rows() {
+        let mut copy = CopyIn::<TestTable>::new(TestTableColumn::all());
+        copy.format(CopyFormat::Text).allow_defaults();
+        let mut encoder = copy.encoder();
+        encoder.write_row(&row()).unwrap();
+        assert_eq!(encoder.rows(), 1);
+        assert_eq!(
+            String::from_utf8(encoder.finish()).unwrap(),
+            "Wyatt\tBack\\\\slash\\nNew line\t30\twyatt@example.com\t555-0100\t\\N\t2024-02-29 10:00:00.000000+00\t\\D\n"
+        );
+    }
+    #[test]
+    fn binary_rows() {
+        let copy =
+            CopyIn::<TestTable>::new([TestTableColumn::Age, TestTableColumn::AnotherTableId]);
+        let mut encoder = copy.encoder();
+        encoder.write_row(&row()).unwrap();
+        let data = encoder.finish();
+
+        let mut expected = BINARY_SIGNATURE.to_vec();
+        expected.extend_from_slice(&[0; 8]);
+        expected.extend_from_slice(&2i16.to_be_bytes());
+        expected.extend_from_slice(&4i32.to_be_bytes());
+        expected.extend_from_slice(&30i32.to_be_bytes());
+        expected.extend_from_slice(&(-1i32).to_be_bytes());
+        expected.extend_from_slice(&(-1i16).to_be_bytes());
+        assert_eq!(data, expected);
+    }
+    #[test]
+    fn failed_rows_are_not_written() {
+        // DEFAULT is not allowed in the binary format
+        let copy = CopyIn::<TestTable>::new([TestTableColumn::Age, TestTableColumn::CreatedAt]);
+        let mut encoder = copy.encoder();
+        let header = encoder.buffered();
+        let Err(err) = encoder.write_row(&row()) else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::CopyValue {
+                column: "created_at",
+                ..
+            }
+        ));
+        assert_eq!(encoder.buffered(), header);
+        assert_eq!(encoder.rows(), 0);
+    }
+}
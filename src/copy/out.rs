@@ -0,0 +1,389 @@
+use std::{borrow::Cow, fmt::Debug, future::poll_fn, marker::PhantomData};
+
+use sqlx::{PgConnection, Postgres, Type, postgres::PgTypeInfo};
+use tracing::debug;
+
+use super::{BINARY_SIGNATURE, CopyFormat};
+use crate::{
+    error::Error,
+    expr::text_value,
+    prelude::{ColumnType, TableType},
+    table_layout::{concat_columns_no_table_name, quote_table_reference},
+    telemetry::trace_query,
+};
+
+/// `COPY {table} ({columns}) TO STDOUT`
+pub struct CopyOut<T: TableType> {
+    columns: Vec<T::Columns>,
+    format: CopyFormat,
+    table: PhantomData<T>,
+}
+impl<T: TableType> Debug for CopyOut<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyOut")
+            .field("table", &T::qualified_table_name())
+            .field("columns", &self.columns)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+impl<T: TableType> CopyOut<T> {
+    pub fn new(columns: impl Into<Vec<T::Columns>>) -> Self {
+        Self {
+            columns: columns.into(),
+            format: CopyFormat::default(),
+            table: PhantomData,
+        }
+    }
+    pub fn format(&mut self, format: CopyFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+    fn table_name(&self) -> Cow<'static, str> {
+        quote_table_reference(T::qualified_table_name())
+    }
+    pub fn statement(&self) -> String {
+        format!(
+            "COPY {} ({}) TO STDOUT (FORMAT {})",
+            self.table_name(),
+            concat_columns_no_table_name(&self.columns),
+            self.format.sql()
+        )
+    }
+    pub fn decoder(&self) -> CopyDecoder {
+        let types = self
+            .columns
+            .iter()
+            .map(|column| {
+                column
+                    .column_info()
+                    .map(|info| info.pg_type)
+                    .unwrap_or_else(<String as Type<Postgres>>::type_info)
+            })
+            .collect();
+        CopyDecoder::new(types, self.format)
+    }
+    /// Copies every row of the table
+    pub async fn fetch_all(&self, conn: &mut PgConnection) -> Result<Vec<CopyOutRow>, sqlx::Error> {
+        let statement = self.statement();
+        let table = self.table_name();
+        let copy = async {
+            let mut stream = conn.copy_out_raw(&statement).await?;
+            let mut decoder = self.decoder();
+            let mut rows = Vec::new();
+            while let Some(data) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                rows.extend(decoder.decode(&data?)?);
+            }
+            decoder.finish()?;
+            debug!(rows = rows.len(), "CopyOut::fetch_all");
+            Ok(rows)
+        };
+        trace_query(Some("COPY"), Some(&table), &statement, copy, |rows| {
+            rows.len() as u64
+        })
+        .await
+    }
+}
+/// A row of a `COPY TO STDOUT`. The values are in the order of the columns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyOutRow {
+    values: Vec<Option<String>>,
+}
+impl CopyOutRow {
+    /// The text output of the value. None if NULL
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.values.get(index)?.as_deref()
+    }
+    /// Parses the text output of the value. None if NULL
+    pub fn parse<V>(&self, index: usize) -> Result<Option<V>, V::Err>
+    where
+        V: std::str::FromStr,
+    {
+        self.get(index).map(str::parse).transpose()
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    pub fn into_values(self) -> Vec<Option<String>> {
+        self.values
+    }
+}
+/// Decodes the data of a `COPY TO STDOUT` into [CopyOutRow]s.
+///
+/// Binary values are converted to their text output with the types of the columns
+#[derive(Debug)]
+pub struct CopyDecoder {
+    types: Vec<PgTypeInfo>,
+    format: CopyFormat,
+    pending: Vec<u8>,
+    header_read: bool,
+    done: bool,
+}
+impl CopyDecoder {
+    pub fn new(types: Vec<PgTypeInfo>, format: CopyFormat) -> Self {
+        Self {
+            types,
+            format,
+            pending: Vec::new(),
+            header_read: false,
+            done: false,
+        }
+    }
+    /// Decodes the rows that are complete. The rest is kept for the next call
+    pub fn decode(&mut self, data: &[u8]) -> Result<Vec<CopyOutRow>, Error> {
+        self.pending.extend_from_slice(data);
+        let mut rows = Vec::new();
+        let consumed = match self.format {
+            CopyFormat::Text => self.decode_text(&mut rows)?,
+            CopyFormat::Binary => self.decode_binary(&mut rows)?,
+        };
+        self.pending.drain(..consumed);
+        Ok(rows)
+    }
+    /// Errors if the data ended in the middle of a row
+    pub fn finish(self) -> Result<(), Error> {
+        let incomplete = match self.format {
+            CopyFormat::Text => !self.pending.is_empty(),
+            CopyFormat::Binary => !self.done || !self.pending.is_empty(),
+        };
+        if incomplete {
+            return Err(Error::InvalidCopyData(
+                "Data ended in the middle of a row".into(),
+            ));
+        }
+        Ok(())
+    }
+    fn decode_text(&self, rows: &mut Vec<CopyOutRow>) -> Result<usize, Error> {
+        let mut consumed = 0;
+        while let Some(end) = self.pending[consumed..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+        {
+            let line = &self.pending[consumed..consumed + end];
+            consumed += end + 1;
+            let values = line
+                .split(|byte| *byte == b'\t')
+                .map(unescape_text)
+                .collect::<Result<Vec<_>, _>>()?;
+            self.check_width(values.len())?;
+            rows.push(CopyOutRow { values });
+        }
+        Ok(consumed)
+    }
+    fn decode_binary(&mut self, rows: &mut Vec<CopyOutRow>) -> Result<usize, Error> {
+        let mut reader = BinaryReader {
+            data: &self.pending,
+            offset: 0,
+        };
+        if !self.header_read {
+            let Some(signature) = reader.take(BINARY_SIGNATURE.len()) else {
+                return Ok(0);
+            };
+            if signature != BINARY_SIGNATURE {
+                return Err(Error::InvalidCopyData("Missing binary signature".into()));
+            }
+            let (Some(_flags), Some(extension)) = (reader.i32(), reader.i32()) else {
+                return Ok(0);
+            };
+            if reader.take(extension.max(0) as usize).is_none() {
+                return Ok(0);
+            }
+            self.header_read = true;
+        }
+        let mut consumed = reader.offset;
+        while !self.done {
+            let Some(count) = reader.i16() else {
+                break;
+            };
+            if count == -1 {
+                self.done = true;
+                consumed = reader.offset;
+                break;
+            }
+            self.check_width(count.max(0) as usize)?;
+            let Some(values) = self
+                .types
+                .iter()
+                .map(|type_info| reader.value(type_info))
+                .collect::<Option<Result<Vec<_>, _>>>()
+            else {
+                break;
+            };
+            rows.push(CopyOutRow { values: values? });
+            consumed = reader.offset;
+        }
+        Ok(consumed)
+    }
+    fn check_width(&self, width: usize) -> Result<(), Error> {
+        if width != self.types.len() {
+            return Err(Error::InvalidCopyData(format!(
+                "Expected {} columns but the row has {width}",
+                self.types.len()
+            )));
+        }
+        Ok(())
+    }
+}
+/// Reads the binary format. Every method returns None if the data is incomplete
+struct BinaryReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+impl<'a> BinaryReader<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + length)?;
+        self.offset += length;
+        Some(bytes)
+    }
+    fn i16(&mut self) -> Option<i16> {
+        Some(i16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+    fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+    fn value(&mut self, type_info: &PgTypeInfo) -> Option<Result<Option<String>, Error>> {
+        let length = self.i32()?;
+        if length < 0 {
+            return Some(Ok(None));
+        }
+        let bytes = self.take(length as usize)?;
+        let value = text_value(type_info, bytes).ok_or_else(|| {
+            Error::InvalidCopyData(format!("{type_info} can not be converted to text"))
+        });
+        Some(value.map(Some))
+    }
+}
+/// Reverses the escaping of the text format. `\N` is NULL
+fn unescape_text(field: &[u8]) -> Result<Option<String>, Error> {
+    if field == b"\\N" {
+        return Ok(None);
+    }
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut iter = field.iter().copied().peekable();
+    while let Some(byte) = iter.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let Some(escaped) = iter.next() else {
+            return Err(Error::InvalidCopyData("Field ends with a backslash".into()));
+        };
+        match escaped {
+            b'b' => bytes.push(0x08),
+            b'f' => bytes.push(0x0C),
+            b'n' => bytes.push(b'\n'),
+            b'r' => bytes.push(b'\r'),
+            b't' => bytes.push(b'\t'),
+            b'v' => bytes.push(0x0B),
+            b'0'..=b'7' => {
+                let mut value = u32::from(escaped - b'0');
+                for _ in 0..2 {
+                    match iter.peek() {
+                        Some(digit @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(digit - b'0');
+                            iter.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            b'x' => {
+                let mut value = 0u32;
+                let mut digits = 0;
+                while digits < 2 {
+                    let Some(digit) = iter.peek().and_then(|byte| (*byte as char).to_digit(16))
+                    else {
+                        break;
+                    };
+                    value = value * 16 + digit;
+                    iter.next();
+                    digits += 1;
+                }
+                if digits == 0 {
+                    bytes.push(b'x');
+                } else {
+                    bytes.push(value as u8);
+                }
+            }
+            other => bytes.push(other),
+        }
+    }
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|err| Error::InvalidCopyData(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestTable, TestTableColumn};
+
+    fn copy_out(format: CopyFormat) -> CopyOut<TestTable> {
+        let mut copy = CopyOut::new([
+            TestTableColumn::Id,
+            TestTableColumn::FirstName,
+            TestTableColumn::AnotherTableId,
+        ]);
+        copy.format(format);
+        copy
+    }
+    #[test]
+    fn statement() {
+        assert_eq!(
+            copy_out(CopyFormat::Text).statement(),
+            "COPY test_table (id, first_name, another_table_id) TO STDOUT (FORMAT text)"
+        );
+    }
+    #[test]
+    fn decode_text() {
+        let mut decoder = copy_out(CopyFormat::Text).decoder();
+        let rows = decoder.decode(b"1\tTab\\there\t\\N\n2\tNew").unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].parse::<i32>(0).unwrap(), Some(1));
+        assert_eq!(rows[0].get(1), Some("Tab\there"));
+        assert_eq!(rows[0].get(2), None);
+
+        let rows = decoder.decode(b"\\nline\\\\\t5\n").unwrap();
+        assert_eq!(rows[0].get(1), Some("New\nline\\"));
+        assert_eq!(rows[0].get(2), Some("5"));
+        decoder.finish().unwrap();
+    }
+    #[test]
+    fn decode_binary() {
+        let mut data = BINARY_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&3i16.to_be_bytes());
+        data.extend_from_slice(&4i32.to_be_bytes());
+        data.extend_from_slice(&7i32.to_be_bytes());
+        data.extend_from_slice(&5i32.to_be_bytes());
+        data.extend_from_slice(b"Wyatt");
+        data.extend_from_slice(&(-1i32).to_be_bytes());
+        data.extend_from_slice(&(-1i16).to_be_bytes());
+
+        let mut decoder = copy_out(CopyFormat::Binary).decoder();
+        // Split in the middle of the row
+        let mut rows = decoder.decode(&data[..25]).unwrap();
+        assert!(rows.is_empty());
+        rows.extend(decoder.decode(&data[25..]).unwrap());
+        assert_eq!(
+            rows,
+            vec![CopyOutRow {
+                values: vec![Some("7".to_owned()), Some("Wyatt".to_owned()), None]
+            }]
+        );
+        decoder.finish().unwrap();
+    }
+    #[test]
+    fn incomplete_data() {
+        let mut decoder = copy_out(CopyFormat::Text).decoder();
+        decoder.decode(b"1\tWyatt").unwrap();
+        assert!(matches!(decoder.finish(), Err(Error::InvalidCopyData(_))));
+
+        let mut decoder = copy_out(CopyFormat::Text).decoder();
+        assert!(decoder.decode(b"1\tWyatt\n").is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! Builders for DDL (Data Definition Language) statements
+//!
+//! `CREATE TABLE`, `ALTER TABLE`, `DROP TABLE`, `CREATE INDEX` and `DROP INDEX`
+//!
+//! Tables are built from the [ColumnInfo](crate::table_layout::ColumnInfo) generated by the [TableType](crate::table_layout::TableType) derive.
+//!
+//! The following column attributes are used for DDL:
+//! - `#[column(primary_key)]`
+//! - `#[column(sql_type = "VARCHAR(255)")]` overrides the type from [sqlx::Type]
+//! - `#[column(default = "NOW()")]`
+//! - `#[column(unique)]`
+//! - `#[column(check = "age >= 0")]`
+//!
+//! Foreign keys come from [Relation](crate::table_layout::Relation) implementations.
+//!
+//! ## Note
+//! DDL statements can not use bound parameters. Values used in index expressions and predicates are written as literals.
+mod index;
+mod table;
+pub use index::*;
+pub use table::*;
@@ -0,0 +1,355 @@
+use std::{borrow::Cow, fmt::Debug};
+
+use tracing::{debug, instrument};
+
+use crate::{
+    prelude::*,
+    table_layout::{quote_ident, quote_table_reference},
+    traits::FormatWhereItem,
+};
+
+/// The index method used by `CREATE INDEX ... USING {method}`
+///
+/// Reference: https://www.postgresql.org/docs/current/indexes-types.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexMethod {
+    BTree,
+    Hash,
+    Gist,
+    SpGist,
+    Gin,
+    Brin,
+}
+impl FormatSql for IndexMethod {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            Self::BTree => Cow::Borrowed("BTREE"),
+            Self::Hash => Cow::Borrowed("HASH"),
+            Self::Gist => Cow::Borrowed("GIST"),
+            Self::SpGist => Cow::Borrowed("SPGIST"),
+            Self::Gin => Cow::Borrowed("GIN"),
+            Self::Brin => Cow::Borrowed("BRIN"),
+        }
+    }
+}
+#[derive(Debug)]
+enum IndexKey {
+    Column(DynColumn),
+    /// Expressions must be wrapped in parentheses
+    Expression(Expr),
+}
+impl FormatSql for IndexKey {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            Self::Column(column) => column.quoted_column_name(),
+            Self::Expression(expr) => Cow::Owned(format!("({})", expr.format_sql())),
+        }
+    }
+}
+/// Builds a `CREATE INDEX` statement
+///
+/// Supports unique, partial (via [WhereableTool::filter]) and expression indexes.
+///
+/// Values in expressions and predicates are written as literals.
+/// A value that can not be written as a literal makes `try_query` return [Error::UnsupportedLiteral](crate::Error::UnsupportedLiteral)
+pub struct CreateIndex<'args> {
+    name: &'static str,
+    table: Cow<'static, str>,
+    unique: bool,
+    concurrently: bool,
+    if_not_exists: bool,
+    method: Option<IndexMethod>,
+    keys: Vec<IndexKey>,
+    where_comparisons: Vec<SQLCondition>,
+    sql: Option<String>,
+    arguments: ArgumentHolder<'args>,
+}
+impl<'args> CreateIndex<'args> {
+    pub fn new(name: &'static str, table: &'static str) -> Self {
+        Self {
+            name,
+            table: quote_table_reference(table),
+            unique: false,
+            concurrently: false,
+            if_not_exists: false,
+            method: None,
+            keys: Vec::new(),
+            where_comparisons: Vec::new(),
+            sql: None,
+            arguments: ArgumentHolder::inline_values(),
+        }
+    }
+    pub fn for_table<T: TableType>(name: &'static str) -> Self {
+        Self::new(name, T::qualified_table_name())
+    }
+    /// The name of the index
+    pub fn index_name(&self) -> &'static str {
+        self.name
+    }
+    /// `CREATE UNIQUE INDEX`
+    pub fn unique(&mut self) -> &mut Self {
+        self.unique = true;
+        self
+    }
+    /// `CREATE INDEX CONCURRENTLY`
+    pub fn concurrently(&mut self) -> &mut Self {
+        self.concurrently = true;
+        self
+    }
+    pub fn if_not_exists(&mut self) -> &mut Self {
+        self.if_not_exists = true;
+        self
+    }
+    /// `USING {method}`
+    pub fn using(&mut self, method: IndexMethod) -> &mut Self {
+        self.method = Some(method);
+        self
+    }
+    pub fn column<C>(&mut self, column: C) -> &mut Self
+    where
+        C: ColumnType + 'static,
+    {
+        self.keys.push(IndexKey::Column(column.dyn_column()));
+        self
+    }
+    pub fn columns<C>(&mut self, columns: impl IntoIterator<Item = C>) -> &mut Self
+    where
+        C: ColumnType + 'static,
+    {
+        for column in columns {
+            self.column(column);
+        }
+        self
+    }
+    /// Adds an expression as a key. i.e. `LOWER(email)`
+    pub fn expression<E>(&mut self, expr: E) -> &mut Self
+    where
+        E: ExprType<'args> + 'args,
+    {
+        let expr = expr.process_unboxed(&mut self.arguments);
+        self.keys.push(IndexKey::Expression(expr));
+        self
+    }
+}
+impl<'args> HasArguments<'args> for CreateIndex<'args> {
+    fn holder(&mut self) -> &mut ArgumentHolder<'args> {
+        &mut self.arguments
+    }
+}
+impl<'args> WhereableTool<'args> for CreateIndex<'args> {
+    fn push_where_comparison(&mut self, comparison: SQLCondition) {
+        self.where_comparisons.push(comparison);
+    }
+}
+impl FormatWhere for CreateIndex<'_> {
+    fn get_conditions(&self) -> &[SQLCondition] {
+        &self.where_comparisons
+    }
+}
+impl Debug for CreateIndex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateIndex")
+            .field("name", &self.name)
+            .field("table", &self.table)
+            .field("unique", &self.unique)
+            .field("method", &self.method)
+            .field("keys", &self.keys)
+            .field("where_comparisons", &self.where_comparisons)
+            .field("sql", &self.sql)
+            .finish()
+    }
+}
+impl FormatSqlQuery for CreateIndex<'_> {
+    #[instrument(skip(self), fields(table = %self.table, statement.type = "CREATE INDEX"))]
+    fn format_sql_query(&mut self) -> &str {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| key.format_sql())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut sql = format!(
+            "CREATE{unique} INDEX{concurrently}{if_not_exists} {name} ON {table}",
+            unique = if self.unique { " UNIQUE" } else { "" },
+            concurrently = if self.concurrently {
+                " CONCURRENTLY"
+            } else {
+                ""
+            },
+            if_not_exists = if self.if_not_exists {
+                " IF NOT EXISTS"
+            } else {
+                ""
+            },
+            name = quote_ident(self.name),
+            table = self.table,
+        );
+        if let Some(method) = &self.method {
+            sql.push_str(" USING ");
+            sql.push_str(&method.format_sql());
+        }
+        sql.push_str(&format!(" ({keys})"));
+        if !self.where_comparisons.is_empty() {
+            sql.push_str(&FormatWhereItem(self).to_string());
+        }
+        sql.push(';');
+        let sql = self.arguments.inline_literals(&sql);
+        debug!(?sql, "CreateIndex::format_sql_query");
+        self.sql = Some(sql);
+        self.sql.as_ref().expect("SQL not set")
+    }
+}
+impl<'args> QueryTool<'args> for CreateIndex<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("CREATE INDEX")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
+
+/// `DROP INDEX [CONCURRENTLY] [IF EXISTS] {name} [CASCADE]`
+pub struct DropIndex {
+    name: Cow<'static, str>,
+    concurrently: bool,
+    if_exists: bool,
+    cascade: bool,
+    sql: Option<String>,
+    arguments: ArgumentHolder<'static>,
+}
+impl DropIndex {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name: quote_table_reference(name),
+            concurrently: false,
+            if_exists: false,
+            cascade: false,
+            sql: None,
+            arguments: Default::default(),
+        }
+    }
+    pub fn concurrently(&mut self) -> &mut Self {
+        self.concurrently = true;
+        self
+    }
+    pub fn if_exists(&mut self) -> &mut Self {
+        self.if_exists = true;
+        self
+    }
+    pub fn cascade(&mut self) -> &mut Self {
+        self.cascade = true;
+        self
+    }
+}
+impl Debug for DropIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DropIndex")
+            .field("name", &self.name)
+            .field("concurrently", &self.concurrently)
+            .field("if_exists", &self.if_exists)
+            .field("cascade", &self.cascade)
+            .field("sql", &self.sql)
+            .finish()
+    }
+}
+impl HasArguments<'static> for DropIndex {
+    fn holder(&mut self) -> &mut ArgumentHolder<'static> {
+        &mut self.arguments
+    }
+}
+impl FormatSqlQuery for DropIndex {
+    #[instrument(skip(self), fields(index = %self.name, statement.type = "DROP INDEX"))]
+    fn format_sql_query(&mut self) -> &str {
+        let sql = format!(
+            "DROP INDEX{concurrently}{if_exists} {name}{cascade};",
+            concurrently = if self.concurrently {
+                " CONCURRENTLY"
+            } else {
+                ""
+            },
+            if_exists = if self.if_exists { " IF EXISTS" } else { "" },
+            name = self.name,
+            cascade = if self.cascade { " CASCADE" } else { "" }
+        );
+        debug!(?sql, "DropIndex::format_sql_query");
+        self.sql = Some(sql);
+        self.sql.as_ref().expect("SQL not set")
+    }
+}
+impl QueryTool<'static> for DropIndex {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("DROP INDEX")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Arguments;
+
+    use crate::{
+        Error,
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    #[test]
+    pub fn unique_partial_index() {
+        let mut query = CreateIndex::for_table::<TestTable>("test_table_email_idx");
+        query
+            .unique()
+            .if_not_exists()
+            .columns([TestTableColumn::Email, TestTableColumn::Phone])
+            .filter(TestTableColumn::AnotherTableId.is_not_null());
+        assert_eq!(
+            query.format_sql_query(),
+            "CREATE UNIQUE INDEX IF NOT EXISTS test_table_email_idx ON test_table (email, phone) WHERE test_table.another_table_id IS NOT NULL;"
+        );
+    }
+    #[test]
+    pub fn expression_index() {
+        let mut query = CreateIndex::for_table::<TestTable>("test_table_lower_email_idx");
+        query
+            .concurrently()
+            .using(IndexMethod::Hash)
+            .expression(TestTableColumn::Email.lower());
+        assert_eq!(
+            query.format_sql_query(),
+            "CREATE INDEX CONCURRENTLY test_table_lower_email_idx ON test_table USING HASH ((LOWER(test_table.email)));"
+        );
+
+        let mut query = DropIndex::new("test_table_lower_email_idx");
+        query.concurrently().if_exists();
+        assert_eq!(
+            query.format_sql_query(),
+            "DROP INDEX CONCURRENTLY IF EXISTS test_table_lower_email_idx;"
+        );
+    }
+    #[test]
+    pub fn index_inlines_values() {
+        let mut query = CreateIndex::for_table::<TestTable>("test_table_age_idx");
+        query
+            .column(TestTableColumn::Age)
+            .filter(TestTableColumn::Age.greater_than(18))
+            .filter(TestTableColumn::Email.not_equals("it's".value()));
+        assert_eq!(
+            query.format_sql_query(),
+            "CREATE INDEX test_table_age_idx ON test_table (age) WHERE test_table.age > 18 AND test_table.email != 'it''s';"
+        );
+        assert_eq!(query.try_take_arguments().unwrap().len(), 0);
+    }
+    #[test]
+    pub fn index_rejects_unsupported_literals() {
+        #[derive(sqlx::Type)]
+        struct Point {
+            x: i32,
+        }
+        let mut query = CreateIndex::for_table::<TestTable>("test_table_point_idx");
+        query
+            .column(TestTableColumn::Age)
+            .filter(TestTableColumn::Age.equals(Point { x: 1 }.value()));
+        assert!(matches!(
+            query.try_query(),
+            Err(Error::UnsupportedLiteral(_))
+        ));
+    }
+}
@@ -0,0 +1,602 @@
+use std::{borrow::Cow, fmt::Debug, marker::PhantomData};
+
+use tracing::{debug, instrument};
+
+use crate::{
+    error::Error,
+    prelude::*,
+    table_layout::{Relation, quote_table_reference},
+};
+
+/// The action taken on the referencing rows when the referenced row is deleted or updated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferentialAction {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+impl FormatSql for ReferentialAction {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            Self::NoAction => Cow::Borrowed("NO ACTION"),
+            Self::Restrict => Cow::Borrowed("RESTRICT"),
+            Self::Cascade => Cow::Borrowed("CASCADE"),
+            Self::SetNull => Cow::Borrowed("SET NULL"),
+            Self::SetDefault => Cow::Borrowed("SET DEFAULT"),
+        }
+    }
+}
+/// `FOREIGN KEY ({column}) REFERENCES {table} ({column})`
+#[derive(Debug)]
+pub struct ForeignKey {
+    pub column: DynColumn,
+    pub references_table: &'static str,
+    pub references_column: DynColumn,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
+}
+impl ForeignKey {
+    /// Creates a foreign key from a [Relation]
+    pub fn from_relation<T, R>() -> Self
+    where
+        T: Relation<R>,
+        R: TableType,
+    {
+        Self {
+            column: T::from_column().dyn_column(),
+            references_table: R::qualified_table_name(),
+            references_column: T::to_column().dyn_column(),
+            on_delete: None,
+            on_update: None,
+        }
+    }
+}
+impl FormatSql for ForeignKey {
+    fn format_sql(&self) -> Cow<'_, str> {
+        let mut sql = format!(
+            "FOREIGN KEY ({}) REFERENCES {} ({})",
+            self.column.quoted_column_name(),
+            self.references_table,
+            self.references_column.quoted_column_name()
+        );
+        if let Some(on_delete) = &self.on_delete {
+            sql.push_str(" ON DELETE ");
+            sql.push_str(&on_delete.format_sql());
+        }
+        if let Some(on_update) = &self.on_update {
+            sql.push_str(" ON UPDATE ");
+            sql.push_str(&on_update.format_sql());
+        }
+        Cow::Owned(sql)
+    }
+}
+/// A column of a `CREATE TABLE` or `ADD COLUMN`.
+///
+/// Shared with the [schema snapshots](crate::schema::TableSnapshot) so both render the same SQL
+#[derive(Debug, Clone)]
+pub(crate) struct ColumnDefinition<'a> {
+    /// The quoted column name
+    pub name: Cow<'a, str>,
+    pub data_type: Cow<'a, str>,
+    pub nullable: bool,
+    pub primary_key: bool,
+    pub unique: bool,
+    pub default: Option<&'a str>,
+    pub check: Option<&'a str>,
+}
+impl ColumnDefinition<'static> {
+    pub fn from_column<C>(column: &C) -> Result<Self, Error>
+    where
+        C: ColumnType + ?Sized,
+    {
+        let info = column.column_info().ok_or(Error::MissingColumnInfo {
+            column: column.column_name(),
+        })?;
+        Ok(Self {
+            name: column.quoted_column_name(),
+            data_type: Cow::Owned(info.type_name().to_owned()),
+            nullable: info.nullable,
+            primary_key: info.primary_key,
+            unique: info.unique,
+            default: info.default,
+            check: info.check,
+        })
+    }
+}
+impl ColumnDefinition<'_> {
+    /// `{name} {type} [NOT NULL] [PRIMARY KEY] [UNIQUE] [DEFAULT {default}] [CHECK ({check})]`
+    ///
+    /// `PRIMARY KEY` is left out if `inline_primary_key` is false. It is then added as a table constraint
+    pub fn to_sql(&self, inline_primary_key: bool) -> String {
+        let mut sql = format!("{} {}", self.name, self.data_type);
+        if self.primary_key && inline_primary_key {
+            sql.push_str(" PRIMARY KEY");
+        } else if !self.nullable || self.primary_key {
+            sql.push_str(" NOT NULL");
+        }
+        if self.unique {
+            sql.push_str(" UNIQUE");
+        }
+        if let Some(default) = self.default {
+            sql.push_str(" DEFAULT ");
+            sql.push_str(default);
+        }
+        if let Some(check) = self.check {
+            sql.push_str(" CHECK (");
+            sql.push_str(check);
+            sql.push(')');
+        }
+        sql
+    }
+}
+/// `CREATE TABLE [IF NOT EXISTS] {table} ({columns}, [PRIMARY KEY ({columns})], {constraints})`
+///
+/// A single primary key column is declared inline
+pub(crate) fn create_table_sql(
+    table: &str,
+    if_not_exists: bool,
+    columns: &[ColumnDefinition<'_>],
+    constraints: impl IntoIterator<Item = String>,
+) -> String {
+    let primary_key: Vec<&str> = columns
+        .iter()
+        .filter(|column| column.primary_key)
+        .map(|column| column.name.as_ref())
+        .collect();
+    let inline_primary_key = primary_key.len() <= 1;
+
+    let mut definitions: Vec<String> = columns
+        .iter()
+        .map(|column| column.to_sql(inline_primary_key))
+        .collect();
+    if !inline_primary_key {
+        definitions.push(format!("PRIMARY KEY ({})", primary_key.join(", ")));
+    }
+    definitions.extend(constraints);
+    format!(
+        "CREATE TABLE{if_not_exists} {table} ({definitions});",
+        if_not_exists = if if_not_exists { " IF NOT EXISTS" } else { "" },
+        definitions = definitions.join(", ")
+    )
+}
+/// Builds a `CREATE TABLE` statement from a [TableType]
+///
+/// A primary key of several columns is added as a table level `PRIMARY KEY ({columns})`
+///
+/// ```rust
+/// use pg_extended_sqlx_queries::prelude::*;
+/// #[derive(TableType)]
+/// #[table(name = "users")]
+/// pub struct User {
+///     #[column(primary_key, sql_type = "SERIAL")]
+///     pub id: i32,
+///     #[column(unique)]
+///     pub name: String,
+///     pub bio: Option<String>,
+/// }
+/// let mut query = CreateTable::<User>::new();
+/// query.if_not_exists();
+/// assert_eq!(
+///     query.format_sql_query(),
+///     "CREATE TABLE IF NOT EXISTS users (id SERIAL PRIMARY KEY, name TEXT NOT NULL UNIQUE, bio TEXT);"
+/// );
+/// ```
+pub struct CreateTable<T: TableType> {
+    /// A column without [ColumnType::column_info] is kept as an error in the arguments
+    columns: Vec<ColumnDefinition<'static>>,
+    if_not_exists: bool,
+    foreign_keys: Vec<ForeignKey>,
+    checks: Vec<Cow<'static, str>>,
+    sql: Option<String>,
+    arguments: ArgumentHolder<'static>,
+    table: PhantomData<T>,
+}
+impl<T> Default for CreateTable<T>
+where
+    T: TableType,
+    T::Columns: AllColumns,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> CreateTable<T>
+where
+    T: TableType,
+    T::Columns: AllColumns,
+{
+    /// `try_query` returns [Error::MissingColumnInfo] if a column does not provide [ColumnType::column_info]
+    pub fn new() -> Self {
+        let mut arguments = ArgumentHolder::default();
+        let mut columns = Vec::new();
+        for column in T::Columns::all() {
+            match ColumnDefinition::from_column(&column) {
+                Ok(definition) => columns.push(definition),
+                Err(err) => arguments.push_error(err),
+            }
+        }
+        Self {
+            columns,
+            if_not_exists: false,
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+            sql: None,
+            arguments,
+            table: PhantomData,
+        }
+    }
+    /// `CREATE TABLE IF NOT EXISTS`
+    pub fn if_not_exists(&mut self) -> &mut Self {
+        self.if_not_exists = true;
+        self
+    }
+    /// Adds a foreign key using the [Relation] between the two tables
+    pub fn foreign_key<R>(&mut self) -> &mut Self
+    where
+        T: Relation<R>,
+        R: TableType,
+    {
+        self.foreign_keys.push(ForeignKey::from_relation::<T, R>());
+        self
+    }
+    /// Adds a foreign key using the [Relation] with `ON DELETE {on_delete}`
+    pub fn foreign_key_on_delete<R>(&mut self, on_delete: ReferentialAction) -> &mut Self
+    where
+        T: Relation<R>,
+        R: TableType,
+    {
+        let mut foreign_key = ForeignKey::from_relation::<T, R>();
+        foreign_key.on_delete = Some(on_delete);
+        self.foreign_keys.push(foreign_key);
+        self
+    }
+    /// Adds a table level `CHECK ({check})` constraint
+    pub fn check(&mut self, check: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.checks.push(check.into());
+        self
+    }
+}
+impl<T: TableType> HasArguments<'static> for CreateTable<T> {
+    fn holder(&mut self) -> &mut ArgumentHolder<'static> {
+        &mut self.arguments
+    }
+}
+impl<T: TableType> Debug for CreateTable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateTable")
+            .field("table", &T::qualified_table_name())
+            .field("if_not_exists", &self.if_not_exists)
+            .field("foreign_keys", &self.foreign_keys)
+            .field("checks", &self.checks)
+            .field("sql", &self.sql)
+            .finish()
+    }
+}
+impl<T> FormatSqlQuery for CreateTable<T>
+where
+    T: TableType,
+    T::Columns: AllColumns,
+{
+    #[instrument(skip(self), fields(table = %T::qualified_table_name(), statement.type = "CREATE TABLE"))]
+    fn format_sql_query(&mut self) -> &str {
+        let constraints = self
+            .foreign_keys
+            .iter()
+            .map(|key| key.format_sql().into_owned())
+            .chain(self.checks.iter().map(|check| format!("CHECK ({check})")));
+        let sql = create_table_sql(
+            T::qualified_table_name(),
+            self.if_not_exists,
+            &self.columns,
+            constraints,
+        );
+        debug!(?sql, "CreateTable::format_sql_query");
+        self.sql = Some(sql);
+        self.sql.as_ref().expect("SQL not set")
+    }
+}
+impl<T> QueryTool<'static> for CreateTable<T>
+where
+    T: TableType,
+    T::Columns: AllColumns,
+{
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("CREATE TABLE")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(T::qualified_table_name())
+    }
+}
+
+/// `DROP TABLE [IF EXISTS] {table} [CASCADE]`
+pub struct DropTable {
+    table: Cow<'static, str>,
+    if_exists: bool,
+    cascade: bool,
+    sql: Option<String>,
+    arguments: ArgumentHolder<'static>,
+}
+impl DropTable {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table: quote_table_reference(table),
+            if_exists: false,
+            cascade: false,
+            sql: None,
+            arguments: Default::default(),
+        }
+    }
+    pub fn for_table<T: TableType>() -> Self {
+        Self::new(T::qualified_table_name())
+    }
+    pub fn if_exists(&mut self) -> &mut Self {
+        self.if_exists = true;
+        self
+    }
+    pub fn cascade(&mut self) -> &mut Self {
+        self.cascade = true;
+        self
+    }
+}
+impl Debug for DropTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DropTable")
+            .field("table", &self.table)
+            .field("if_exists", &self.if_exists)
+            .field("cascade", &self.cascade)
+            .field("sql", &self.sql)
+            .finish()
+    }
+}
+impl HasArguments<'static> for DropTable {
+    fn holder(&mut self) -> &mut ArgumentHolder<'static> {
+        &mut self.arguments
+    }
+}
+impl FormatSqlQuery for DropTable {
+    #[instrument(skip(self), fields(table = %self.table, statement.type = "DROP TABLE"))]
+    fn format_sql_query(&mut self) -> &str {
+        let sql = format!(
+            "DROP TABLE{if_exists} {table}{cascade};",
+            if_exists = if self.if_exists { " IF EXISTS" } else { "" },
+            table = self.table,
+            cascade = if self.cascade { " CASCADE" } else { "" }
+        );
+        debug!(?sql, "DropTable::format_sql_query");
+        self.sql = Some(sql);
+        self.sql.as_ref().expect("SQL not set")
+    }
+}
+impl QueryTool<'static> for DropTable {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("DROP TABLE")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
+
+#[derive(Debug)]
+enum AlterTableAction {
+    AddColumn {
+        definition: String,
+        if_not_exists: bool,
+    },
+    DropColumn {
+        column: DynColumn,
+        if_exists: bool,
+        cascade: bool,
+    },
+}
+impl FormatSql for AlterTableAction {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            Self::AddColumn {
+                definition,
+                if_not_exists,
+            } => {
+                let if_not_exists = if *if_not_exists { " IF NOT EXISTS" } else { "" };
+                Cow::Owned(format!("ADD COLUMN{if_not_exists} {definition}"))
+            }
+            Self::DropColumn {
+                column,
+                if_exists,
+                cascade,
+            } => {
+                let if_exists = if *if_exists { " IF EXISTS" } else { "" };
+                let cascade = if *cascade { " CASCADE" } else { "" };
+                Cow::Owned(format!(
+                    "DROP COLUMN{if_exists} {}{cascade}",
+                    column.quoted_column_name()
+                ))
+            }
+        }
+    }
+}
+/// `ALTER TABLE {table} ADD COLUMN ..., DROP COLUMN ...`
+pub struct AlterTable {
+    table: Cow<'static, str>,
+    actions: Vec<AlterTableAction>,
+    sql: Option<String>,
+    arguments: ArgumentHolder<'static>,
+}
+impl AlterTable {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table: quote_table_reference(table),
+            actions: Vec::new(),
+            sql: None,
+            arguments: Default::default(),
+        }
+    }
+    pub fn for_table<T: TableType>() -> Self {
+        Self::new(T::qualified_table_name())
+    }
+    /// `ADD COLUMN {definition}`
+    ///
+    /// `try_query` returns [Error::MissingColumnInfo] if the column does not provide [ColumnType::column_info]
+    pub fn add_column<C>(&mut self, column: C) -> &mut Self
+    where
+        C: ColumnType,
+    {
+        self.push_add_column(&column, false)
+    }
+    /// `ADD COLUMN IF NOT EXISTS {definition}`
+    pub fn add_column_if_not_exists<C>(&mut self, column: C) -> &mut Self
+    where
+        C: ColumnType,
+    {
+        self.push_add_column(&column, true)
+    }
+    fn push_add_column<C>(&mut self, column: &C, if_not_exists: bool) -> &mut Self
+    where
+        C: ColumnType,
+    {
+        match ColumnDefinition::from_column(column) {
+            Ok(definition) => self.actions.push(AlterTableAction::AddColumn {
+                definition: definition.to_sql(true),
+                if_not_exists,
+            }),
+            Err(err) => self.arguments.push_error(err),
+        }
+        self
+    }
+    /// `DROP COLUMN {column}`
+    pub fn drop_column<C>(&mut self, column: C) -> &mut Self
+    where
+        C: ColumnType + 'static,
+    {
+        self.actions.push(AlterTableAction::DropColumn {
+            column: column.dyn_column(),
+            if_exists: false,
+            cascade: false,
+        });
+        self
+    }
+    /// `DROP COLUMN IF EXISTS {column}`
+    pub fn drop_column_if_exists<C>(&mut self, column: C) -> &mut Self
+    where
+        C: ColumnType + 'static,
+    {
+        self.actions.push(AlterTableAction::DropColumn {
+            column: column.dyn_column(),
+            if_exists: true,
+            cascade: false,
+        });
+        self
+    }
+}
+impl Debug for AlterTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlterTable")
+            .field("table", &self.table)
+            .field("actions", &self.actions)
+            .field("sql", &self.sql)
+            .finish()
+    }
+}
+impl HasArguments<'static> for AlterTable {
+    fn holder(&mut self) -> &mut ArgumentHolder<'static> {
+        &mut self.arguments
+    }
+}
+impl FormatSqlQuery for AlterTable {
+    #[instrument(skip(self), fields(table = %self.table, statement.type = "ALTER TABLE"))]
+    fn format_sql_query(&mut self) -> &str {
+        let actions = self
+            .actions
+            .iter()
+            .map(|action| action.format_sql())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!("ALTER TABLE {table} {actions};", table = self.table);
+        debug!(?sql, "AlterTable::format_sql_query");
+        self.sql = Some(sql);
+        self.sql.as_ref().expect("SQL not set")
+    }
+}
+impl QueryTool<'static> for AlterTable {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("ALTER TABLE")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Error,
+        prelude::*,
+        testing::{
+            AnotherTable, AuthUser, AuthUserColumn, Nickname, Nicknames, TestTable, UserRole,
+        },
+    };
+
+    #[test]
+    pub fn create_table() {
+        let mut query = CreateTable::<AnotherTable>::new();
+        query.if_not_exists();
+        assert_eq!(
+            query.format_sql_query(),
+            "CREATE TABLE IF NOT EXISTS another_table (id INT4 PRIMARY KEY, email TEXT NOT NULL, phone TEXT NOT NULL, age INT4 NOT NULL, updated_at TIMESTAMPTZ NOT NULL, created_at TIMESTAMPTZ NOT NULL);"
+        );
+    }
+    #[test]
+    pub fn create_table_with_foreign_key() {
+        let mut query = CreateTable::<AuthUser>::new();
+        query
+            .foreign_key_on_delete::<TestTable>(ReferentialAction::Cascade)
+            .check("char_length(username) > 2");
+        assert_eq!(
+            query.format_sql_query(),
+            "CREATE TABLE auth.users (id SERIAL PRIMARY KEY, username VARCHAR(32) NOT NULL UNIQUE CHECK (username <> ''), test_table_id INT4, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), FOREIGN KEY (test_table_id) REFERENCES test_table (id) ON DELETE CASCADE, CHECK (char_length(username) > 2));"
+        );
+    }
+    #[test]
+    pub fn create_table_with_composite_primary_key() {
+        let mut query = CreateTable::<UserRole>::new();
+        assert_eq!(
+            query.format_sql_query(),
+            "CREATE TABLE user_roles (user_id INT4 NOT NULL, role TEXT NOT NULL, granted_by INT4, PRIMARY KEY (user_id, role));"
+        );
+    }
+    #[test]
+    pub fn create_table_requires_column_info() {
+        let mut query = CreateTable::<Nicknames>::new();
+        assert!(matches!(
+            query.try_query(),
+            Err(Error::MissingColumnInfo { column: "nickname" })
+        ));
+    }
+    #[test]
+    pub fn alter_table_requires_column_info() {
+        let mut query = AlterTable::for_table::<AuthUser>();
+        query.add_column(Nickname);
+        assert!(matches!(
+            query.try_query(),
+            Err(Error::MissingColumnInfo { column: "nickname" })
+        ));
+    }
+    #[test]
+    pub fn alter_and_drop_table() {
+        let mut query = AlterTable::for_table::<AuthUser>();
+        query
+            .add_column_if_not_exists(AuthUserColumn::CreatedAt)
+            .drop_column_if_exists(AuthUserColumn::TestTableId);
+        assert_eq!(
+            query.format_sql_query(),
+            "ALTER TABLE auth.users ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), DROP COLUMN IF EXISTS test_table_id;"
+        );
+
+        let mut query = DropTable::for_table::<AuthUser>();
+        query.if_exists().cascade();
+        assert_eq!(
+            query.format_sql_query(),
+            "DROP TABLE IF EXISTS auth.users CASCADE;"
+        );
+    }
+}
@@ -1,32 +1,78 @@
-use crate::prelude::*;
+use crate::{
+    error::Error, policy::UnfilteredWrites, prelude::*, table_layout::quote_table_reference,
+};
 use std::{borrow::Cow, fmt::Debug};
 
 use tracing::{debug, instrument};
 
 pub struct DeleteQueryBuilder<'args> {
     table: Cow<'args, str>,
+    using: Vec<TableSource>,
     where_comparisons: Vec<SQLCondition>,
+    returning: Option<Returning>,
     sql: Option<String>,
+    /// Allows the query to not have a `WHERE`
+    all_rows: bool,
     arguments: ArgumentHolder<'args>,
 }
 impl Debug for DeleteQueryBuilder<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DeleteQueryBuilder")
             .field("table", &self.table)
+            .field("using", &self.using)
             .field("where_comparisons", &self.where_comparisons)
+            .field("returning", &self.returning)
             .field("sql", &self.sql)
+            .field("all_rows", &self.all_rows)
             .finish()
     }
 }
 impl<'args> DeleteQueryBuilder<'args> {
     pub fn new(table: &'args str) -> Self {
         Self {
-            table: Cow::Borrowed(table),
+            table: quote_table_reference(table),
+            using: Vec::new(),
             where_comparisons: vec![],
+            returning: None,
             sql: None,
+            all_rows: false,
             arguments: Default::default(),
         }
     }
+    /// Deletes every row if no filter is added.
+    ///
+    /// Without this a `DELETE` without a `WHERE` is an [Error::UnfilteredWrite]
+    pub fn all_rows(&mut self) -> &mut Self {
+        self.all_rows = true;
+        self
+    }
+    /// `USING {table}`. Join conditions go in the filter
+    pub fn using(&mut self, table: &'args str) -> &mut Self {
+        self.sql = None;
+        self.using.push(TableSource::table(table));
+        self
+    }
+    /// `USING {table} AS {alias}`. Use [SourceColumn] to reference its columns
+    pub fn using_as(
+        &mut self,
+        table: &'args str,
+        alias: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sql = None;
+        self.using.push(TableSource::table_as(table, alias));
+        self
+    }
+    /// `USING ({query}) AS {alias}`
+    pub fn using_subquery(
+        &mut self,
+        query: SelectExprBuilder<'args>,
+        alias: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sql = None;
+        let source = TableSource::subquery(query, alias, &mut self.arguments);
+        self.using.push(source);
+        self
+    }
 }
 impl<'args> HasArguments<'args> for DeleteQueryBuilder<'args> {
     fn holder(&mut self) -> &mut ArgumentHolder<'args> {
@@ -48,24 +94,62 @@ impl<'args> FormatSqlQuery for DeleteQueryBuilder<'args> {
     #[instrument(skip(self), fields(table = %self.table, statement.type = "DELETE"))]
     fn format_sql_query(&mut self) -> &str {
         let mut sql = format!("DELETE FROM {}", self.table);
-
+        if !self.using.is_empty() {
+            let using: Vec<_> = self
+                .using
+                .iter()
+                .map(|source| source.format_sql())
+                .collect();
+            sql.push_str(" USING ");
+            sql.push_str(&using.join(", "));
+        }
         if !self.where_comparisons.is_empty() {
             let where_sql = self.format_where();
             sql.push_str(" WHERE ");
             sql.push_str(&where_sql);
         }
-        debug!(?sql, "UpdateQueryBuilder::format_sql_query");
+        if let Some(returning) = &self.returning {
+            sql.push(' ');
+            if self.using.is_empty() {
+                sql.push_str(&returning.format_sql());
+            } else {
+                sql.push_str(&returning.format_qualified(&self.table));
+            }
+        }
+        debug!(?sql, "DeleteQueryBuilder::format_sql_query");
         self.sql = Some(sql);
 
         self.sql.as_ref().expect("SQL not set")
     }
 }
-impl<'args> QueryTool<'args> for DeleteQueryBuilder<'args> {}
+impl<'args> QueryTool<'args> for DeleteQueryBuilder<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("DELETE")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+    fn validate(&self) -> Result<(), Error> {
+        if self.where_comparisons.is_empty() && !self.all_rows {
+            return UnfilteredWrites::check("DELETE", &self.table);
+        }
+        Ok(())
+    }
+}
+impl SupportsReturning for DeleteQueryBuilder<'_> {
+    fn returning(&mut self, returning: Returning) -> &mut Self {
+        self.sql = None;
+        self.returning = Some(returning);
+        self
+    }
+}
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::testing::{TestTable, TestTableColumn};
+    use crate::testing::{
+        AnotherTable, AnotherTableColumn, AuthUser, AuthUserColumn, TestTable, TestTableColumn,
+    };
 
     #[test]
     fn test_delete_query_builder() {
@@ -74,4 +158,102 @@ mod tests {
         let sql = delete_query_builder.format_sql_query();
         assert_eq!(sql, "DELETE FROM test_table WHERE test_table.id = $1");
     }
+    #[test]
+    fn test_delete_with_schema() {
+        let mut delete_query_builder = DeleteQueryBuilder::new(AuthUser::qualified_table_name());
+        delete_query_builder.filter(AuthUserColumn::Id.equals(1.value()));
+        let sql = delete_query_builder.format_sql_query();
+        assert_eq!(sql, "DELETE FROM auth.users WHERE users.id = $1");
+    }
+    #[test]
+    fn unfiltered_delete_is_denied() {
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::UnfilteredWrite {
+                statement: "DELETE",
+                ..
+            }
+        ));
+
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        query.all_rows();
+        assert!(query.try_query().is_ok());
+    }
+    #[test]
+    fn test_delete_returning() {
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        query
+            .filter(TestTableColumn::Id.equals(1.value()))
+            .return_all();
+        assert_eq!(
+            query.format_sql_query(),
+            "DELETE FROM test_table WHERE test_table.id = $1 RETURNING *"
+        );
+        query.return_columns(vec![TestTableColumn::Id, TestTableColumn::Email]);
+        assert_eq!(
+            query.format_sql_query(),
+            "DELETE FROM test_table WHERE test_table.id = $1 RETURNING id, email"
+        );
+        query.return_expressions([TestTableColumn::Age.alias("deleted_age")]);
+        assert_eq!(
+            query.format_sql_query(),
+            "DELETE FROM test_table WHERE test_table.id = $1 RETURNING test_table.age AS deleted_age"
+        );
+    }
+    #[test]
+    fn test_delete_using() {
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        query
+            .using(AnotherTable::table_name())
+            .filter(TestTableColumn::AnotherTableId.equals(AnotherTableColumn::Id))
+            .filter(AnotherTableColumn::Age.greater_than(50.value()));
+        assert_eq!(
+            query.format_sql_query(),
+            "DELETE FROM test_table USING another_table WHERE test_table.another_table_id = another_table.id AND another_table.age > $1"
+        );
+        query.return_all();
+        assert!(
+            query
+                .format_sql_query()
+                .ends_with(" RETURNING test_table.*")
+        );
+
+        // The table name does not have to be 'static
+        let archive = format!("{}_{}", AnotherTable::table_name(), 2024);
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        query.using_as(&archive, "archive").filter(
+            TestTableColumn::Id.equals(SourceColumn::of("archive", AnotherTableColumn::Id)),
+        );
+        assert_eq!(
+            query.format_sql_query(),
+            "DELETE FROM test_table USING another_table_2024 AS archive WHERE test_table.id = archive.id"
+        );
+
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        query
+            .using_subquery(
+                SelectExprBuilder::new(AnotherTable::table_name())
+                    .column(AnotherTableColumn::Id)
+                    .column(AnotherTableColumn::Email)
+                    .filter(AnotherTableColumn::Age.greater_than(50.value())),
+                "old",
+            )
+            .filter(TestTableColumn::Age.equals(1.value()));
+        assert_eq!(
+            query.format_sql_query(),
+            "DELETE FROM test_table USING (SELECT another_table.id, another_table.email FROM another_table WHERE another_table.age > $1) AS old WHERE test_table.age = $2"
+        );
+        let returning = query.try_query_returning::<TestTable>();
+        assert!(returning.is_ok());
+        assert!(
+            query
+                .format_sql_query()
+                .ends_with("RETURNING test_table.id, test_table.first_name, test_table.last_name, test_table.age, \
+                            test_table.email, test_table.phone, test_table.another_table_id, test_table.updated_at, test_table.created_at")
+        );
+    }
 }
@@ -0,0 +1,141 @@
+//! Errors returned by the `try_` variants of the query builders
+use std::fmt::Display;
+
+use sqlx::error::BoxDynError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The arguments of the query were already taken.
+    ///
+    /// Happens if `query()` is called twice on the same builder
+    ArgumentsTaken,
+    /// A value failed to encode while being bound
+    Encode(BoxDynError),
+    /// The query contains [Param](crate::expr::Param)s and must be executed with a [QueryTemplate](crate::template::QueryTemplate)
+    NamedParameters,
+    /// Bound values and [Param](crate::expr::Param)s were used in the same query
+    MixedParameters,
+    /// No value was given for a named parameter of a [QueryTemplate](crate::template::QueryTemplate)
+    MissingParameter(String),
+    /// An `UPDATE` or `DELETE` without a `WHERE`. Call `all_rows()` if this is intended
+    ///
+    /// See [UnfilteredWrites](crate::policy::UnfilteredWrites)
+    UnfilteredWrite {
+        statement: &'static str,
+        table: String,
+    },
+    /// A value of a `COPY FROM STDIN` row could not be written
+    ///
+    /// See [CopyIn](crate::copy::CopyIn)
+    CopyValue {
+        column: &'static str,
+        error: BoxDynError,
+    },
+    /// The data of a `COPY TO STDOUT` could not be decoded
+    InvalidCopyData(String),
+    /// A required clause was never added. i.e. the `USING` of a `MERGE`
+    MissingClause {
+        statement: &'static str,
+        clause: &'static str,
+    },
+    /// A value was written to a `#[column(generated)]` or `#[column(read_only)]` column.
+    /// Or an `UPDATE` set a `#[column(insert_only)]` column
+    ColumnNotWritable { column: &'static str },
+    /// A row of an [InsertManyBuilder](crate::insert::many::InsertManyBuilder) has a value that does not match a column.
+    ///
+    /// The column is not one of the columns of the query, was given twice or the row has too many values
+    UnmatchedValue { column: Option<&'static str> },
+    /// A value used in a DDL statement can not be written as a literal. Contains the type name
+    UnsupportedLiteral(String),
+    /// The column does not provide [ColumnType::column_info](crate::table_layout::ColumnType::column_info). Use the TableType derive
+    MissingColumnInfo { column: &'static str },
+}
+impl Error {
+    /// Short name of the error. Recorded as `error.type` by the [telemetry](crate::telemetry)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ArgumentsTaken => "arguments_taken",
+            Self::Encode(_) => "encode",
+            Self::NamedParameters => "named_parameters",
+            Self::MixedParameters => "mixed_parameters",
+            Self::MissingParameter(_) => "missing_parameter",
+            Self::UnfilteredWrite { .. } => "unfiltered_write",
+            Self::CopyValue { .. } => "copy_value",
+            Self::InvalidCopyData(_) => "invalid_copy_data",
+            Self::MissingClause { .. } => "missing_clause",
+            Self::ColumnNotWritable { .. } => "column_not_writable",
+            Self::UnmatchedValue { .. } => "unmatched_value",
+            Self::UnsupportedLiteral(_) => "unsupported_literal",
+            Self::MissingColumnInfo { .. } => "missing_column_info",
+        }
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ArgumentsTaken => write!(f, "Arguments already taken"),
+            Self::Encode(err) => write!(f, "Failed to add argument: {err}"),
+            Self::NamedParameters => {
+                write!(f, "Query has named parameters. Use a QueryTemplate")
+            }
+            Self::MixedParameters => {
+                write!(f, "Bound values and named parameters can not be mixed")
+            }
+            Self::MissingParameter(name) => write!(f, "Missing value for parameter `{name}`"),
+            Self::UnfilteredWrite { statement, table } => write!(
+                f,
+                "{statement} on {table} has no WHERE clause. Call all_rows() to write every row"
+            ),
+            Self::CopyValue { column, error } => {
+                write!(f, "Failed to write column `{column}`: {error}")
+            }
+            Self::InvalidCopyData(reason) => write!(f, "Invalid COPY data: {reason}"),
+            Self::MissingClause { statement, clause } => {
+                write!(f, "{statement} is missing its {clause} clause")
+            }
+            Self::ColumnNotWritable { column } => {
+                write!(f, "Column `{column}` can not be written")
+            }
+            Self::UnmatchedValue {
+                column: Some(column),
+            } => write!(
+                f,
+                "Value for `{column}` does not match a column of the INSERT"
+            ),
+            Self::UnmatchedValue { column: None } => {
+                write!(f, "Row has more values than the INSERT has columns")
+            }
+            Self::UnsupportedLiteral(type_name) => {
+                write!(
+                    f,
+                    "Values of type {type_name} can not be written as a literal"
+                )
+            }
+            Self::MissingColumnInfo { column } => {
+                write!(f, "Column `{column}` does not provide column_info")
+            }
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Encode(err) | Self::CopyValue { error: err, .. } => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+/// Values that failed to encode become [sqlx::Error::Encode] and invalid `COPY` data [sqlx::Error::Decode].
+///
+/// Every other error is a query that was built wrong. i.e. an `UPDATE` without a `WHERE`.
+/// They become [sqlx::Error::InvalidArgument]
+impl From<Error> for sqlx::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Encode(err) => sqlx::Error::Encode(err),
+            err @ Error::CopyValue { .. } => sqlx::Error::Encode(Box::new(err)),
+            err @ Error::InvalidCopyData(_) => sqlx::Error::Decode(Box::new(err)),
+            err => sqlx::Error::InvalidArgument(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! `EXPLAIN` for any [QueryTool]. Requires the `explain` feature
+//!
+//! ```no_run
+//! use pg_extended_sqlx_queries::{explain::*, prelude::*};
+//! # async fn example(pool: sqlx::PgPool) -> Result<(), Box<dyn std::error::Error>> {
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     #[column(primary_key)]
+//!     pub id: i32,
+//!     pub age: i32,
+//! }
+//! let mut query = SelectQueryBuilder::with_columns(User::table_name(), UserColumn::all());
+//! query.filter(UserColumn::Age.greater_than(18.value()));
+//! let output = query
+//!     .explain(ExplainOptions::default().analyze(true))?
+//!     .execute(&pool)
+//!     .await?;
+//! if let Some(plan) = output.plan() {
+//!     plan.warn_sequential_scans(&["users"]);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use serde_json::{Map, Value};
+use sqlx::{Postgres, error::BoxDynError, postgres::PgArguments};
+use tracing::{trace, warn};
+
+use crate::{error::Error, traits::QueryTool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExplainFormat {
+    Text,
+    /// Parsed into an [ExplainPlan]
+    #[default]
+    Json,
+}
+/// The options of `EXPLAIN (..)`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExplainOptions {
+    /// Executes the query. Writes are not rolled back
+    pub analyze: bool,
+    pub buffers: bool,
+    pub verbose: bool,
+    pub settings: bool,
+    pub format: ExplainFormat,
+}
+impl ExplainOptions {
+    pub fn analyze(&mut self, analyze: bool) -> &mut Self {
+        self.analyze = analyze;
+        self
+    }
+    pub fn buffers(&mut self, buffers: bool) -> &mut Self {
+        self.buffers = buffers;
+        self
+    }
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.verbose = verbose;
+        self
+    }
+    pub fn settings(&mut self, settings: bool) -> &mut Self {
+        self.settings = settings;
+        self
+    }
+    pub fn format(&mut self, format: ExplainFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+    /// `EXPLAIN (ANALYZE, FORMAT JSON) {sql}`
+    pub fn wrap_sql(&self, sql: &str) -> String {
+        let mut options = Vec::new();
+        if self.analyze {
+            options.push("ANALYZE");
+        }
+        if self.buffers {
+            options.push("BUFFERS");
+        }
+        if self.verbose {
+            options.push("VERBOSE");
+        }
+        if self.settings {
+            options.push("SETTINGS");
+        }
+        options.push(match self.format {
+            ExplainFormat::Text => "FORMAT TEXT",
+            ExplainFormat::Json => "FORMAT JSON",
+        });
+        format!("EXPLAIN ({}) {sql}", options.join(", "))
+    }
+}
+/// Adds [ExplainTool::explain] to every [QueryTool]
+pub trait ExplainTool<'args>: QueryTool<'args> {
+    /// Wraps the SQL and arguments of the query in an `EXPLAIN`
+    fn explain(&mut self, options: &ExplainOptions) -> Result<Explain, Error> {
+        self.validate()?;
+        let arguments = self.try_take_arguments()?;
+        let sql = options.wrap_sql(self.format_sql_query());
+        Ok(Explain {
+            sql,
+            arguments,
+            format: options.format,
+        })
+    }
+}
+impl<'args, Q> ExplainTool<'args> for Q where Q: QueryTool<'args> {}
+
+/// An `EXPLAIN` that is ready to be executed
+pub struct Explain {
+    sql: String,
+    arguments: PgArguments,
+    format: ExplainFormat,
+}
+impl std::fmt::Debug for Explain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Explain")
+            .field("sql", &self.sql)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+impl Explain {
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+    pub async fn execute<'c, E>(self, conn: E) -> Result<ExplainOutput, sqlx::Error>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
+        trace!(sql = ?self.sql, "Executing Explain");
+        match self.format {
+            ExplainFormat::Text => {
+                let lines: Vec<String> = sqlx::query_scalar_with(&self.sql, self.arguments)
+                    .fetch_all(conn)
+                    .await?;
+                Ok(ExplainOutput::Text(lines.join("\n")))
+            }
+            ExplainFormat::Json => {
+                let value: Value = sqlx::query_scalar_with(&self.sql, self.arguments)
+                    .fetch_one(conn)
+                    .await?;
+                let plan = ExplainPlan::from_json(value).map_err(sqlx::Error::Decode)?;
+                Ok(ExplainOutput::Json(Box::new(plan)))
+            }
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExplainOutput {
+    Text(String),
+    Json(Box<ExplainPlan>),
+}
+impl ExplainOutput {
+    pub fn plan(&self) -> Option<&ExplainPlan> {
+        match self {
+            Self::Json(plan) => Some(plan),
+            Self::Text(_) => None,
+        }
+    }
+}
+/// The output of `EXPLAIN (FORMAT JSON)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainPlan {
+    pub root: PlanNode,
+    /// Milliseconds. Only with ANALYZE
+    pub planning_time: Option<f64>,
+    /// Milliseconds. Only with ANALYZE
+    pub execution_time: Option<f64>,
+    /// Only with SETTINGS
+    pub settings: Map<String, Value>,
+}
+impl ExplainPlan {
+    pub fn from_json(value: Value) -> Result<Self, BoxDynError> {
+        let mut object = match value {
+            Value::Array(mut items) if !items.is_empty() => items.swap_remove(0),
+            value => value,
+        };
+        let object = object
+            .as_object_mut()
+            .ok_or("Expected an object in the EXPLAIN output")?;
+        let root = object.remove("Plan").ok_or("Missing Plan")?;
+        Ok(Self {
+            root: PlanNode::from_json(root)?,
+            planning_time: object.get("Planning Time").and_then(Value::as_f64),
+            execution_time: object.get("Execution Time").and_then(Value::as_f64),
+            settings: match object.remove("Settings") {
+                Some(Value::Object(settings)) => settings,
+                _ => Map::new(),
+            },
+        })
+    }
+    /// The estimated cost of the whole query
+    pub fn total_cost(&self) -> f64 {
+        self.root.total_cost
+    }
+    /// Every node depth first
+    pub fn nodes(&self) -> Vec<&PlanNode> {
+        let mut nodes = Vec::new();
+        self.root.collect_nodes(&mut nodes);
+        nodes
+    }
+    /// `Seq Scan` and `Parallel Seq Scan` nodes on any of the tables. Logs a warning for each one
+    ///
+    /// Tables can be qualified with a schema. The schema is only compared if the plan was VERBOSE
+    pub fn warn_sequential_scans(&self, tables: &[&str]) -> Vec<&PlanNode> {
+        let scans: Vec<_> = self
+            .nodes()
+            .into_iter()
+            .filter(|node| node.is_sequential_scan())
+            .filter(|node| tables.iter().any(|table| node.is_on_table(table)))
+            .collect();
+        for scan in &scans {
+            warn!(
+                table = scan.relation_name.as_deref(),
+                total_cost = scan.total_cost,
+                "Sequential scan in query plan"
+            );
+        }
+        scans
+    }
+}
+/// A node of the plan tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanNode {
+    /// i.e. `Seq Scan`, `Index Scan`, `Hash Join`
+    pub node_type: String,
+    pub relation_name: Option<String>,
+    /// Only with VERBOSE
+    pub schema: Option<String>,
+    pub alias: Option<String>,
+    pub index_name: Option<String>,
+    pub startup_cost: f64,
+    pub total_cost: f64,
+    pub plan_rows: f64,
+    pub plan_width: f64,
+    /// Only with ANALYZE
+    pub actual_total_time: Option<f64>,
+    /// Only with ANALYZE
+    pub actual_rows: Option<f64>,
+    /// Only with ANALYZE
+    pub actual_loops: Option<f64>,
+    pub children: Vec<PlanNode>,
+    /// The other keys of the node. i.e. `Filter` or `Shared Hit Blocks`
+    pub details: Map<String, Value>,
+}
+impl PlanNode {
+    pub fn from_json(value: Value) -> Result<Self, BoxDynError> {
+        let Value::Object(mut object) = value else {
+            return Err("Expected a plan node object".into());
+        };
+        let mut take_string = |key: &str| match object.remove(key) {
+            Some(Value::String(value)) => Some(value),
+            _ => None,
+        };
+        let node_type = take_string("Node Type").ok_or("Missing Node Type")?;
+        let relation_name = take_string("Relation Name");
+        let schema = take_string("Schema");
+        let alias = take_string("Alias");
+        let index_name = take_string("Index Name");
+        let mut take_number = |key: &str| object.remove(key).as_ref().and_then(Value::as_f64);
+        let startup_cost = take_number("Startup Cost").unwrap_or_default();
+        let total_cost = take_number("Total Cost").unwrap_or_default();
+        let plan_rows = take_number("Plan Rows").unwrap_or_default();
+        let plan_width = take_number("Plan Width").unwrap_or_default();
+        let actual_total_time = take_number("Actual Total Time");
+        let actual_rows = take_number("Actual Rows");
+        let actual_loops = take_number("Actual Loops");
+        let children = match object.remove("Plans") {
+            Some(Value::Array(plans)) => plans
+                .into_iter()
+                .map(PlanNode::from_json)
+                .collect::<Result<_, _>>()?,
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            node_type,
+            relation_name,
+            schema,
+            alias,
+            index_name,
+            startup_cost,
+            total_cost,
+            plan_rows,
+            plan_width,
+            actual_total_time,
+            actual_rows,
+            actual_loops,
+            children,
+            details: object,
+        })
+    }
+    /// `Seq Scan` or `Parallel Seq Scan`
+    pub fn is_sequential_scan(&self) -> bool {
+        matches!(self.node_type.as_str(), "Seq Scan" | "Parallel Seq Scan")
+    }
+    /// If the node reads the table. `table` can be qualified with a schema
+    pub fn is_on_table(&self, table: &str) -> bool {
+        let (schema, name) = match table.rsplit_once('.') {
+            Some((schema, name)) => (Some(schema), name),
+            None => (None, table),
+        };
+        if self.relation_name.as_deref() != Some(name) {
+            return false;
+        }
+        match (schema, &self.schema) {
+            (Some(schema), Some(node_schema)) => schema == node_schema,
+            _ => true,
+        }
+    }
+    fn collect_nodes<'a>(&'a self, nodes: &mut Vec<&'a PlanNode>) {
+        nodes.push(self);
+        for child in &self.children {
+            child.collect_nodes(nodes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    #[test]
+    fn wraps_query() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query.filter(TestTableColumn::Age.equals(50.value()));
+        let explain = query
+            .explain(ExplainOptions::default().analyze(true).buffers(true))
+            .unwrap();
+        assert!(
+            explain
+                .sql()
+                .starts_with("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) SELECT")
+        );
+        assert!(query.explain(&ExplainOptions::default()).is_err());
+    }
+    #[test]
+    fn parses_plan() {
+        let output = json!([{
+            "Plan": {
+                "Node Type": "Hash Join",
+                "Startup Cost": 1.5,
+                "Total Cost": 42.0,
+                "Plan Rows": 10,
+                "Plan Width": 8,
+                "Actual Rows": 3,
+                "Hash Cond": "(a.id = b.a_id)",
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan",
+                        "Relation Name": "test_table",
+                        "Alias": "test_table",
+                        "Total Cost": 20.0
+                    },
+                    {
+                        "Node Type": "Index Scan",
+                        "Relation Name": "another_table",
+                        "Index Name": "another_table_pkey",
+                        "Total Cost": 8.0
+                    }
+                ]
+            },
+            "Planning Time": 0.1,
+            "Execution Time": 1.2
+        }]);
+        let plan = ExplainPlan::from_json(output).unwrap();
+        assert_eq!(plan.total_cost(), 42.0);
+        assert_eq!(plan.execution_time, Some(1.2));
+        assert_eq!(plan.root.actual_rows, Some(3.0));
+        assert_eq!(plan.root.details["Hash Cond"], "(a.id = b.a_id)");
+        assert_eq!(plan.nodes().len(), 3);
+
+        let scans = plan.warn_sequential_scans(&["test_table", "another_table"]);
+        assert_eq!(scans.len(), 1);
+        assert_eq!(scans[0].relation_name.as_deref(), Some("test_table"));
+        assert!(plan.warn_sequential_scans(&["another_table"]).is_empty());
+    }
+    #[test]
+    fn finds_parallel_sequential_scans() {
+        let output = json!([{
+            "Plan": {
+                "Node Type": "Gather",
+                "Total Cost": 1000.0,
+                "Plans": [
+                    {
+                        "Node Type": "Parallel Seq Scan",
+                        "Relation Name": "test_table",
+                        "Total Cost": 900.0
+                    }
+                ]
+            }
+        }]);
+        let plan = ExplainPlan::from_json(output).unwrap();
+        let scans = plan.warn_sequential_scans(&["test_table"]);
+        assert_eq!(scans.len(), 1);
+        assert_eq!(scans[0].node_type, "Parallel Seq Scan");
+    }
+}
@@ -17,15 +17,21 @@ mod returning;
 pub use returning::*;
 mod conflict;
 mod other;
+mod param;
+mod source;
 use crate::{table_layout::*, traits::FormatSql};
 pub use conflict::*;
 pub use multi::*;
 pub use other::*;
+pub use param::*;
+pub use source::*;
 mod cast;
 mod collate;
+mod typed;
 pub use cast::*;
 pub use collate::*;
 pub use select_expr::*;
+pub use typed::*;
 pub struct DynExpr<'args>(Box<dyn ExprType<'args> + 'args>);
 impl<'args> DynExpr<'args> {
     pub fn new<E>(expr: E) -> Self
@@ -132,6 +138,71 @@ from_box_expr!(
     SQLMathExpr => Math
 );
 
+impl Expr {
+    /// Calls `visit` with the expression and every expression inside of it.
+    ///
+    /// Subqueries and [OtherSql] are not entered
+    pub(crate) fn walk(&self, visit: &mut impl FnMut(&Expr)) {
+        visit(self);
+        match self {
+            Expr::Function(function) => {
+                for expr in function.params().exprs() {
+                    expr.walk(visit);
+                }
+            }
+            Expr::Multiple(multiple) => {
+                for expr in multiple.exprs() {
+                    expr.walk(visit);
+                }
+            }
+            Expr::Condition(condition) => match condition.as_ref() {
+                SQLCondition::CompareValue { left, right, .. }
+                | SQLCondition::Then { left, right, .. } => {
+                    left.walk(visit);
+                    right.walk(visit);
+                }
+                SQLCondition::Between { value, start, end } => {
+                    value.walk(visit);
+                    start.walk(visit);
+                    end.walk(visit);
+                }
+                SQLCondition::NotNull(expr)
+                | SQLCondition::Null(expr)
+                | SQLCondition::Not(expr)
+                | SQLCondition::Grouped(expr)
+                | SQLCondition::Collate {
+                    expression: expr, ..
+                } => expr.walk(visit),
+            },
+            Expr::Math(math) => match math.as_ref() {
+                SQLMathExpr::TwoOperands { left, right, .. } => {
+                    left.walk(visit);
+                    right.walk(visit);
+                }
+                SQLMathExpr::OneOperand { value, .. } => value.walk(visit),
+            },
+            Expr::Alias(alias) => alias.expr().walk(visit),
+            Expr::Cast(cast) => cast.expr().walk(visit),
+            Expr::ArgumentIndex(_)
+            | Expr::Column(_)
+            | Expr::Select(_)
+            | Expr::Wildcard(_)
+            | Expr::Empty
+            | Expr::Other(_)
+            | Expr::Keywords(_) => {}
+        }
+    }
+    /// If a `#[column(sensitive)]` column is used in the expression
+    pub(crate) fn references_sensitive_column(&self) -> bool {
+        let mut sensitive = false;
+        self.walk(&mut |expr| {
+            if let Expr::Column(column) = expr {
+                sensitive |= column.column_info().is_some_and(|info| info.sensitive);
+            }
+        });
+        sensitive
+    }
+}
 impl FormatSql for Expr {
     fn format_sql(&self) -> Cow<'_, str> {
         match self {
@@ -42,6 +42,11 @@ pub struct ExprAlias {
     expr: Box<Expr>,
     alias: Cow<'static, str>,
 }
+impl ExprAlias {
+    pub(crate) fn expr(&self) -> &Expr {
+        &self.expr
+    }
+}
 impl FormatSql for ExprAlias {
     fn format_sql(&self) -> Cow<'_, str> {
         Cow::Owned(format!("{} AS {}", self.expr.format_sql(), self.alias))
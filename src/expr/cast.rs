@@ -32,6 +32,11 @@ pub struct Cast {
     expr: Box<Expr>,
     as_type: Cow<'static, str>,
 }
+impl Cast {
+    pub(crate) fn expr(&self) -> &Expr {
+        &self.expr
+    }
+}
 impl FormatSql for Cast {
     fn format_sql(&self) -> std::borrow::Cow<'_, str> {
         Cow::Owned(format!("{}::{}", self.expr.format_sql(), self.as_type))
@@ -66,6 +71,9 @@ pub trait CastableExprType<'args>: ExprType<'args> {
 
     cast_fn! {
         as_date => "DATE",
-        as_text => "TEXT"
+        as_text => "TEXT",
+        as_interval => "INTERVAL",
+        as_timestamp => "TIMESTAMP",
+        as_timestamptz => "TIMESTAMPTZ"
     }
 }
@@ -1,9 +1,11 @@
 use std::borrow::Cow;
 
-use crate::prelude::*;
+use crate::{prelude::*, table_layout::quote_ident};
 mod builder;
+mod excluded;
 mod query;
 pub use builder::*;
+pub use excluded::*;
 pub use query::*;
 pub enum SetColummBuilder<'args> {
     /// Does SET `column = excluded.column`
@@ -35,12 +37,12 @@ impl FormatSql for SetColumm {
         match self {
             Self::SetExcluded(column) => format!(
                 "{column_name} = EXCLUDED.{column_name}",
-                column_name = column.column_name()
+                column_name = column.quoted_column_name()
             )
             .into(),
             Self::SetExpr { column, expr } => format!(
                 "{column_name} = {expr}",
-                column_name = column.column_name(),
+                column_name = column.quoted_column_name(),
                 expr = expr.format_sql()
             )
             .into(),
@@ -51,6 +53,25 @@ impl FormatSql for SetColumm {
 pub enum ConflictAction {
     DoNothing,
     DoUpdate(Vec<SetColumm>),
+    /// `DO UPDATE SET ... WHERE {conditions}`. Rows that do not match are left unchanged
+    DoUpdateWhere {
+        columns: Vec<SetColumm>,
+        conditions: Vec<SQLCondition>,
+    },
+}
+fn format_set_columns(columns: &[SetColumm]) -> String {
+    columns
+        .iter()
+        .map(|column| column.format_sql())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+fn format_conditions(conditions: &[SQLCondition]) -> String {
+    conditions
+        .iter()
+        .map(|condition| condition.format_sql())
+        .collect::<Vec<_>>()
+        .join(" AND ")
 }
 
 impl FormatSql for ConflictAction {
@@ -58,13 +79,17 @@ impl FormatSql for ConflictAction {
         match self {
             Self::DoNothing => "DO NOTHING".into(),
             Self::DoUpdate(columns) => {
-                let columns = columns
-                    .iter()
-                    .map(|column| column.format_sql())
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("DO UPDATE SET {columns}").into()
+                format!("DO UPDATE SET {}", format_set_columns(columns)).into()
             }
+            Self::DoUpdateWhere {
+                columns,
+                conditions,
+            } => format!(
+                "DO UPDATE SET {} WHERE {}",
+                format_set_columns(columns),
+                format_conditions(conditions)
+            )
+            .into(),
         }
     }
 }
@@ -73,6 +98,13 @@ impl FormatSql for ConflictAction {
 pub enum ConflictTarget {
     Columns(Vec<DynColumn>),
     Constraint(&'static str),
+    /// `({elements}) [WHERE {predicate}]`. Infers expression and partial unique indexes.
+    ///
+    /// Built with [ConflictTargetBuilder]
+    Index {
+        elements: Vec<Expr>,
+        predicate: Vec<SQLCondition>,
+    },
 }
 impl ConflictTarget {
     pub fn columns<C>(columns: Vec<C>) -> Self
@@ -96,12 +128,29 @@ impl FormatSql for ConflictTarget {
             Self::Columns(columns) => {
                 let columns = columns
                     .iter()
-                    .map(|column| column.column_name())
+                    .map(|column| column.quoted_column_name())
                     .collect::<Vec<_>>()
                     .join(", ");
                 format!("({columns})").into()
             }
-            Self::Constraint(constraint) => format!("ON CONSTRAINT {constraint}").into(),
+            Self::Constraint(constraint) => {
+                format!("ON CONSTRAINT {}", quote_ident(constraint)).into()
+            }
+            Self::Index {
+                elements,
+                predicate,
+            } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| element.format_sql())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if predicate.is_empty() {
+                    format!("({elements})").into()
+                } else {
+                    format!("({elements}) WHERE {}", format_conditions(predicate)).into()
+                }
+            }
         }
     }
 }
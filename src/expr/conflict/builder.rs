@@ -1,6 +1,8 @@
-use crate::prelude::*;
+use std::borrow::Cow;
 
-use super::{ConflictAction, SetColumm};
+use crate::{error::Error, prelude::*};
+
+use super::{ConflictAction, ConflictTarget, SetColumm};
 
 pub enum SetColummBuilder<'args> {
     /// Does SET `column = excluded.column`
@@ -15,9 +17,15 @@ impl<C: ColumnType + 'static> From<C> for SetColummBuilder<'_> {
         Self::SetExcluded(column.dyn_column())
     }
 }
+type DynFilter<'args> = FilterConditionBuilder<'args, DynExpr<'args>, DynExpr<'args>>;
 pub enum ConflictActionBuilder<'args> {
     DoNothing,
     DoUpdate(Vec<SetColummBuilder<'args>>),
+    /// `DO UPDATE SET ... WHERE ...`. Created by calling `filter` on the builder
+    DoUpdateWhere {
+        columns: Vec<SetColummBuilder<'args>>,
+        conditions: Vec<DynFilter<'args>>,
+    },
 }
 impl<'args> ConflictActionBuilder<'args> {
     pub fn do_nothing() -> Self {
@@ -32,65 +40,218 @@ impl<'args> ConflictActionBuilder<'args> {
     {
         Self::DoUpdate(columns.into_iter().map(SetColummBuilder::from).collect())
     }
+    /// Splits the builder into the SET columns and the WHERE conditions
+    fn into_update(self) -> (Vec<SetColummBuilder<'args>>, Vec<DynFilter<'args>>) {
+        match self {
+            Self::DoNothing => (Vec::new(), Vec::new()),
+            Self::DoUpdate(columns) => (columns, Vec::new()),
+            Self::DoUpdateWhere {
+                columns,
+                conditions,
+            } => (columns, conditions),
+        }
+    }
+    fn from_update(
+        columns: Vec<SetColummBuilder<'args>>,
+        conditions: Vec<DynFilter<'args>>,
+    ) -> Self {
+        if conditions.is_empty() {
+            Self::DoUpdate(columns)
+        } else {
+            Self::DoUpdateWhere {
+                columns,
+                conditions,
+            }
+        }
+    }
     pub fn set_many_to_excluded<C>(self, new_columns: Vec<C>) -> Self
     where
         C: ColumnType + 'static,
     {
-        match self {
-            Self::DoUpdate(mut columns) => {
-                let new_columns: Vec<_> = new_columns
-                    .into_iter()
-                    .map(SetColummBuilder::from)
-                    .collect();
-                columns.extend(new_columns);
-                Self::DoUpdate(columns)
-            }
-            _ => Self::DoUpdate(
-                new_columns
-                    .into_iter()
-                    .map(SetColummBuilder::from)
-                    .collect(),
-            ),
-        }
+        let (mut columns, conditions) = self.into_update();
+        columns.extend(new_columns.into_iter().map(SetColummBuilder::from));
+        Self::from_update(columns, conditions)
     }
     pub fn set_column_to_excluded<C>(self, column: C) -> Self
     where
         C: ColumnType + 'static,
     {
-        match self {
-            Self::DoUpdate(mut columns) => {
-                columns.push(SetColummBuilder::SetExcluded(column.dyn_column()));
-                Self::DoUpdate(columns)
-            }
-            _ => Self::DoUpdate(vec![SetColummBuilder::SetExcluded(column.dyn_column())]),
-        }
+        let (mut columns, conditions) = self.into_update();
+        columns.push(SetColummBuilder::SetExcluded(column.dyn_column()));
+        Self::from_update(columns, conditions)
     }
 
     pub fn set_column(self, column: DynColumn, expr: DynExpr<'args>) -> Self {
-        match self {
-            Self::DoUpdate(mut columns) => {
-                columns.push(SetColummBuilder::SetExpr { column, expr });
-                Self::DoUpdate(columns)
-            }
-            _ => Self::DoUpdate(vec![SetColummBuilder::SetExpr { column, expr }]),
-        }
+        let (mut columns, conditions) = self.into_update();
+        columns.push(SetColummBuilder::SetExpr { column, expr });
+        Self::from_update(columns, conditions)
+    }
+    /// SET `column = {expr}`. Use [Excluded](super::Excluded) to reference the proposed row
+    pub fn set<C, E>(self, column: C, expr: E) -> Self
+    where
+        C: ColumnType + 'static,
+        E: ExprType<'args> + 'args,
+    {
+        self.set_column(column.dyn_column(), DynExpr::new(expr))
     }
+    /// A `DO UPDATE` without any SET columns is kept as an [Error::MissingClause] in the holder.
+    /// It is returned when the arguments are taken
     pub fn process(self, args: &mut ArgumentHolder<'args>) -> ConflictAction {
-        match self {
-            Self::DoNothing => ConflictAction::DoNothing,
-            Self::DoUpdate(columns) => {
-                let columns = columns
-                    .into_iter()
-                    .map(|column| match column {
-                        SetColummBuilder::SetExcluded(column) => SetColumm::SetExcluded(column),
-                        SetColummBuilder::SetExpr { column, expr } => SetColumm::SetExpr {
-                            column,
-                            expr: expr.process_unboxed(args),
-                        },
-                    })
-                    .collect();
-                ConflictAction::DoUpdate(columns)
-            }
+        if let Self::DoNothing = self {
+            return ConflictAction::DoNothing;
+        }
+        let (columns, conditions) = self.into_update();
+        if columns.is_empty() {
+            args.push_error(Error::MissingClause {
+                statement: "ON CONFLICT DO UPDATE",
+                clause: "SET",
+            });
+        }
+        let columns = columns
+            .into_iter()
+            .map(|column| match column {
+                SetColummBuilder::SetExcluded(column) => {
+                    args.check_updatable(&column);
+                    SetColumm::SetExcluded(column)
+                }
+                SetColummBuilder::SetExpr { column, expr } => {
+                    args.check_updatable(&column);
+                    SetColumm::SetExpr {
+                        column,
+                        expr: expr.process_unboxed(args),
+                    }
+                }
+            })
+            .collect();
+        if conditions.is_empty() {
+            return ConflictAction::DoUpdate(columns);
+        }
+        let conditions = conditions
+            .into_iter()
+            .map(|condition| condition.process_inner(args))
+            .collect();
+        ConflictAction::DoUpdateWhere {
+            columns,
+            conditions,
+        }
+    }
+}
+impl<'args> ExpressionWhereable<'args> for ConflictActionBuilder<'args> {
+    /// Turns the action into a `DO UPDATE`. Only filter actions that set columns.
+    ///
+    /// A filtered [do_nothing](Self::do_nothing) has no SET columns and the query returns [Error::MissingClause]
+    fn push_where_comparison<L: ExprType<'args> + 'args, R: ExprType<'args> + 'args>(
+        &mut self,
+        comparison: FilterConditionBuilder<'args, L, R>,
+    ) {
+        let (columns, mut conditions) = std::mem::replace(self, Self::DoNothing).into_update();
+        conditions.push(comparison.dyn_expression());
+        *self = Self::from_update(columns, conditions);
+    }
+}
+/// A column of the conflict target. Not qualified with the table name
+#[derive(Debug)]
+struct IndexColumn(DynColumn);
+impl FormatSql for IndexColumn {
+    fn format_sql(&self) -> Cow<'_, str> {
+        self.0.quoted_column_name()
+    }
+}
+/// Infers a unique index by its columns, expressions and predicate.
+///
+/// `ON CONFLICT ({elements}) WHERE {predicate}`
+/// ```
+/// use pg_extended_sqlx_queries::prelude::*;
+/// #[derive(TableType)]
+/// #[table(name = "users")]
+/// pub struct User {
+///     #[column(primary_key)]
+///     pub id: i32,
+///     pub email: String,
+///     pub deleted_at: Option<i64>,
+/// }
+/// let mut query = InsertQueryBuilder::new(User::table_name());
+/// query.insert(UserColumn::Email, "a@example.com").on_conflict_do_nothing(
+///     ConflictTargetBuilder::expression(SqlFunctionBuilder::lower().add_param(UserColumn::Email))
+///         .filter(UserColumn::DeletedAt.is_null()),
+/// );
+/// assert_eq!(
+///     query.format_sql_query(),
+///     "INSERT INTO users (email) VALUES ($1) ON CONFLICT (LOWER(users.email)) WHERE users.deleted_at IS NULL DO NOTHING;"
+/// );
+/// ```
+#[derive(Default)]
+pub struct ConflictTargetBuilder<'args> {
+    elements: Vec<DynExpr<'args>>,
+    predicate: Vec<DynFilter<'args>>,
+}
+impl<'args> ConflictTargetBuilder<'args> {
+    pub fn columns<C>(columns: Vec<C>) -> Self
+    where
+        C: ColumnType + 'static,
+    {
+        let mut this = Self::default();
+        for column in columns {
+            this = this.column(column);
+        }
+        this
+    }
+    pub fn expression<E>(expr: E) -> Self
+    where
+        E: ExprType<'args> + 'args,
+    {
+        Self::default().and_expression(expr)
+    }
+    pub fn column<C>(mut self, column: C) -> Self
+    where
+        C: ColumnType + 'static,
+    {
+        self.elements.push(DynExpr::new(OtherSql::new(IndexColumn(
+            column.dyn_column(),
+        ))));
+        self
+    }
+    pub fn and_expression<E>(mut self, expr: E) -> Self
+    where
+        E: ExprType<'args> + 'args,
+    {
+        self.elements.push(DynExpr::new(expr));
+        self
+    }
+    pub fn process(self, args: &mut ArgumentHolder<'args>) -> ConflictTarget {
+        ConflictTarget::Index {
+            elements: self
+                .elements
+                .into_iter()
+                .map(|element| element.process_unboxed(args))
+                .collect(),
+            predicate: self
+                .predicate
+                .into_iter()
+                .map(|condition| condition.process_inner(args))
+                .collect(),
         }
     }
 }
+impl<'args> ExpressionWhereable<'args> for ConflictTargetBuilder<'args> {
+    fn push_where_comparison<L: ExprType<'args> + 'args, R: ExprType<'args> + 'args>(
+        &mut self,
+        comparison: FilterConditionBuilder<'args, L, R>,
+    ) {
+        self.predicate.push(comparison.dyn_expression());
+    }
+}
+/// Accepted as the target of [ConflictQuery::on_conflict](super::ConflictQuery::on_conflict)
+pub trait IntoConflictTarget<'args> {
+    fn into_conflict_target(self, args: &mut ArgumentHolder<'args>) -> ConflictTarget;
+}
+impl<'args> IntoConflictTarget<'args> for ConflictTarget {
+    fn into_conflict_target(self, _: &mut ArgumentHolder<'args>) -> ConflictTarget {
+        self
+    }
+}
+impl<'args> IntoConflictTarget<'args> for ConflictTargetBuilder<'args> {
+    fn into_conflict_target(self, args: &mut ArgumentHolder<'args>) -> ConflictTarget {
+        self.process(args)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{prelude::*, table_layout::PrefixedColumn};
+
+/// The value that was proposed for insertion. `EXCLUDED.{column}`
+///
+/// Only valid in the `DO UPDATE` action of an `ON CONFLICT`
+/// ```
+/// use pg_extended_sqlx_queries::prelude::*;
+/// #[derive(TableType)]
+/// #[table(name = "stats")]
+/// pub struct Stats {
+///     #[column(primary_key)]
+///     pub id: i32,
+///     pub count: i32,
+/// }
+/// let mut query = InsertQueryBuilder::new(Stats::table_name());
+/// query.insert(StatsColumn::Id, 1).insert(StatsColumn::Count, 1).on_conflict(
+///     ConflictTarget::columns(vec![StatsColumn::Id]),
+///     ConflictActionBuilder::do_update()
+///         .set(StatsColumn::Count, StatsColumn::Count.add(Excluded(StatsColumn::Count))),
+/// );
+/// assert_eq!(
+///     query.format_sql_query(),
+///     "INSERT INTO stats (id, count) VALUES ($1, $2) ON CONFLICT (id) DO UPDATE SET count = (stats.count + EXCLUDED.count);"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Excluded<C: ColumnType>(pub C);
+
+impl<'args, C> ExprType<'args> for Excluded<C>
+where
+    C: ColumnType + 'static,
+{
+    fn process(self: Box<Self>, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        self.process_unboxed(args)
+    }
+
+    fn process_unboxed(self, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::Column(DynColumn::new(PrefixedColumn::new("EXCLUDED", self.0)))
+    }
+}
+impl<'args, C> Aliasable<'args> for Excluded<C> where C: ColumnType + 'static {}
+impl<'args, C> WrapInFunction<'args> for Excluded<C> where C: ColumnType + 'static {}
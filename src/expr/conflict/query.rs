@@ -1,17 +1,17 @@
 use crate::prelude::*;
 
-use super::{ConflictActionBuilder, ConflictTarget, OnConflict};
+use super::{ConflictActionBuilder, IntoConflictTarget, OnConflict};
 
 pub trait ConflictQuery<'args>: HasArguments<'args> {
     fn set_on_conflict(&mut self, on_conflict: OnConflict) -> &mut Self;
 
     fn on_conflict(
         &mut self,
-        target: ConflictTarget,
+        target: impl IntoConflictTarget<'args>,
         action: ConflictActionBuilder<'args>,
     ) -> &mut Self {
         let on_conflict = OnConflict {
-            conflict_target: target,
+            conflict_target: target.into_conflict_target(self.holder()),
             action: action.process(self.holder()),
         };
         self.set_on_conflict(on_conflict)
@@ -19,13 +19,13 @@ pub trait ConflictQuery<'args>: HasArguments<'args> {
 
     fn on_conflict_set_excluded<C: ColumnType + 'static>(
         &mut self,
-        target: ConflictTarget,
+        target: impl IntoConflictTarget<'args>,
         columns: Vec<C>,
     ) -> &mut Self {
         self.on_conflict(target, ConflictActionBuilder::update_to_excluded(columns))
     }
 
-    fn on_conflict_do_nothing(&mut self, target: ConflictTarget) -> &mut Self {
+    fn on_conflict_do_nothing(&mut self, target: impl IntoConflictTarget<'args>) -> &mut Self {
         self.on_conflict(target, ConflictActionBuilder::do_nothing())
     }
 }
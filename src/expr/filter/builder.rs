@@ -89,16 +89,25 @@ impl<'args, L: ExprType<'args> + 'args, R: ExprType<'args> + 'args>
                 left,
                 comparison,
                 right,
-            } => SQLCondition::CompareValue {
-                left: left.process_unboxed(args),
-                comparison,
-                right: right.process_unboxed(args),
-            },
-            FilterConditionBuilderInner::Between { value, start, end } => SQLCondition::Between {
-                value: value.process_unboxed(args),
-                start: start.process_unboxed(args),
-                end: end.process_unboxed(args),
-            },
+            } => {
+                let start = args.debug_values().len();
+                let left = left.process_unboxed(args);
+                let right = right.process_unboxed(args);
+                args.mark_sensitive_comparison(&[&left, &right], start);
+                SQLCondition::CompareValue {
+                    left,
+                    comparison,
+                    right,
+                }
+            }
+            FilterConditionBuilderInner::Between { value, start, end } => {
+                let first_argument = args.debug_values().len();
+                let value = value.process_unboxed(args);
+                let start = start.process_unboxed(args);
+                let end = end.process_unboxed(args);
+                args.mark_sensitive_comparison(&[&value, &start, &end], first_argument);
+                SQLCondition::Between { value, start, end }
+            }
             FilterConditionBuilderInner::NotNull(expr) => {
                 SQLCondition::NotNull(expr.process_unboxed(args))
             }
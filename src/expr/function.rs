@@ -1,8 +1,10 @@
 use std::borrow::Cow;
 mod expr;
 pub use expr::*;
+pub mod datetime;
 pub mod extract;
 use crate::traits::FormatSql;
+pub use datetime::*;
 pub use extract::*;
 
 use super::{
@@ -76,6 +78,30 @@ impl<'args> SqlFunctionBuilder<'args> {
     pub fn upper() -> Self {
         Self::new("UPPER")
     }
+    /// `AGE(end, start)` the interval between two timestamps
+    ///
+    /// For `AGE(timestamp)` use [WrapInFunction::age](super::WrapInFunction::age)
+    pub fn age_between<E, S>(end: E, start: S) -> Self
+    where
+        E: ExprType<'args> + 'args,
+        S: ExprType<'args> + 'args,
+    {
+        Self::new("AGE").add_param(end).add_param(start)
+    }
+    /// `GENERATE_SERIES(start, stop, step)`
+    ///
+    /// When used with timestamps the step should be an interval.
+    pub fn generate_series<Start, Stop, Step>(start: Start, stop: Stop, step: Step) -> Self
+    where
+        Start: ExprType<'args> + 'args,
+        Stop: ExprType<'args> + 'args,
+        Step: ExprType<'args> + 'args,
+    {
+        Self::new("GENERATE_SERIES")
+            .add_param(start)
+            .add_param(stop)
+            .add_param(step)
+    }
 }
 impl<'args> ExprType<'args> for SqlFunctionBuilder<'args> {
     fn process(self: Box<Self>, args: &mut ArgumentHolder<'args>) -> Expr
@@ -112,6 +138,11 @@ pub struct SqlFunction<Params: FormatSql = MultipleExpr> {
     params: Params,
 }
 
+impl<Params: FormatSql> SqlFunction<Params> {
+    pub(crate) fn params(&self) -> &Params {
+        &self.params
+    }
+}
 impl<Params: FormatSql> FormatSql for SqlFunction<Params> {
     fn format_sql(&self) -> std::borrow::Cow<'_, str> {
         let params = self.params.format_sql();
@@ -0,0 +1,280 @@
+//! Date/Time functions and operators
+//!
+//! Reference: https://www.postgresql.org/docs/current/functions-datetime.html
+use std::borrow::Cow;
+
+use crate::{
+    expr::{
+        Aliasable, ArgumentHolder, DynExpr, Expr, ExprType, Keywords, MultipleExprBuilder,
+        WrapInFunction,
+    },
+    traits::FormatSql,
+};
+
+use super::SqlFunctionBuilder;
+
+/// The precision used by `DATE_TRUNC`
+///
+/// Reference: https://www.postgresql.org/docs/current/functions-datetime.html#FUNCTIONS-DATETIME-TRUNC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTruncField {
+    Microseconds,
+    Milliseconds,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    Decade,
+    Century,
+    Millennium,
+}
+impl FormatSql for DateTruncField {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            DateTruncField::Microseconds => Cow::Borrowed("'microseconds'"),
+            DateTruncField::Milliseconds => Cow::Borrowed("'milliseconds'"),
+            DateTruncField::Second => Cow::Borrowed("'second'"),
+            DateTruncField::Minute => Cow::Borrowed("'minute'"),
+            DateTruncField::Hour => Cow::Borrowed("'hour'"),
+            DateTruncField::Day => Cow::Borrowed("'day'"),
+            DateTruncField::Week => Cow::Borrowed("'week'"),
+            DateTruncField::Month => Cow::Borrowed("'month'"),
+            DateTruncField::Quarter => Cow::Borrowed("'quarter'"),
+            DateTruncField::Year => Cow::Borrowed("'year'"),
+            DateTruncField::Decade => Cow::Borrowed("'decade'"),
+            DateTruncField::Century => Cow::Borrowed("'century'"),
+            DateTruncField::Millennium => Cow::Borrowed("'millennium'"),
+        }
+    }
+}
+impl<'args> ExprType<'args> for DateTruncField {
+    fn process(self: Box<Self>, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::Keywords((*self).into())
+    }
+
+    fn process_unboxed(self, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::Keywords(self.into())
+    }
+}
+/// The named arguments accepted by `MAKE_INTERVAL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalField {
+    Years,
+    Months,
+    Weeks,
+    Days,
+    Hours,
+    Mins,
+    Secs,
+}
+impl FormatSql for IntervalField {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            IntervalField::Years => Cow::Borrowed("years"),
+            IntervalField::Months => Cow::Borrowed("months"),
+            IntervalField::Weeks => Cow::Borrowed("weeks"),
+            IntervalField::Days => Cow::Borrowed("days"),
+            IntervalField::Hours => Cow::Borrowed("hours"),
+            IntervalField::Mins => Cow::Borrowed("mins"),
+            IntervalField::Secs => Cow::Borrowed("secs"),
+        }
+    }
+}
+impl<'args> ExprType<'args> for IntervalField {
+    fn process(self: Box<Self>, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::Keywords((*self).into())
+    }
+
+    fn process_unboxed(self, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::Keywords(self.into())
+    }
+}
+/// Builds a `MAKE_INTERVAL(...)` call using named arguments.
+///
+/// Fields that are not set are left to their Postgres default of 0.
+///
+/// Postgres expects `integer` for every field except `secs` which is a `double precision`
+///
+/// ```rust
+/// use pg_extended_sqlx_queries::prelude::*;
+/// use pg_extended_sqlx_queries::fake::FakeQuery;
+/// let mut fake_query = FakeQuery::default();
+/// let expr = MakeIntervalBuilder::new().days(7).hours(12);
+/// let expr = expr.process_unboxed(fake_query.holder());
+/// assert_eq!(expr.format_sql(), "MAKE_INTERVAL(days => $1, hours => $2)");
+/// ```
+#[derive(Default)]
+pub struct MakeIntervalBuilder<'args> {
+    fields: Vec<(IntervalField, DynExpr<'args>)>,
+}
+macro_rules! interval_field {
+    (
+        $(
+            $name:ident => $field:ident
+        ),*
+    ) => {
+        $(
+            pub fn $name<E>(self, value: E) -> Self
+            where
+                E: ExprType<'args> + 'args,
+            {
+                self.field(IntervalField::$field, value)
+            }
+        )*
+    };
+}
+impl<'args> MakeIntervalBuilder<'args> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets a field of the interval. Setting the same field twice will override the previous value
+    pub fn field<E>(mut self, field: IntervalField, value: E) -> Self
+    where
+        E: ExprType<'args> + 'args,
+    {
+        self.fields.retain(|(existing, _)| *existing != field);
+        self.fields.push((field, DynExpr::new(value)));
+        self
+    }
+    interval_field!(
+        years => Years,
+        months => Months,
+        weeks => Weeks,
+        days => Days,
+        hours => Hours,
+        mins => Mins,
+        secs => Secs
+    );
+}
+impl<'args> ExprType<'args> for MakeIntervalBuilder<'args> {
+    fn process(self: Box<Self>, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        self.process_unboxed(args)
+    }
+
+    fn process_unboxed(self, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        let mut function = SqlFunctionBuilder::new("MAKE_INTERVAL");
+        for (field, value) in self.fields {
+            let param = MultipleExprBuilder::new()
+                .push(field)
+                .push(Keywords::NamedArgument)
+                .push(value);
+            function = function.add_param(param);
+        }
+        function.process_unboxed(args)
+    }
+}
+impl<'args> Aliasable<'args> for MakeIntervalBuilder<'args> {}
+impl<'args> WrapInFunction<'args> for MakeIntervalBuilder<'args> {}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::postgres::types::PgInterval;
+
+    use crate::fake::FakeQuery;
+    use crate::prelude::*;
+    use crate::testing::TestTableColumn;
+
+    #[test]
+    pub fn now_minus_interval() {
+        let expr = SqlFunctionBuilder::now().subtract(PgInterval {
+            months: 0,
+            days: 7,
+            microseconds: 0,
+        });
+
+        let mut parent = FakeQuery::default();
+        let expr = expr.process_unboxed(&mut parent.arguments);
+
+        assert_eq!(expr.format_sql(), "(NOW() - $1)");
+    }
+    #[test]
+    pub fn now_minus_make_interval() {
+        let expr = SqlFunctionBuilder::now().subtract(MakeIntervalBuilder::new().days(7));
+
+        let mut parent = FakeQuery::default();
+        let expr = expr.process_unboxed(&mut parent.arguments);
+
+        assert_eq!(expr.format_sql(), "(NOW() - MAKE_INTERVAL(days => $1))");
+    }
+    #[test]
+    pub fn date_trunc() {
+        let expr = TestTableColumn::CreatedAt.date_trunc(DateTruncField::Day);
+
+        let mut parent = FakeQuery::default();
+        let expr = expr.process_unboxed(&mut parent.arguments);
+
+        assert_eq!(
+            expr.format_sql(),
+            "DATE_TRUNC('day', test_table.created_at)"
+        );
+    }
+    #[test]
+    pub fn at_time_zone() {
+        let expr = TestTableColumn::CreatedAt.at_time_zone("America/New_York");
+
+        let mut parent = FakeQuery::default();
+        let expr = expr.process_unboxed(&mut parent.arguments);
+
+        assert_eq!(expr.format_sql(), "(test_table.created_at AT TIME ZONE $1)");
+    }
+    #[test]
+    pub fn age_and_to_char() {
+        let mut parent = FakeQuery::default();
+
+        let age = TestTableColumn::CreatedAt
+            .age()
+            .process_unboxed(&mut parent.arguments);
+        assert_eq!(age.format_sql(), "AGE(test_table.created_at)");
+
+        let age_between =
+            SqlFunctionBuilder::age_between(TestTableColumn::UpdatedAt, TestTableColumn::CreatedAt)
+                .process_unboxed(&mut parent.arguments);
+        assert_eq!(
+            age_between.format_sql(),
+            "AGE(test_table.updated_at, test_table.created_at)"
+        );
+
+        let to_char = TestTableColumn::CreatedAt
+            .to_char("YYYY-MM-DD")
+            .process_unboxed(&mut parent.arguments);
+        assert_eq!(to_char.format_sql(), "TO_CHAR(test_table.created_at, $1)");
+    }
+    #[test]
+    pub fn generate_series() {
+        let expr = SqlFunctionBuilder::generate_series(
+            SqlFunctionBuilder::now().subtract(MakeIntervalBuilder::new().days(7)),
+            SqlFunctionBuilder::now(),
+            MakeIntervalBuilder::new().days(1),
+        );
+
+        let mut parent = FakeQuery::default();
+        let expr = expr.process_unboxed(&mut parent.arguments);
+
+        assert_eq!(
+            expr.format_sql(),
+            "GENERATE_SERIES((NOW() - MAKE_INTERVAL(days => $1)), NOW(), MAKE_INTERVAL(days => $2))"
+        );
+    }
+}
@@ -42,7 +42,8 @@ pub trait WrapInFunction<'args>: ExprType<'args> + 'args {
         ln => "LN",
         log10 => "LOG10",
         degrees => "DEGREES",
-        radians => "RADIANS"
+        radians => "RADIANS",
+        age => "AGE"
     );
 
     /// Calls Postgres EXTRACT function on the given expression.
@@ -58,4 +59,41 @@ pub trait WrapInFunction<'args>: ExprType<'args> + 'args {
             .push(self);
         SqlFunctionBuilder::new("EXTRACT").add_param(params)
     }
+
+    /// Calls Postgres DATE_TRUNC function on the given expression.
+    ///
+    /// Reference: https://www.postgresql.org/docs/current/functions-datetime.html#FUNCTIONS-DATETIME-TRUNC
+    fn date_trunc(self, field: DateTruncField) -> SqlFunctionBuilder<'args>
+    where
+        Self: Sized,
+    {
+        SqlFunctionBuilder::new("DATE_TRUNC")
+            .add_param(field)
+            .add_param(self)
+    }
+    /// Calls Postgres TO_CHAR function on the given expression.
+    ///
+    /// Reference: https://www.postgresql.org/docs/current/functions-formatting.html
+    fn to_char<F>(self, format: F) -> SqlFunctionBuilder<'args>
+    where
+        Self: Sized,
+        F: ExprType<'args> + 'args,
+    {
+        SqlFunctionBuilder::new("TO_CHAR")
+            .add_param(self)
+            .add_param(format)
+    }
+    /// `({self} AT TIME ZONE {zone})`
+    ///
+    /// Reference: https://www.postgresql.org/docs/current/functions-datetime.html#FUNCTIONS-DATETIME-ZONECONVERT
+    fn at_time_zone<Z>(self, zone: Z) -> MultipleExprBuilder<'args>
+    where
+        Self: Sized,
+        Z: ExprType<'args> + 'args,
+    {
+        MultipleExprBuilder::with(self)
+            .then(Keywords::AtTimeZone)
+            .then(zone)
+            .grouped()
+    }
 }
@@ -14,6 +14,14 @@ pub enum ExtractType {
     Minute,
     Second,
     DayOfYear,
+    /// Seconds since 1970-01-01 00:00:00 UTC
+    Epoch,
+    /// Day of the week as Monday (1) to Sunday (7)
+    IsoDayOfWeek,
+    Quarter,
+    /// The time zone offset from UTC, in seconds
+    Timezone,
+    Microseconds,
 }
 impl<'args> ExprType<'args> for ExtractType {
     fn process(self: Box<Self>, _: &mut ArgumentHolder<'args>) -> Expr
@@ -43,6 +51,11 @@ impl FormatSql for ExtractType {
             ExtractType::Minute => std::borrow::Cow::Borrowed("MINUTE"),
             ExtractType::Second => std::borrow::Cow::Borrowed("SECOND"),
             ExtractType::DayOfYear => std::borrow::Cow::Borrowed("DOY"),
+            ExtractType::Epoch => std::borrow::Cow::Borrowed("EPOCH"),
+            ExtractType::IsoDayOfWeek => std::borrow::Cow::Borrowed("ISODOW"),
+            ExtractType::Quarter => std::borrow::Cow::Borrowed("QUARTER"),
+            ExtractType::Timezone => std::borrow::Cow::Borrowed("TIMEZONE"),
+            ExtractType::Microseconds => std::borrow::Cow::Borrowed("MICROSECONDS"),
         }
     }
 }
@@ -63,4 +76,16 @@ mod tests {
 
         assert_eq!(expr.format_sql(), "EXTRACT(DAY FROM test_table.created_at)");
     }
+    #[test]
+    pub fn test_extract_epoch() {
+        let expr = TestTableColumn::CreatedAt.extract(ExtractType::Epoch);
+
+        let mut parent = FakeQuery::default();
+        let expr = expr.process_unboxed(&mut parent.arguments);
+
+        assert_eq!(
+            expr.format_sql(),
+            "EXTRACT(EPOCH FROM test_table.created_at)"
+        );
+    }
 }
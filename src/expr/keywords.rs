@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use crate::traits::FormatSql;
 
-use super::{ArgumentHolder, Expr, ExprType, ExtractType};
+use super::{ArgumentHolder, DateTruncField, Expr, ExprType, ExtractType, IntervalField};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Keywords {
@@ -13,14 +13,32 @@ pub enum Keywords {
     Null,
     Limit,
     Offset,
+    /// `AT TIME ZONE`
+    AtTimeZone,
+    /// The `=>` used for named function arguments
+    NamedArgument,
     /// Extract types
     ExtractTypes(ExtractType),
+    /// The precision passed to `DATE_TRUNC`
+    DateTruncField(DateTruncField),
+    /// The named arguments of `MAKE_INTERVAL`
+    IntervalField(IntervalField),
 }
 impl From<ExtractType> for Keywords {
     fn from(extract: ExtractType) -> Self {
         Keywords::ExtractTypes(extract)
     }
 }
+impl From<DateTruncField> for Keywords {
+    fn from(field: DateTruncField) -> Self {
+        Keywords::DateTruncField(field)
+    }
+}
+impl From<IntervalField> for Keywords {
+    fn from(field: IntervalField) -> Self {
+        Keywords::IntervalField(field)
+    }
+}
 impl FormatSql for Keywords {
     fn format_sql(&self) -> std::borrow::Cow<'_, str> {
         match self {
@@ -31,7 +49,11 @@ impl FormatSql for Keywords {
             Keywords::Null => std::borrow::Cow::Borrowed("NULL"),
             Keywords::Limit => std::borrow::Cow::Borrowed("LIMIT"),
             Keywords::Offset => std::borrow::Cow::Borrowed("OFFSET"),
+            Keywords::AtTimeZone => std::borrow::Cow::Borrowed("AT TIME ZONE"),
+            Keywords::NamedArgument => std::borrow::Cow::Borrowed("=>"),
             Keywords::ExtractTypes(extract) => extract.format_sql(),
+            Keywords::DateTruncField(field) => field.format_sql(),
+            Keywords::IntervalField(field) => field.format_sql(),
         }
     }
 }
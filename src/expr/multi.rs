@@ -8,6 +8,7 @@ pub trait MultipleExprType<'args>: ExprType<'args> {
 }
 pub struct MultipleExprBuilder<'args> {
     functions: Vec<DynExpr<'args>>,
+    grouped: bool,
     phantom: std::marker::PhantomData<&'args ()>,
 }
 impl<'args> Aliasable<'args> for MultipleExprBuilder<'args> {}
@@ -21,12 +22,14 @@ impl<'args> MultipleExprBuilder<'args> {
     pub fn new() -> Self {
         Self {
             functions: Vec::new(),
+            grouped: false,
             phantom: std::marker::PhantomData,
         }
     }
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             functions: Vec::with_capacity(capacity),
+            grouped: false,
             phantom: std::marker::PhantomData,
         }
     }
@@ -43,6 +46,11 @@ impl<'args> MultipleExprBuilder<'args> {
         self.functions.push(DynExpr::new(function));
         self
     }
+    /// Groups the expressions in parenthesis
+    pub fn grouped(mut self) -> Self {
+        self.grouped = true;
+        self
+    }
     pub(crate) fn process_inner(self, args: &mut ArgumentHolder<'args>) -> MultipleExpr {
         self.process_inner_with_seperator(args, " ")
     }
@@ -51,13 +59,15 @@ impl<'args> MultipleExprBuilder<'args> {
         args: &mut ArgumentHolder<'args>,
         seperator: impl Into<Cow<'static, str>>,
     ) -> MultipleExpr {
-        MultipleExpr::with_separator(
+        let mut exprs = MultipleExpr::with_separator(
             self.functions
                 .into_iter()
                 .map(|function| function.process_unboxed(args))
                 .collect(),
             seperator,
-        )
+        );
+        exprs.grouped = self.grouped;
+        exprs
     }
 }
 impl<'args> MultipleExprType<'args> for MultipleExprBuilder<'args> {
@@ -73,12 +83,14 @@ impl<'args> MultipleExprType<'args> for MultipleExprBuilder<'args> {
 pub struct MultipleExpr {
     functions: Vec<Expr>,
     seperator: Cow<'static, str>,
+    grouped: bool,
 }
 impl Default for MultipleExpr {
     fn default() -> Self {
         Self {
             functions: Vec::new(),
             seperator: Cow::Borrowed(" "),
+            grouped: false,
         }
     }
 }
@@ -87,17 +99,22 @@ impl MultipleExpr {
         Self {
             functions,
             seperator: Cow::Borrowed(" "),
+            grouped: false,
         }
     }
     pub fn with_separator(functions: Vec<Expr>, seperator: impl Into<Cow<'static, str>>) -> Self {
         Self {
             functions,
             seperator: seperator.into(),
+            grouped: false,
         }
     }
     pub fn set_separator(&mut self, seperator: impl Into<Cow<'static, str>>) {
         self.seperator = seperator.into();
     }
+    pub(crate) fn exprs(&self) -> &[Expr] {
+        &self.functions
+    }
 }
 impl FormatSql for MultipleExpr {
     fn format_sql(&self) -> std::borrow::Cow<'_, str> {
@@ -107,7 +124,11 @@ impl FormatSql for MultipleExpr {
             .map(|function| function.format_sql())
             .collect::<Vec<_>>()
             .join(&self.seperator);
-        Cow::Owned(functions)
+        if self.grouped {
+            Cow::Owned(format!("({functions})"))
+        } else {
+            Cow::Owned(functions)
+        }
     }
 }
 
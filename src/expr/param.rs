@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+
+use super::{ArgumentHolder, Expr, ExprType, WrapInFunction};
+
+/// A named parameter whose value is bound when a [QueryTemplate](crate::template::QueryTemplate) is executed.
+///
+/// Using the same name twice reuses the same placeholder.
+///
+/// A query with named parameters can not contain bound values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param(Cow<'static, str>);
+impl Param {
+    pub fn named(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+impl<'args> ExprType<'args> for Param {
+    fn process(self: Box<Self>, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        self.process_unboxed(args)
+    }
+
+    fn process_unboxed(self, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::ArgumentIndex(args.push_named_param(self.0))
+    }
+}
+impl WrapInFunction<'_> for Param {}
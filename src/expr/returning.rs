@@ -1,7 +1,21 @@
+use std::borrow::Cow;
+
+use sqlx::{
+    FromRow, Postgres,
+    postgres::{PgArguments, PgRow},
+    query::QueryAs,
+};
+
 use crate::{
-    ColumnType, prelude::DynColumn, table_layout::concat_columns_no_table_name, traits::FormatSql,
+    ColumnType,
+    error::Error,
+    prelude::{AllColumns, DynColumn, TableType},
+    table_layout::concat_columns_no_table_name,
+    traits::{FormatSql, QueryTool},
 };
 
+use super::{Expr, ExprType, HasArguments};
+
 #[derive(Debug)]
 pub enum Returning {
     /// Return Wildcard
@@ -12,15 +26,42 @@ pub enum Returning {
     ///
     /// `RETURNING column1, column2`
     Columns(Vec<DynColumn>),
+    /// Return any expressions
+    ///
+    /// `RETURNING expr1, expr2 AS alias`
+    Expressions(Vec<Expr>),
+}
+impl Returning {
+    /// Prefixes the columns with the table. i.e. `RETURNING {table}.id` or `RETURNING {table}.*`
+    ///
+    /// Used by statements with other sources (`USING`, `FROM`) that can have columns with the same names
+    pub fn format_qualified(&self, table: &str) -> Cow<'_, str> {
+        match self {
+            Self::All => format!("RETURNING {table}.*").into(),
+            Self::Columns(columns) => {
+                let columns: Vec<_> = columns
+                    .iter()
+                    .map(|column| format!("{table}.{}", column.quoted_column_name()))
+                    .collect();
+                format!("RETURNING {}", columns.join(", ")).into()
+            }
+            Self::Expressions(_) => self.format_sql(),
+        }
+    }
 }
 impl FormatSql for Returning {
-    fn format_sql(&self) -> std::borrow::Cow<'_, str> {
+    fn format_sql(&self) -> Cow<'_, str> {
         match self {
             Self::All => "RETURNING *".into(),
             Self::Columns(columns) => {
                 let columns = concat_columns_no_table_name(columns);
                 format!("RETURNING {columns}").into()
             }
+            Self::Expressions(expressions) => {
+                let expressions: Vec<_> =
+                    expressions.iter().map(|expr| expr.format_sql()).collect();
+                format!("RETURNING {}", expressions.join(", ")).into()
+            }
         }
     }
 }
@@ -37,4 +78,38 @@ pub trait SupportsReturning {
             columns.into_iter().map(|c| c.dyn_column()).collect(),
         ))
     }
+    fn return_expressions<'args, I, E>(&mut self, expressions: I) -> &mut Self
+    where
+        Self: HasArguments<'args>,
+        I: IntoIterator<Item = E>,
+        E: ExprType<'args> + 'args,
+    {
+        let expressions = expressions
+            .into_iter()
+            .map(|expr| expr.process_unboxed(self.holder()))
+            .collect();
+        self.returning(Returning::Expressions(expressions))
+    }
+    /// Returns every column of the table and decodes the rows into it
+    fn query_returning<'args, T>(&mut self) -> QueryAs<'_, Postgres, T, PgArguments>
+    where
+        Self: QueryTool<'args>,
+        T: TableType + for<'r> FromRow<'r, PgRow>,
+        T::Columns: AllColumns,
+    {
+        self.return_columns(T::Columns::all());
+        self.query_as()
+    }
+    /// [SupportsReturning::query_returning] that returns an [Error] instead of panicking
+    fn try_query_returning<'args, T>(
+        &mut self,
+    ) -> Result<QueryAs<'_, Postgres, T, PgArguments>, Error>
+    where
+        Self: QueryTool<'args>,
+        T: TableType + for<'r> FromRow<'r, PgRow>,
+        T::Columns: AllColumns,
+    {
+        self.return_columns(T::Columns::all());
+        self.try_query_as()
+    }
 }
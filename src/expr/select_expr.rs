@@ -2,6 +2,7 @@ use std::borrow::Cow;
 mod join;
 use crate::prelude::ColumnType;
 use crate::select::{Join, JoinType};
+use crate::table_layout::quote_table_reference;
 use crate::traits::{ExpressionWhereable, FormatSql, FormatWhere};
 use crate::{pagination::PaginationOwnedSupportingTool, prelude::DynColumn};
 pub use join::*;
@@ -10,7 +11,7 @@ use super::{Aliasable, DynExpr, Expr, ExprType, FilterConditionBuilder, WrapInFu
 use super::{SQLCondition, SQLOrder, arguments::ArgumentHolder};
 #[derive(Debug, PartialEq)]
 pub struct SelectExpr {
-    table: &'static str,
+    table: Cow<'static, str>,
     select: Vec<Expr>,
     where_comparisons: Vec<SQLCondition>,
     limit: Option<i64>,
@@ -25,6 +26,15 @@ impl FormatWhere for SelectExpr {
 }
 impl FormatSql for SelectExpr {
     fn format_sql(&self) -> Cow<'_, str> {
+        Cow::Owned(self.format_select(true))
+    }
+}
+impl SelectExpr {
+    /// Formats the subquery for `FROM` or `USING`. Multiple columns are not wrapped into a row
+    pub(crate) fn format_as_source(&self) -> String {
+        self.format_select(false)
+    }
+    fn format_select(&self, single_value: bool) -> String {
         let mut columns: Vec<_> = self.select.iter().map(|item| item.format_sql()).collect();
         for join in &self.joins {
             columns.extend(join.columns_to_select.iter().map(|expr| expr.format_sql()));
@@ -34,7 +44,7 @@ impl FormatSql for SelectExpr {
         // Wrap the {columns} in parentheses because they are only allowed to return 1 column
         let distinct = if self.distinct { " DISTINCT " } else { " " };
 
-        let mut sql = if self.select.len() == 1 {
+        let mut sql = if self.select.len() == 1 || !single_value {
             format!(
                 "(SELECT{distinct}{columns} FROM {table}",
                 columns = concat_columns,
@@ -68,12 +78,11 @@ impl FormatSql for SelectExpr {
         }
 
         sql.push(')');
-
-        Cow::Owned(sql)
+        sql
     }
 }
 pub struct SelectExprBuilder<'args> {
-    table: &'static str,
+    table: Cow<'static, str>,
     select: Vec<DynExpr<'args>>,
     where_comparisons: Vec<FilterConditionBuilder<'args, DynExpr<'args>, DynExpr<'args>>>,
     limit: Option<i64>,
@@ -105,7 +114,7 @@ impl PaginationOwnedSupportingTool for SelectExprBuilder<'_> {
 impl<'args> SelectExprBuilder<'args> {
     pub fn new(table: &'static str) -> Self {
         Self {
-            table,
+            table: quote_table_reference(table),
             select: Vec::new(),
             where_comparisons: Vec::new(),
             limit: None,
@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+
+use crate::{
+    ColumnType,
+    table_layout::{DynColumn, PrefixedColumn, quote_ident, quote_table_reference},
+    traits::FormatSql,
+};
+
+use super::{
+    Aliasable, ArgumentHolder, DynExpr, Expr, ExprType, OtherSql, SelectExpr, SelectExprBuilder,
+    WrapInFunction,
+};
+
+/// An additional table or subquery. i.e. `DELETE ... USING {source}` or `UPDATE ... FROM {source}`
+///
+/// Use [SourceColumn] to reference the columns of an aliased source
+#[derive(Debug, PartialEq)]
+pub enum TableSource {
+    /// `{table} [AS {alias}]`
+    Table {
+        table: Cow<'static, str>,
+        alias: Option<Cow<'static, str>>,
+    },
+    /// `({query}) AS {alias}`
+    Subquery {
+        query: SelectExpr,
+        alias: Cow<'static, str>,
+    },
+    /// `(VALUES (..), (..)) AS {alias} ({columns})`
+    Values {
+        rows: Vec<Vec<Expr>>,
+        alias: Cow<'static, str>,
+        columns: Vec<Cow<'static, str>>,
+    },
+}
+impl TableSource {
+    pub fn table(table: &str) -> Self {
+        Self::Table {
+            table: Cow::Owned(quote_table_reference(table).into_owned()),
+            alias: None,
+        }
+    }
+    pub fn table_as(table: &str, alias: impl Into<Cow<'static, str>>) -> Self {
+        Self::Table {
+            table: Cow::Owned(quote_table_reference(table).into_owned()),
+            alias: Some(alias.into()),
+        }
+    }
+    /// Processes the subquery. Its arguments are added to the holder
+    pub fn subquery<'args>(
+        query: SelectExprBuilder<'args>,
+        alias: impl Into<Cow<'static, str>>,
+        args: &mut ArgumentHolder<'args>,
+    ) -> Self {
+        let Expr::Select(query) = query.process_unboxed(args) else {
+            unreachable!("SelectExprBuilder always processes into Expr::Select")
+        };
+        Self::Subquery {
+            query,
+            alias: alias.into(),
+        }
+    }
+    /// Processes the rows. Their arguments are added to the holder
+    pub fn values<'args>(values: ValuesList<'args>, args: &mut ArgumentHolder<'args>) -> Self {
+        let rows = values
+            .rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| value.process_unboxed(args))
+                    .collect()
+            })
+            .collect();
+        Self::Values {
+            rows,
+            alias: values.alias,
+            columns: values.columns,
+        }
+    }
+}
+impl FormatSql for TableSource {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            Self::Table { table, alias: None } => Cow::Borrowed(table),
+            Self::Table {
+                table,
+                alias: Some(alias),
+            } => Cow::Owned(format!("{table} AS {}", quote_ident(alias))),
+            Self::Subquery { query, alias } => Cow::Owned(format!(
+                "{} AS {}",
+                query.format_as_source(),
+                quote_ident(alias)
+            )),
+            Self::Values {
+                rows,
+                alias,
+                columns,
+            } => {
+                let rows: Vec<_> = rows
+                    .iter()
+                    .map(|row| {
+                        let values: Vec<_> = row.iter().map(|value| value.format_sql()).collect();
+                        format!("({})", values.join(", "))
+                    })
+                    .collect();
+                let columns: Vec<_> = columns.iter().map(|column| quote_ident(column)).collect();
+                Cow::Owned(format!(
+                    "(VALUES {}) AS {} ({})",
+                    rows.join(", "),
+                    quote_ident(alias),
+                    columns.join(", ")
+                ))
+            }
+        }
+    }
+}
+/// Rows for a `VALUES` [TableSource]
+pub struct ValuesList<'args> {
+    alias: Cow<'static, str>,
+    columns: Vec<Cow<'static, str>>,
+    rows: Vec<Vec<DynExpr<'args>>>,
+}
+impl<'args> ValuesList<'args> {
+    pub fn new<I, C>(alias: impl Into<Cow<'static, str>>, columns: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Cow<'static, str>>,
+    {
+        Self {
+            alias: alias.into(),
+            columns: columns.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+    /// Adds a row. Should have a value for every column
+    pub fn row<I, E>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: ExprType<'args> + 'args,
+    {
+        self.rows
+            .push(values.into_iter().map(DynExpr::new).collect());
+        self
+    }
+}
+/// A column of an aliased [TableSource]. `{source}.{column}`
+///
+/// The source can be an alias or a table name qualified with a schema
+#[derive(Debug, Clone)]
+pub struct SourceColumn {
+    source: Cow<'static, str>,
+    column: Cow<'static, str>,
+    /// The column passed to [of](Self::of). Values compared to a `#[column(sensitive)]` column are redacted
+    typed: Option<PrefixedColumn>,
+}
+impl SourceColumn {
+    pub fn new(source: impl Into<Cow<'static, str>>, column: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            source: source.into(),
+            column: column.into(),
+            typed: None,
+        }
+    }
+    /// The same column of a table that is used under an alias
+    pub fn of<C>(source: impl Into<Cow<'static, str>>, column: C) -> Self
+    where
+        C: ColumnType + 'static,
+    {
+        let source = source.into();
+        let prefix = quote_table_reference(&source).into_owned();
+        Self {
+            column: Cow::Borrowed(column.column_name()),
+            typed: Some(PrefixedColumn::new(prefix, column)),
+            source,
+        }
+    }
+}
+impl PartialEq for SourceColumn {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.column == other.column
+    }
+}
+impl Eq for SourceColumn {}
+impl FormatSql for SourceColumn {
+    fn format_sql(&self) -> Cow<'_, str> {
+        Cow::Owned(format!(
+            "{}.{}",
+            quote_table_reference(&self.source),
+            quote_ident(&self.column)
+        ))
+    }
+}
+impl<'args> ExprType<'args> for SourceColumn {
+    fn process(self: Box<Self>, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        self.process_unboxed(args)
+    }
+
+    fn process_unboxed(self, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        match self.typed {
+            Some(column) => Expr::Column(DynColumn::new(column)),
+            None => Expr::Other(OtherSql::new(self)),
+        }
+    }
+}
+impl<'args> Aliasable<'args> for SourceColumn {}
+impl<'args> WrapInFunction<'args> for SourceColumn {}
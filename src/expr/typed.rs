@@ -0,0 +1,388 @@
+//! An optional typed layer on top of [ExprType]
+//!
+//! [TypedExpr] carries the type of the expression so comparisons, math and casts
+//! are checked at compile time.
+//!
+//! ```rust,compile_fail
+//! use pg_extended_sqlx_queries::prelude::*;
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     pub id: i32,
+//!     pub name: String,
+//! }
+//! // Will not compile because `id` is an INT4 and "hello" is TEXT
+//! let _ = UserColumn::ID.equals("hello");
+//! ```
+//!
+//! The typed column constants also restrict the values passed to `insert` and `set`
+//!
+//! ```rust,compile_fail
+//! use pg_extended_sqlx_queries::prelude::*;
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     pub id: i32,
+//!     pub name: String,
+//! }
+//! let mut query = InsertQueryBuilder::new(User::table_name());
+//! // Will not compile because `name` is a String
+//! query.insert(UserColumn::NAME, 5);
+//! ```
+//!
+//! The untyped API is still available by calling [TypedExpr::untyped] or [TypedExpr::dyn_expr]
+use std::{borrow::Cow, marker::PhantomData};
+
+use sqlx::postgres::types::PgInterval;
+
+use super::{
+    Aliasable, ArgumentHolder, CastableExprType, DynExpr, Expr, ExprAsType, ExprType,
+    FilterConditionBuilder, FilterExpr, MathExpr, MathExprBuilder, SQLComparison, WrapInFunction,
+};
+use crate::expr::CastBuilder;
+pub mod column_value;
+pub mod sql_types;
+pub use column_value::*;
+use sql_types::*;
+
+/// A Rust type that has a known Postgres type.
+pub trait HasSqlType {
+    type SqlType: SqlType;
+}
+/// An [ExprType] with a known Postgres type.
+pub trait TypedExprType<'args>: ExprType<'args> {
+    type SqlType: SqlType;
+}
+impl<'args, T> TypedExprType<'args> for T
+where
+    T: HasSqlType + ExprType<'args>,
+{
+    type SqlType = T::SqlType;
+}
+macro_rules! has_sql_type {
+    (
+        $(
+            $ty:ty => $sql_type:ident
+        ),*
+    ) => {
+        $(
+            impl HasSqlType for $ty {
+                type SqlType = $sql_type;
+            }
+        )*
+    };
+}
+has_sql_type!(
+    bool => Bool,
+    i16 => Int2,
+    i32 => Int4,
+    i64 => Int8,
+    f32 => Float4,
+    f64 => Float8,
+    String => Text,
+    str => Text,
+    Vec<u8> => Bytea,
+    [u8] => Bytea,
+    PgInterval => Interval,
+    sqlx::postgres::types::PgCiText => Text
+);
+impl<T> HasSqlType for Option<T>
+where
+    T: HasSqlType,
+{
+    type SqlType = T::SqlType;
+}
+impl<T> HasSqlType for &T
+where
+    T: HasSqlType + ?Sized,
+{
+    type SqlType = T::SqlType;
+}
+#[cfg(feature = "chrono")]
+has_sql_type!(
+    chrono::NaiveDateTime => Timestamp,
+    chrono::NaiveDate => Date,
+    chrono::NaiveTime => Time,
+    chrono::Duration => Interval
+);
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> HasSqlType for chrono::DateTime<Tz> {
+    type SqlType = Timestamptz;
+}
+#[cfg(feature = "uuid")]
+has_sql_type!(uuid::Uuid => Uuid);
+#[cfg(feature = "json")]
+impl<T> HasSqlType for sqlx::types::Json<T> {
+    type SqlType = Jsonb;
+}
+
+/// An expression with a known type `T`.
+///
+/// `T` can either be a Rust type implementing [HasSqlType] or one of the [sql_types]
+///
+/// Comparison, math and cast methods on this type require the other side to be of the same SQL type.
+///
+/// The [TableType](crate::table_layout::TableType) derive generates a constant for every column. i.e. `UserColumn::ID`
+pub struct TypedExpr<'args, T, E> {
+    expr: E,
+    phantom: TypedPhantom<'args, T>,
+}
+type TypedPhantom<'args, T> = PhantomData<(fn() -> T, &'args ())>;
+impl<T, E: Clone> Clone for TypedExpr<'_, T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            expr: self.expr.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+impl<T, E: Copy> Copy for TypedExpr<'_, T, E> {}
+impl<T, E: std::fmt::Debug> std::fmt::Debug for TypedExpr<'_, T, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedExpr")
+            .field("expr", &self.expr)
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+macro_rules! typed_comparison {
+    (
+        $(
+            $name:ident: $value:ident
+        ),*
+    ) => {
+        $(
+            pub fn $name<R>(self, value: R) -> FilterConditionBuilder<'args, E, R>
+            where
+                T: HasSqlType,
+                R: TypedExprType<'args, SqlType = T::SqlType> + 'args,
+            {
+                self.expr.compare(SQLComparison::$value, value)
+            }
+        )*
+    };
+}
+macro_rules! typed_math {
+    (
+        $(
+            $name:ident
+        ),*
+    ) => {
+        $(
+            #[allow(clippy::should_implement_trait)]
+            pub fn $name<R>(
+                self,
+                value: R,
+            ) -> TypedExpr<'args, T::SqlType, MathExprBuilder<'args, E, R>>
+            where
+                T: HasSqlType,
+                T::SqlType: NumericSqlType,
+                R: TypedExprType<'args, SqlType = T::SqlType> + 'args,
+            {
+                TypedExpr::new(self.expr.$name(value))
+            }
+        )*
+    };
+}
+impl<T, E> TypedExpr<'_, T, E> {
+    /// Wraps the expression with the type `T`
+    ///
+    /// It is up to the caller to make sure the expression is actually of type `T`
+    pub const fn new(expr: E) -> Self {
+        Self {
+            expr,
+            phantom: PhantomData,
+        }
+    }
+    /// Returns the inner untyped expression
+    pub fn untyped(self) -> E {
+        self.expr
+    }
+}
+impl<'args, T, E> TypedExpr<'args, T, E>
+where
+    E: ExprType<'args> + 'args,
+{
+    /// Returns the inner expression as a [DynExpr]
+    pub fn dyn_expr(self) -> DynExpr<'args> {
+        DynExpr::new(self.expr)
+    }
+
+    typed_comparison! {
+        equals: Equals,
+        not_equals: NotEquals,
+        less_than: LessThan,
+        less_than_or_equals: LessThanOrEqualTo,
+        greater_than: GreaterThan,
+        greater_than_or_equals: GreaterThanOrEqualTo
+    }
+    typed_math!(add, subtract, multiply, divide, modulo);
+
+    pub fn like<R>(self, value: R) -> FilterConditionBuilder<'args, E, R>
+    where
+        T: HasSqlType<SqlType = Text>,
+        R: TypedExprType<'args, SqlType = Text> + 'args,
+    {
+        self.expr.like(value)
+    }
+    pub fn ilike<R>(self, value: R) -> FilterConditionBuilder<'args, E, R>
+    where
+        T: HasSqlType<SqlType = Text>,
+        R: TypedExprType<'args, SqlType = Text> + 'args,
+    {
+        self.expr.ilike(value)
+    }
+    pub fn between<R>(self, start: R, end: R) -> FilterConditionBuilder<'args, E, R>
+    where
+        T: HasSqlType,
+        R: TypedExprType<'args, SqlType = T::SqlType> + 'args,
+    {
+        self.expr.between(start, end)
+    }
+    pub fn is_null(self) -> FilterConditionBuilder<'args, E, ()> {
+        self.expr.is_null()
+    }
+    pub fn is_not_null(self) -> FilterConditionBuilder<'args, E, ()> {
+        self.expr.is_not_null()
+    }
+    /// `{self} + {interval}`
+    pub fn add_interval<R>(
+        self,
+        interval: R,
+    ) -> TypedExpr<'args, <T::SqlType as IntervalArithmetic>::Output, MathExprBuilder<'args, E, R>>
+    where
+        T: HasSqlType,
+        T::SqlType: IntervalArithmetic,
+        R: TypedExprType<'args, SqlType = Interval> + 'args,
+    {
+        TypedExpr::new(self.expr.add(interval))
+    }
+    /// `{self} - {interval}`
+    pub fn subtract_interval<R>(
+        self,
+        interval: R,
+    ) -> TypedExpr<'args, <T::SqlType as IntervalArithmetic>::Output, MathExprBuilder<'args, E, R>>
+    where
+        T: HasSqlType,
+        T::SqlType: IntervalArithmetic,
+        R: TypedExprType<'args, SqlType = Interval> + 'args,
+    {
+        TypedExpr::new(self.expr.subtract(interval))
+    }
+    /// Casts the expression to `To`. Only casts that Postgres supports are allowed.
+    pub fn cast<To>(self) -> TypedExpr<'args, To, CastBuilder<'args>>
+    where
+        T: HasSqlType,
+        T::SqlType: SqlCast<To>,
+        To: SqlType,
+    {
+        TypedExpr::new(CastBuilder {
+            expr: DynExpr::new(self.expr),
+            cast_type: Cow::Borrowed(To::NAME),
+        })
+    }
+    pub fn alias(self, alias: impl Into<Cow<'static, str>>) -> ExprAsType<'args, E> {
+        ExprAsType::new(self.expr, alias)
+    }
+}
+impl<'args, T, E> ExprType<'args> for TypedExpr<'args, T, E>
+where
+    E: ExprType<'args>,
+{
+    fn process(self: Box<Self>, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        self.expr.process_unboxed(args)
+    }
+
+    fn process_unboxed(self, args: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        self.expr.process_unboxed(args)
+    }
+}
+impl<'args, T, E> TypedExprType<'args> for TypedExpr<'args, T, E>
+where
+    T: HasSqlType,
+    E: ExprType<'args>,
+{
+    type SqlType = T::SqlType;
+}
+impl<'args, T: 'args, E> WrapInFunction<'args> for TypedExpr<'args, T, E> where
+    E: ExprType<'args> + 'args
+{
+}
+impl<'args, T: 'args, E> Aliasable<'args> for TypedExpr<'args, T, E> where E: ExprType<'args> + 'args
+{}
+impl<'args, T: 'args, E> CastableExprType<'args> for TypedExpr<'args, T, E> where
+    E: ExprType<'args> + 'args
+{
+}
+
+/// Converts any [ExprType] into a [TypedExpr]
+pub trait IntoTypedExpr<'args>: ExprType<'args> + Sized {
+    /// Marks the expression as type `T`.
+    ///
+    /// It is up to the caller to make sure the expression is actually of type `T`
+    fn typed<T>(self) -> TypedExpr<'args, T, Self>
+    where
+        Self: 'args,
+    {
+        TypedExpr::new(self)
+    }
+}
+impl<'args, E> IntoTypedExpr<'args> for E where E: ExprType<'args> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::fake::FakeQuery;
+    use crate::prelude::*;
+    use crate::testing::TestTableColumn;
+
+    use super::sql_types;
+
+    #[test]
+    pub fn typed_comparison() {
+        let mut parent = FakeQuery::default();
+        parent.filter(
+            TestTableColumn::AGE.equals(18).and(
+                TestTableColumn::FIRST_NAME
+                    .like("J%")
+                    .or(TestTableColumn::ANOTHER_TABLE_ID.equals(TestTableColumn::ID)),
+            ),
+        );
+
+        assert_eq!(
+            parent.format_where(),
+            "test_table.age = $1 AND test_table.first_name LIKE $2 OR test_table.another_table_id = test_table.id"
+        );
+    }
+    #[test]
+    pub fn typed_math_and_cast() {
+        let mut parent = FakeQuery::default();
+        let expr = TestTableColumn::AGE
+            .multiply(2)
+            .cast::<sql_types::Text>()
+            .process_unboxed(&mut parent.arguments);
+
+        assert_eq!(expr.format_sql(), "(test_table.age * $1)::TEXT");
+    }
+    #[test]
+    pub fn typed_function() {
+        let mut parent = FakeQuery::default();
+        parent.filter(
+            TestTableColumn::AGE.greater_than(
+                SqlFunctionBuilder::new("LENGTH")
+                    .add_param(TestTableColumn::FIRST_NAME)
+                    .typed::<i32>(),
+            ),
+        );
+
+        assert_eq!(
+            parent.format_where(),
+            "test_table.age > LENGTH(test_table.first_name)"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Restricts the values that can be inserted or set for a column
+use super::{HasSqlType, TypedExpr};
+use crate::{
+    expr::ExprType,
+    table_layout::{ColumnType, DynColumn},
+};
+
+/// A value that can be stored in a column with the Rust type `T`
+///
+/// - `T` itself
+/// - `T` for an `Option<T>` column
+/// - `&str` for a `String` column and `&[u8]` for a `Vec<u8>` column
+/// - A [TypedExpr] with the same SQL type
+pub trait ColumnValue<'args, T: ?Sized>: ExprType<'args> {}
+
+impl<'args, T> ColumnValue<'args, T> for T where T: ExprType<'args> {}
+impl<'args, T> ColumnValue<'args, Option<T>> for T where T: ExprType<'args> {}
+macro_rules! borrowed_column_value {
+    (
+        $(
+            $borrowed:ty => $owned:ty
+        ),*
+    ) => {
+        $(
+            impl<'args> ColumnValue<'args, $owned> for &'args $borrowed {}
+            impl<'args> ColumnValue<'args, Option<$owned>> for &'args $borrowed {}
+        )*
+    };
+}
+borrowed_column_value!(str => String, [u8] => Vec<u8>);
+
+impl<'args, T, U, E> ColumnValue<'args, T> for TypedExpr<'args, U, E>
+where
+    T: HasSqlType,
+    U: HasSqlType<SqlType = T::SqlType>,
+    E: ExprType<'args>,
+{
+}
+
+/// The column argument of [InsertQueryBuilder::insert](crate::insert::InsertQueryBuilder::insert)
+/// and [UpdateQueryBuilder::set](crate::update::UpdateQueryBuilder::set)
+///
+/// Any [ColumnType] will accept any value.
+/// The typed column constants generated by the [TableType](crate::table_layout::TableType) derive
+/// will only accept a [ColumnValue] for the field type.
+pub trait ColumnTarget<'args, V> {
+    fn target_column(self) -> DynColumn;
+}
+impl<'args, C, V> ColumnTarget<'args, V> for C
+where
+    C: ColumnType + 'static,
+    V: ExprType<'args>,
+{
+    fn target_column(self) -> DynColumn {
+        self.dyn_column()
+    }
+}
+impl<'args, T, C, V> ColumnTarget<'args, V> for TypedExpr<'_, T, C>
+where
+    C: ColumnType + 'static,
+    V: ColumnValue<'args, T>,
+{
+    fn target_column(self) -> DynColumn {
+        self.untyped().dyn_column()
+    }
+}
@@ -0,0 +1,147 @@
+//! Marker types representing Postgres types for [TypedExpr](super::TypedExpr)
+//!
+//! These are never constructed. They only exist at the type level.
+
+/// A Postgres type
+pub trait SqlType {
+    /// The name of the type used when casting. `{expr}::{NAME}`
+    const NAME: &'static str;
+}
+/// Types that support `+ - * / %` with another value of the same type
+pub trait NumericSqlType: SqlType {}
+/// Types that support adding or subtracting an interval.
+pub trait IntervalArithmetic: SqlType {
+    /// The resulting type of `{self} + interval`
+    type Output: SqlType;
+}
+/// Marks that Postgres can cast `Self` to `To`
+pub trait SqlCast<To: SqlType>: SqlType {}
+
+macro_rules! sql_types {
+    (
+        $(
+            $(#[$docs:meta])*
+            $name:ident => $sql_name:literal
+        ),*
+    ) => {
+        $(
+            $(#[$docs])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum $name {}
+            impl SqlType for $name {
+                const NAME: &'static str = $sql_name;
+            }
+            impl super::HasSqlType for $name {
+                type SqlType = $name;
+            }
+            impl SqlCast<$name> for $name {}
+        )*
+    };
+}
+/// Every type can be cast to text
+macro_rules! cast_to_text {
+    ($($name:ident),*) => {
+        $(impl SqlCast<Text> for $name {})*
+    };
+}
+sql_types! {
+    /// `BOOL`
+    Bool => "BOOL",
+    /// `INT2` / `SMALLINT`
+    Int2 => "INT2",
+    /// `INT4` / `INTEGER`
+    Int4 => "INT4",
+    /// `INT8` / `BIGINT`
+    Int8 => "INT8",
+    /// `FLOAT4` / `REAL`
+    Float4 => "FLOAT4",
+    /// `FLOAT8` / `DOUBLE PRECISION`
+    Float8 => "FLOAT8",
+    /// `NUMERIC`
+    Numeric => "NUMERIC",
+    /// `TEXT`, `VARCHAR`, `CITEXT`
+    Text => "TEXT",
+    /// `BYTEA`
+    Bytea => "BYTEA",
+    /// `TIMESTAMP` (without time zone)
+    Timestamp => "TIMESTAMP",
+    /// `TIMESTAMPTZ`
+    Timestamptz => "TIMESTAMPTZ",
+    /// `DATE`
+    Date => "DATE",
+    /// `TIME`
+    Time => "TIME",
+    /// `INTERVAL`
+    Interval => "INTERVAL",
+    /// `UUID`
+    Uuid => "UUID",
+    /// `JSONB`
+    Jsonb => "JSONB"
+}
+cast_to_text!(
+    Bool,
+    Int2,
+    Int4,
+    Int8,
+    Float4,
+    Float8,
+    Numeric,
+    Bytea,
+    Timestamp,
+    Timestamptz,
+    Date,
+    Time,
+    Interval,
+    Uuid,
+    Jsonb
+);
+
+macro_rules! numeric {
+    ($($name:ident),*) => {
+        $(impl NumericSqlType for $name {})*
+    };
+}
+numeric!(Int2, Int4, Int8, Float4, Float8, Numeric);
+
+macro_rules! interval_arithmetic {
+    ($($name:ident => $output:ident),*) => {
+        $(
+            impl IntervalArithmetic for $name {
+                type Output = $output;
+            }
+        )*
+    };
+}
+interval_arithmetic!(
+    Timestamp => Timestamp,
+    Timestamptz => Timestamptz,
+    Date => Timestamp,
+    Time => Time,
+    Interval => Interval
+);
+
+macro_rules! sql_cast {
+    ($($from:ident => [$($to:ident),*]),*) => {
+        $(
+            $(impl SqlCast<$to> for $from {})*
+        )*
+    };
+}
+sql_cast! {
+    Bool => [Int4],
+    Int2 => [Int4, Int8, Float4, Float8, Numeric],
+    Int4 => [Bool, Int2, Int8, Float4, Float8, Numeric],
+    Int8 => [Int2, Int4, Float4, Float8, Numeric],
+    Float4 => [Int2, Int4, Int8, Float8, Numeric],
+    Float8 => [Int2, Int4, Int8, Float4, Numeric],
+    Numeric => [Int2, Int4, Int8, Float4, Float8],
+    Text => [
+        Bool, Int2, Int4, Int8, Float4, Float8, Numeric, Bytea, Timestamp, Timestamptz, Date,
+        Time, Interval, Uuid, Jsonb
+    ],
+    Timestamp => [Timestamptz, Date, Time],
+    Timestamptz => [Timestamp, Date, Time],
+    Date => [Timestamp, Timestamptz],
+    Time => [Interval],
+    Interval => [Time]
+}
@@ -12,7 +12,9 @@ use sqlx::{
     },
 };
 pub mod arguments;
+pub mod debug;
 pub use arguments::*;
+pub use debug::*;
 pub trait DynEncodeType<'args> {
     fn value(self) -> DynEncode<'args>;
 }
@@ -153,7 +155,12 @@ value_expr_type!(
     PgMoney, PgPath, PgPoint, PgPolygon
 );
 #[cfg(feature = "chrono")]
-value_expr_type!(chrono::NaiveDateTime, chrono::NaiveTime, chrono::NaiveDate,);
+value_expr_type!(
+    chrono::NaiveDateTime,
+    chrono::NaiveTime,
+    chrono::NaiveDate,
+    chrono::Duration
+);
 #[cfg(feature = "chrono")]
 value_expr_type!(chrono::DateTime<Tz>: where Tz: chrono::TimeZone  + 'args);
 
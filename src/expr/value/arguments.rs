@@ -1,8 +1,13 @@
 use std::borrow::Cow;
 
-use sqlx::{Arguments, Database, Postgres, postgres::PgArguments};
+use sqlx::{Arguments, Database, Postgres, TypeInfo, postgres::PgArguments};
 
-use crate::traits::FormatSql;
+use crate::{
+    error::Error,
+    expr::{DebugSqlOptions, DebugValue, Expr, ExprType, WrapInFunction, render_debug_sql},
+    table_layout::ColumnType,
+    traits::FormatSql,
+};
 
 /// A sql tool that has [Arguments](sqlx::Arguments) that can be used to build a query.
 ///
@@ -12,38 +17,271 @@ use crate::traits::FormatSql;
 /// The tools are highly inspired by the QueryBuilder in sqlx.
 ///
 /// This means it stores arguments in an [Option] and can be removed from the type and may cause panics. if removed and then the item is used again
+///
+/// Use the `try_` variants to get an [Error] instead
 pub trait HasArguments<'args> {
     /// Returns a mutable reference to the argument holder.
     fn holder(&mut self) -> &mut ArgumentHolder<'args>;
+    /// Takes the arguments. Returns the first error that happened while binding values
+    fn try_take_arguments(&mut self) -> Result<PgArguments, Error> {
+        self.holder().try_take_arguments()
+    }
+    /// Binds a value. See [ArgumentHolder::try_push_argument]
+    fn try_push_argument<T>(&mut self, value: T) -> Result<ArgumentIndex, Error>
+    where
+        T: 'args + sqlx::Encode<'args, Postgres> + sqlx::Type<Postgres>,
+    {
+        self.holder().try_push_argument(value)
+    }
+    /// Binds a value once. The returned [ArgumentIndex] can be used as an expression any number of times.
+    ///
+    /// Only use the index in the query it was bound to
+    fn bind_once<T>(&mut self, value: T) -> ArgumentIndex
+    where
+        T: 'args + sqlx::Encode<'args, Postgres> + sqlx::Type<Postgres>,
+    {
+        self.holder().push_argument(value)
+    }
+    /// [HasArguments::bind_once] for a secret. The value is redacted by `QueryTool::debug_sql`
+    /// wherever the index is used
+    fn bind_once_sensitive<T>(&mut self, value: T) -> ArgumentIndex
+    where
+        T: 'args + sqlx::Encode<'args, Postgres> + sqlx::Type<Postgres>,
+    {
+        let holder = self.holder();
+        let index = holder.push_argument(value);
+        holder.mark_sensitive(&[&Expr::ArgumentIndex(index)], holder.debug_values.len());
+        index
+    }
 }
 
 pub struct ArgumentHolder<'args> {
     arguments: Option<<Postgres as Database>::Arguments<'args>>,
+    /// The first error from [ArgumentHolder::push_argument]. Returned when the arguments are taken
+    error: Option<Error>,
+    /// Names of the [Param](crate::expr::Param)s in placeholder order
+    named_params: Vec<Cow<'static, str>>,
+    /// A copy of every bound value. Used by `QueryTool::debug_sql`.
+    ///
+    /// Only recorded with the `debug-sql` feature or if [Self::inline_values] is set
+    debug_values: Vec<DebugValue>,
+    /// Values are only kept in [Self::debug_values] and are written into the SQL as literals.
+    ///
+    /// Used by DDL statements which can not have bound parameters
+    inline_values: bool,
 }
 impl Default for ArgumentHolder<'_> {
     fn default() -> Self {
         Self {
             arguments: Some(Default::default()),
+            error: None,
+            named_params: Vec::new(),
+            debug_values: Vec::new(),
+            inline_values: false,
         }
     }
 }
 impl<'args> ArgumentHolder<'args> {
+    /// A holder that does not bind values. See [Self::inline_literals]
+    pub(crate) fn inline_values() -> Self {
+        Self {
+            inline_values: true,
+            ..Default::default()
+        }
+    }
     /// Takes the arguments list or panics if it is not available.
+    ///
+    /// Also panics if a value failed to encode. See [Self::try_take_arguments]
     pub fn take_arguments_or_error(&mut self) -> PgArguments {
-        self.arguments.take().expect("Arguments already taken")
+        self.try_take_arguments()
+            .unwrap_or_else(|err| panic!("{err}"))
     }
     /// Borrows the arguments list or panics if it is not available.
     pub fn borrow_arguments_or_error(&mut self) -> &mut PgArguments {
-        self.arguments.as_mut().expect("Arguments already taken")
+        self.try_borrow_arguments()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// Takes the arguments list.
+    ///
+    /// Returns the first error that happened while pushing arguments.
+    /// The arguments are dropped with the error so the query can not be retried with them
+    pub fn try_take_arguments(&mut self) -> Result<PgArguments, Error> {
+        if let Some(err) = self.error.take() {
+            // The arguments do not match the placeholders. Later calls return ArgumentsTaken
+            self.arguments = None;
+            return Err(err);
+        }
+        if !self.named_params.is_empty() {
+            return Err(Error::NamedParameters);
+        }
+        self.arguments.take().ok_or(Error::ArgumentsTaken)
+    }
+    /// Takes the names of the [Param](crate::expr::Param)s in placeholder order.
+    ///
+    /// The arguments are taken as well so the query can not be executed without the parameters
+    pub fn try_take_named_params(&mut self) -> Result<Vec<Cow<'static, str>>, Error> {
+        if let Some(err) = self.error.take() {
+            self.arguments = None;
+            return Err(err);
+        }
+        if !self.is_empty() {
+            return Err(Error::MixedParameters);
+        }
+        self.arguments.take().ok_or(Error::ArgumentsTaken)?;
+        Ok(std::mem::take(&mut self.named_params))
+    }
+    /// Pushes a named parameter. Using the same name twice returns the same index
+    pub fn push_named_param(&mut self, name: Cow<'static, str>) -> ArgumentIndex {
+        if let Some(position) = self.named_params.iter().position(|param| *param == name) {
+            return ArgumentIndex(position + 1);
+        }
+        if !self.is_empty() {
+            self.error.get_or_insert(Error::MixedParameters);
+        }
+        self.named_params.push(name);
+        ArgumentIndex(self.named_params.len())
+    }
+    /// Keeps the error until the arguments are taken. Only the first error is kept
+    pub(crate) fn push_error(&mut self, err: Error) {
+        self.error.get_or_insert(err);
+    }
+    /// Explicit values can not be inserted into generated or read only columns. DEFAULT is allowed
+    pub(crate) fn check_insertable<C: ColumnType>(&mut self, column: &C) {
+        if column
+            .column_info()
+            .is_some_and(|info| !info.is_insertable())
+        {
+            self.push_error(Error::ColumnNotWritable {
+                column: column.column_name(),
+            });
+        }
+    }
+    /// Generated, read only and insert only columns can not be updated
+    pub(crate) fn check_updatable<C: ColumnType>(&mut self, column: &C) {
+        if column
+            .column_info()
+            .is_some_and(|info| !info.is_updatable())
+        {
+            self.push_error(Error::ColumnNotWritable {
+                column: column.column_name(),
+            });
+        }
+    }
+    pub fn has_named_params(&self) -> bool {
+        !self.named_params.is_empty()
+    }
+    pub fn try_borrow_arguments(&mut self) -> Result<&mut PgArguments, Error> {
+        self.arguments.as_mut().ok_or(Error::ArgumentsTaken)
+    }
+    /// The number of arguments that have been pushed
+    pub fn len(&self) -> usize {
+        if self.inline_values {
+            return self.debug_values.len();
+        }
+        self.arguments
+            .as_ref()
+            .map(|arguments| arguments.len())
+            .unwrap_or_default()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
     /// Pushes an argument to the arguments list.
+    ///
+    /// If the value fails to encode the error is kept and returned by [Self::try_take_arguments]
     pub fn push_argument<T>(&mut self, value: T) -> ArgumentIndex
     where
         T: 'args + sqlx::Encode<'args, Postgres> + sqlx::Type<Postgres>,
     {
-        let arguments = self.borrow_arguments_or_error();
-        arguments.add(value).expect("Failed to add argument");
-        ArgumentIndex(arguments.len())
+        if self.has_named_params() {
+            self.error.get_or_insert(Error::MixedParameters);
+            return ArgumentIndex(self.named_params.len() + self.len() + 1);
+        }
+        let index = ArgumentIndex(self.len() + 1);
+        match self.try_push_argument(value) {
+            Ok(index) => index,
+            Err(err) => {
+                self.error.get_or_insert(err);
+                index
+            }
+        }
+    }
+    /// Pushes an argument to the arguments list.
+    pub fn try_push_argument<T>(&mut self, value: T) -> Result<ArgumentIndex, Error>
+    where
+        T: 'args + sqlx::Encode<'args, Postgres> + sqlx::Type<Postgres>,
+    {
+        if self.inline_values {
+            self.try_borrow_arguments()?;
+            let value = DebugValue::new(&value);
+            if value.try_literal().is_none() {
+                return Err(Error::UnsupportedLiteral(
+                    value.type_info().name().to_owned(),
+                ));
+            }
+            self.debug_values.push(value);
+            return Ok(ArgumentIndex(self.debug_values.len()));
+        }
+        #[cfg(feature = "debug-sql")]
+        let debug_value = DebugValue::new(&value);
+        let arguments = self.try_borrow_arguments()?;
+        arguments.add(value).map_err(Error::Encode)?;
+        let index = ArgumentIndex(arguments.len());
+        #[cfg(feature = "debug-sql")]
+        self.debug_values.push(debug_value);
+        Ok(index)
+    }
+    /// Replaces the placeholders of the values kept by [Self::inline_values] with their literals.
+    ///
+    /// A value that can not be written as a literal is never kept. It is an [Error::UnsupportedLiteral] when it is pushed
+    pub(crate) fn inline_literals(&self, sql: &str) -> String {
+        let options = DebugSqlOptions {
+            redact_sensitive: false,
+            redact_all: false,
+        };
+        render_debug_sql(sql, &self.debug_values, &options)
+    }
+    /// The bound values in placeholder order. Empty without the `debug-sql` feature
+    pub fn debug_values(&self) -> &[DebugValue] {
+        &self.debug_values
+    }
+    /// Processes a value that is written to or compared with a column.
+    ///
+    /// Values bound to `#[column(sensitive)]` columns are redacted by `QueryTool::debug_sql`
+    pub fn process_for_column<C, E>(&mut self, column: &C, value: E) -> Expr
+    where
+        C: ColumnType + ?Sized,
+        E: ExprType<'args> + 'args,
+    {
+        let start = self.debug_values.len();
+        let expr = value.process_unboxed(self);
+        if column.column_info().is_some_and(|info| info.sensitive) {
+            self.mark_sensitive(&[&expr], start);
+        }
+        expr
+    }
+    /// Marks the values bound since `start` and the [ArgumentIndex]s used in the expressions as sensitive
+    pub(crate) fn mark_sensitive(&mut self, exprs: &[&Expr], start: usize) {
+        for value in self.debug_values.iter_mut().skip(start) {
+            value.mark_sensitive();
+        }
+        for expr in exprs {
+            expr.walk(&mut |expr| {
+                if let Expr::ArgumentIndex(ArgumentIndex(index)) = expr
+                    && let Some(value) = index
+                        .checked_sub(1)
+                        .and_then(|index| self.debug_values.get_mut(index))
+                {
+                    value.mark_sensitive();
+                }
+            });
+        }
+    }
+    /// Marks the values of a comparison as sensitive if either side uses a sensitive column
+    pub(crate) fn mark_sensitive_comparison(&mut self, exprs: &[&Expr], start: usize) {
+        if exprs.iter().any(|expr| expr.references_sensitive_column()) {
+            self.mark_sensitive(exprs, start);
+        }
     }
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -54,3 +292,111 @@ impl FormatSql for ArgumentIndex {
         Cow::Owned(format!("${}", self.0))
     }
 }
+/// Reuses an argument that was already bound. See [HasArguments::bind_once]
+impl<'args> ExprType<'args> for ArgumentIndex {
+    fn process(self: Box<Self>, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::ArgumentIndex(*self)
+    }
+
+    fn process_unboxed(self, _: &mut ArgumentHolder<'args>) -> Expr
+    where
+        Self: 'args,
+    {
+        Expr::ArgumentIndex(self)
+    }
+}
+impl WrapInFunction<'_> for ArgumentIndex {}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Encode, Postgres, Type, encode::IsNull, error::BoxDynError, postgres::PgTypeInfo};
+
+    use crate::{
+        Error,
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    #[derive(Debug)]
+    struct FailingEncode;
+    impl Type<Postgres> for FailingEncode {
+        fn type_info() -> PgTypeInfo {
+            <i32 as Type<Postgres>>::type_info()
+        }
+    }
+    impl Encode<'_, Postgres> for FailingEncode {
+        fn encode_by_ref(
+            &self,
+            _: &mut sqlx::postgres::PgArgumentBuffer,
+        ) -> Result<IsNull, BoxDynError> {
+            Err("Unable to encode".into())
+        }
+    }
+    #[test]
+    fn arguments_taken_twice() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query.filter(TestTableColumn::Age.equals(50.value()));
+        assert!(query.try_query().is_ok());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::ArgumentsTaken));
+        assert_eq!(err.kind(), "arguments_taken");
+        assert!(matches!(
+            sqlx::Error::from(err),
+            sqlx::Error::InvalidArgument(_)
+        ));
+    }
+    #[test]
+    fn encode_error_is_returned() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query
+            .filter(TestTableColumn::Age.equals(FailingEncode.value()))
+            .filter(TestTableColumn::Id.equals(1.value()));
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert_eq!(err.to_string(), "Failed to add argument: Unable to encode");
+        assert!(matches!(sqlx::Error::from(err), sqlx::Error::Encode(_)));
+        assert!(matches!(query.try_query(), Err(Error::ArgumentsTaken)));
+
+        let mut holder = ArgumentHolder::default();
+        assert!(matches!(
+            holder.try_push_argument(FailingEncode),
+            Err(Error::Encode(_))
+        ));
+        assert_eq!(
+            holder.try_push_argument(1).unwrap(),
+            super::ArgumentIndex(1)
+        );
+
+        let mut holder = ArgumentHolder::default();
+        holder.push_named_param("id".into());
+        holder.push_argument(1);
+        assert!(matches!(
+            holder.try_take_named_params(),
+            Err(Error::MixedParameters)
+        ));
+        assert!(matches!(
+            holder.try_take_named_params(),
+            Err(Error::ArgumentsTaken)
+        ));
+    }
+    #[test]
+    fn bind_once_reuses_placeholder() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        let age = query.bind_once(50);
+        query
+            .filter(TestTableColumn::Age.equals(age))
+            .filter(TestTableColumn::Id.not_equals(age))
+            .filter(TestTableColumn::FirstName.equals("Wyatt".value()));
+        let sql = query.format_sql_query().to_owned();
+        assert!(sql.contains("test_table.age = $1"));
+        assert!(sql.contains("test_table.id != $1"));
+        assert!(sql.contains("test_table.first_name = $2"));
+        assert_eq!(query.holder().len(), 2);
+    }
+}
@@ -0,0 +1,599 @@
+//! Rendering of queries with the bound values inlined. See `QueryTool::debug_sql`
+//!
+//! The output is for logging and pasting into psql. Never execute it
+use std::fmt::Write;
+
+use sqlx::{
+    Encode, Postgres, Type, TypeInfo,
+    encode::IsNull,
+    postgres::{PgArgumentBuffer, PgTypeInfo},
+};
+
+/// Shown in place of redacted values
+pub const REDACTED: &str = "'<redacted>'";
+
+/// A copy of a bound value kept for [render_debug_sql]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugValue {
+    type_info: PgTypeInfo,
+    /// The binary encoding. None if NULL
+    bytes: Option<Vec<u8>>,
+    sensitive: bool,
+}
+impl DebugValue {
+    pub fn new<'args, T>(value: &T) -> Self
+    where
+        T: Encode<'args, Postgres> + Type<Postgres>,
+    {
+        let type_info = value.produces().unwrap_or_else(T::type_info);
+        let mut buffer = PgArgumentBuffer::default();
+        let bytes = match value.encode_by_ref(&mut buffer) {
+            Ok(IsNull::No) => Some(buffer.to_vec()),
+            _ => None,
+        };
+        Self {
+            type_info,
+            bytes,
+            sensitive: false,
+        }
+    }
+    pub fn type_info(&self) -> &PgTypeInfo {
+        &self.type_info
+    }
+    pub fn is_null(&self) -> bool {
+        self.bytes.is_none()
+    }
+    /// If the value was bound to a `#[column(sensitive)]` column
+    pub fn is_sensitive(&self) -> bool {
+        self.sensitive
+    }
+    pub(crate) fn mark_sensitive(&mut self) {
+        self.sensitive = true;
+    }
+    /// The value as an escaped SQL literal.
+    ///
+    /// Types that can not be rendered are written as a comment with the type name
+    pub fn literal(&self) -> String {
+        self.try_literal()
+            .unwrap_or_else(|| format!("NULL /* {} */", self.type_info.name()))
+    }
+    /// The value as an escaped SQL literal. None if the type can not be rendered
+    pub fn try_literal(&self) -> Option<String> {
+        let Some(bytes) = &self.bytes else {
+            return Some("NULL".to_owned());
+        };
+        let type_name = self.type_info.name();
+        if let Some(element_type) = type_name.strip_suffix("[]") {
+            array_literal(bytes, element_type)
+        } else if self.type_info.oid().is_some() {
+            literal(self.type_info.oid().map(|oid| oid.0), bytes)
+        } else {
+            // Enums and other types declared by name are encoded as text
+            std::str::from_utf8(bytes)
+                .ok()
+                .filter(|text| !text.chars().any(char::is_control))
+                .map(|text| format!("{}::{type_name}", quote(text)))
+        }
+    }
+}
+/// Options for [render_debug_sql]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugSqlOptions {
+    /// Replace values bound to `#[column(sensitive)]` columns. Default true
+    pub redact_sensitive: bool,
+    /// Replace every value
+    pub redact_all: bool,
+}
+impl Default for DebugSqlOptions {
+    fn default() -> Self {
+        Self {
+            redact_sensitive: true,
+            redact_all: false,
+        }
+    }
+}
+impl DebugSqlOptions {
+    pub fn redact_sensitive(&mut self, redact: bool) -> &mut Self {
+        self.redact_sensitive = redact;
+        self
+    }
+    pub fn redact_all(&mut self, redact: bool) -> &mut Self {
+        self.redact_all = redact;
+        self
+    }
+    fn is_redacted(&self, value: &DebugValue) -> bool {
+        self.redact_all || (self.redact_sensitive && value.sensitive)
+    }
+}
+/// Replaces the `$n` placeholders of the query with the literals of the values.
+///
+/// Placeholders inside of quoted strings and identifiers are left alone
+pub fn render_debug_sql(sql: &str, values: &[DebugValue], options: &DebugSqlOptions) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    let mut chars = sql.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        match (quote, char) {
+            (Some(open), char) => {
+                if open == char {
+                    quote = None;
+                }
+                result.push(char);
+            }
+            (None, '\'' | '"') => {
+                quote = Some(char);
+                result.push(char);
+            }
+            (None, '$') => {
+                let mut end = start + 1;
+                while let Some((index, digit)) = chars.peek() {
+                    if !digit.is_ascii_digit() {
+                        break;
+                    }
+                    end = index + 1;
+                    chars.next();
+                }
+                let value = sql[start + 1..end]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| values.get(index.checked_sub(1)?));
+                match value {
+                    Some(value) if options.is_redacted(value) => result.push_str(REDACTED),
+                    Some(value) => result.push_str(&value.literal()),
+                    None => result.push_str(&sql[start..end]),
+                }
+            }
+            (None, char) => result.push(char),
+        }
+    }
+    result
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+fn cast(text: &str, type_name: &str) -> String {
+    format!("{}::{type_name}", quote(text))
+}
+fn be_bytes<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    bytes.try_into().ok()
+}
+fn float_text<F>(value: F) -> String
+where
+    F: std::fmt::Display + Into<f64> + Copy,
+{
+    let float: f64 = value.into();
+    if float.is_finite() {
+        value.to_string()
+    } else if float.is_nan() {
+        "NaN".to_owned()
+    } else if float.is_sign_positive() {
+        "Infinity".to_owned()
+    } else {
+        "-Infinity".to_owned()
+    }
+}
+/// The name used to cast the text of a builtin type
+fn cast_type_name(oid: u32) -> Option<&'static str> {
+    let name = match oid {
+        17 => "BYTEA",
+        18 => "\"char\"",
+        114 => "JSON",
+        3802 => "JSONB",
+        700 => "FLOAT4",
+        701 => "FLOAT8",
+        1082 => "DATE",
+        1083 => "TIME",
+        1114 => "TIMESTAMP",
+        1184 => "TIMESTAMPTZ",
+        1186 => "INTERVAL",
+        1700 => "NUMERIC",
+        2950 => "UUID",
+        _ => return None,
+    };
+    Some(name)
+}
+/// Literal for the binary encoding of a builtin type
+fn literal(oid: Option<u32>, bytes: &[u8]) -> Option<String> {
+    let oid = oid?;
+    let text = text_output(oid, bytes)?;
+    let literal = match oid {
+        16 if text == "t" => "TRUE".to_owned(),
+        16 => "FALSE".to_owned(),
+        19 | 25 | 1042 | 1043 => quote(&text),
+        20 | 21 | 23 | 26 => text,
+        700 | 701 | 1700 if !matches!(text.as_str(), "NaN" | "Infinity" | "-Infinity") => text,
+        oid => cast(&text, cast_type_name(oid)?),
+    };
+    Some(literal)
+}
+/// The Postgres text output of a value. The same as `{value}::TEXT`
+///
+/// Types declared by name, such as enums, are encoded as text. One dimensional arrays are supported
+pub(crate) fn text_value(type_info: &PgTypeInfo, bytes: &[u8]) -> Option<String> {
+    if type_info.name().ends_with("[]") {
+        return array_text(bytes);
+    }
+    match type_info.oid() {
+        Some(oid) => text_output(oid.0, bytes),
+        None => std::str::from_utf8(bytes).ok().map(str::to_owned),
+    }
+}
+/// The text output for the binary encoding of a builtin type
+fn text_output(oid: u32, bytes: &[u8]) -> Option<String> {
+    let text = match oid {
+        // BOOL
+        16 => match bytes {
+            [0] => "f".to_owned(),
+            _ => "t".to_owned(),
+        },
+        // BYTEA
+        17 => {
+            let mut hex = String::with_capacity(bytes.len() * 2 + 2);
+            hex.push_str("\\x");
+            for byte in bytes {
+                write!(hex, "{byte:02x}").ok()?;
+            }
+            hex
+        }
+        // CHAR
+        18 => char::from(*bytes.first()?).to_string(),
+        // TEXT, NAME, BPCHAR, VARCHAR, JSON
+        19 | 25 | 1042 | 1043 | 114 => std::str::from_utf8(bytes).ok()?.to_owned(),
+        // INT8
+        20 => i64::from_be_bytes(be_bytes(bytes)?).to_string(),
+        // INT2
+        21 => i16::from_be_bytes(be_bytes(bytes)?).to_string(),
+        // INT4
+        23 => i32::from_be_bytes(be_bytes(bytes)?).to_string(),
+        // OID
+        26 => u32::from_be_bytes(be_bytes(bytes)?).to_string(),
+        // JSONB. Prefixed with the format version
+        3802 => std::str::from_utf8(bytes.get(1..)?).ok()?.to_owned(),
+        // FLOAT4
+        700 => float_text(f32::from_be_bytes(be_bytes(bytes)?)),
+        // FLOAT8
+        701 => float_text(f64::from_be_bytes(be_bytes(bytes)?)),
+        // DATE. Days since 2000-01-01
+        1082 => format_date(i32::from_be_bytes(be_bytes(bytes)?) as i64),
+        // TIME. Microseconds since midnight
+        1083 => format_time(i64::from_be_bytes(be_bytes(bytes)?)),
+        // TIMESTAMP and TIMESTAMPTZ. Microseconds since 2000-01-01
+        1114 | 1184 => match i64::from_be_bytes(be_bytes(bytes)?) {
+            i64::MAX => "infinity".to_owned(),
+            i64::MIN => "-infinity".to_owned(),
+            micros => {
+                let days = micros.div_euclid(86_400_000_000);
+                let time = micros.rem_euclid(86_400_000_000);
+                let mut text = format!("{} {}", format_date(days), format_time(time));
+                if oid == 1184 {
+                    text.push_str("+00");
+                }
+                text
+            }
+        },
+        // INTERVAL
+        1186 => {
+            let micros = i64::from_be_bytes(be_bytes(bytes.get(0..8)?)?);
+            let days = i32::from_be_bytes(be_bytes(bytes.get(8..12)?)?);
+            let months = i32::from_be_bytes(be_bytes(bytes.get(12..16)?)?);
+            format!("{months} mons {days} days {micros} microseconds")
+        }
+        // NUMERIC
+        1700 => numeric_text(bytes)?,
+        // UUID
+        2950 => {
+            let bytes: [u8; 16] = be_bytes(bytes)?;
+            let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!(
+                "{}-{}-{}-{}-{}",
+                &hex[0..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..32]
+            )
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+/// The elements of a one dimensional array. `(element_oid, elements)`. NULL elements are None
+fn array_elements(bytes: &[u8]) -> Option<(u32, Vec<Option<&[u8]>>)> {
+    let read_i32 = |offset: usize| -> Option<i32> {
+        Some(i32::from_be_bytes(be_bytes(
+            bytes.get(offset..offset + 4)?,
+        )?))
+    };
+    let dimensions = read_i32(0)?;
+    let element_oid = read_i32(8)? as u32;
+    let length = match dimensions {
+        0 => 0,
+        1 => read_i32(12)?,
+        _ => return None,
+    };
+    let mut offset = 20;
+    let mut elements = Vec::with_capacity(length.max(0) as usize);
+    for _ in 0..length {
+        let element_length = read_i32(offset)?;
+        offset += 4;
+        if element_length < 0 {
+            elements.push(None);
+            continue;
+        }
+        let element = bytes.get(offset..offset + element_length as usize)?;
+        offset += element_length as usize;
+        elements.push(Some(element));
+    }
+    Some((element_oid, elements))
+}
+/// `{a,"b c",NULL}`
+fn array_text(bytes: &[u8]) -> Option<String> {
+    let (element_oid, elements) = array_elements(bytes)?;
+    let elements = elements
+        .into_iter()
+        .map(|element| {
+            let Some(element) = element else {
+                return Some("NULL".to_owned());
+            };
+            let text = text_output(element_oid, element)?;
+            let needs_quotes = text.is_empty()
+                || text.eq_ignore_ascii_case("NULL")
+                || text.chars().any(|char| {
+                    matches!(char, '{' | '}' | ',' | '"' | '\\') || char.is_whitespace()
+                });
+            if needs_quotes {
+                Some(format!(
+                    "\"{}\"",
+                    text.replace('\\', "\\\\").replace('"', "\\\"")
+                ))
+            } else {
+                Some(text)
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("{{{}}}", elements.join(",")))
+}
+/// One dimensional arrays are written as `ARRAY[..]`
+fn array_literal(bytes: &[u8], element_type: &str) -> Option<String> {
+    let (element_oid, elements) = array_elements(bytes)?;
+    if elements.is_empty() {
+        return Some(format!("'{{}}'::{element_type}[]"));
+    }
+    let elements = elements
+        .into_iter()
+        .map(|element| match element {
+            Some(element) => literal(Some(element_oid), element),
+            None => Some("NULL".to_owned()),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("ARRAY[{}]::{element_type}[]", elements.join(", ")))
+}
+fn numeric_text(bytes: &[u8]) -> Option<String> {
+    let read_u16 = |offset: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(be_bytes(
+            bytes.get(offset..offset + 2)?,
+        )?))
+    };
+    let digit_count = read_u16(0)? as usize;
+    let weight = read_u16(2)? as i16 as isize;
+    let sign = read_u16(4)?;
+    let scale = read_u16(6)? as usize;
+    let digits = (0..digit_count)
+        .map(|index| read_u16(8 + index * 2))
+        .collect::<Option<Vec<_>>>()?;
+    if sign == 0xC000 {
+        return Some("NaN".to_owned());
+    }
+    // Digits are base 10000. `weight` is the power of the first digit
+    let digit = |index: isize| -> u16 {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| digits.get(index).copied())
+            .unwrap_or_default()
+    };
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        write!(text, "{}", digit(0)).ok()?;
+        for index in 1..=weight {
+            write!(text, "{:04}", digit(index)).ok()?;
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut index = weight + 1;
+        while fraction.len() < scale {
+            write!(fraction, "{:04}", digit(index)).ok()?;
+            index += 1;
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Some(text)
+}
+/// `YYYY-MM-DD` for the days since 2000-01-01
+fn format_date(days_since_2000: i64) -> String {
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days_since_2000 + 10_957 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+/// `HH:MM:SS.ffffff` for the microseconds since midnight
+fn format_time(micros: i64) -> String {
+    let seconds = micros / 1_000_000;
+    format!(
+        "{:02}:{:02}:{:02}.{:06}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        micros % 1_000_000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    fn render(sql: &str, values: &[DebugValue]) -> String {
+        render_debug_sql(sql, values, &DebugSqlOptions::default())
+    }
+    #[test]
+    fn literals() {
+        assert_eq!(DebugValue::new(&5i32).literal(), "5");
+        assert_eq!(DebugValue::new(&-5i64).literal(), "-5");
+        assert_eq!(DebugValue::new(&true).literal(), "TRUE");
+        assert_eq!(DebugValue::new(&1.5f64).literal(), "1.5");
+        assert_eq!(DebugValue::new(&f32::NAN).literal(), "'NaN'::FLOAT4");
+        assert_eq!(DebugValue::new(&"it's").literal(), "'it''s'");
+        assert_eq!(DebugValue::new(&None::<i32>).literal(), "NULL");
+        assert_eq!(
+            DebugValue::new(&vec![1i32, 2]).literal(),
+            "ARRAY[1, 2]::INT4[]"
+        );
+        assert_eq!(
+            DebugValue::new(&Vec::<String>::new()).literal(),
+            "'{}'::TEXT[]"
+        );
+        assert_eq!(
+            DebugValue::new(&vec![0xDEu8, 0xAD]).literal(),
+            "'\\xdead'::BYTEA"
+        );
+    }
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "2000-01-01");
+        assert_eq!(format_date(-1), "1999-12-31");
+        assert_eq!(format_date(8_825), "2024-02-29");
+        assert_eq!(format_time(3_723_000_004), "01:02:03.000004");
+    }
+    #[test]
+    fn numeric() {
+        // 12345.678
+        let mut bytes = Vec::new();
+        for part in [3u16, 1, 0, 3, 1, 2345, 6780] {
+            bytes.extend_from_slice(&part.to_be_bytes());
+        }
+        assert_eq!(numeric_text(&bytes).unwrap(), "12345.678");
+        // -0.05
+        let mut bytes = Vec::new();
+        for part in [1u16, (-1i16) as u16, 0x4000, 2, 500] {
+            bytes.extend_from_slice(&part.to_be_bytes());
+        }
+        assert_eq!(numeric_text(&bytes).unwrap(), "-0.05");
+    }
+    #[test]
+    fn placeholders() {
+        let values = [DebugValue::new(&1i32), DebugValue::new(&"a")];
+        assert_eq!(
+            render(
+                "SELECT * FROM t WHERE a = $1 AND b = $2 AND c = '$1'",
+                &values
+            ),
+            "SELECT * FROM t WHERE a = 1 AND b = 'a' AND c = '$1'"
+        );
+        assert_eq!(render("SELECT $3", &values), "SELECT $3");
+
+        let mut sensitive = DebugValue::new(&"hunter2");
+        sensitive.mark_sensitive();
+        assert_eq!(
+            render("SELECT $1", &[sensitive.clone()]),
+            "SELECT '<redacted>'"
+        );
+        assert_eq!(
+            render_debug_sql(
+                "SELECT $1",
+                &[sensitive],
+                DebugSqlOptions::default().redact_sensitive(false)
+            ),
+            "SELECT 'hunter2'"
+        );
+    }
+    #[cfg(feature = "debug-sql")]
+    #[test]
+    fn sensitive_columns() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .set(TestTableColumn::Phone, "555-0100".value())
+            .set(TestTableColumn::FirstName, "Wyatt".value())
+            .filter(TestTableColumn::Phone.equals("555-0199".value()));
+        assert_eq!(
+            query.debug_sql(),
+            "UPDATE test_table SET phone = '<redacted>', first_name = 'Wyatt' WHERE test_table.phone = '<redacted>';"
+        );
+        assert!(
+            query
+                .debug_sql_with(DebugSqlOptions::default().redact_sensitive(false))
+                .contains("phone = '555-0100'")
+        );
+    }
+    #[cfg(feature = "debug-sql")]
+    #[test]
+    fn sensitive_comparisons() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        let secret = query.bind_once_sensitive("555-0100");
+        let reused = query.bind_once("555-0101");
+        query
+            .filter("555-0102".value().equals(TestTableColumn::Phone))
+            .filter(
+                SqlFunctionBuilder::lower()
+                    .add_param(TestTableColumn::Phone)
+                    .equals("555-0103".value()),
+            )
+            .filter(TestTableColumn::FirstName.equals(secret))
+            .filter(TestTableColumn::Phone.equals(reused))
+            .filter(TestTableColumn::Age.equals(50.value()));
+        let sql = query.debug_sql();
+        assert!(!sql.contains("555-"), "{sql}");
+        assert!(sql.ends_with("test_table.age = 50"), "{sql}");
+    }
+    #[cfg(feature = "debug-sql")]
+    #[test]
+    fn wrapped_sensitive_columns() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query.filter(SourceColumn::of("t", TestTableColumn::Phone).equals("555-0100".value()));
+        assert!(query.debug_sql().ends_with("WHERE t.phone = '<redacted>'"));
+
+        let mut query = InsertQueryBuilder::new(TestTable::table_name());
+        query
+            .insert(TestTableColumn::Phone, "555-0101".value())
+            .on_conflict(
+                ConflictTarget::columns(vec![TestTableColumn::Id]),
+                ConflictActionBuilder::do_update()
+                    .set(TestTableColumn::Phone, Excluded(TestTableColumn::Phone))
+                    .filter(Excluded(TestTableColumn::Phone).not_equals("555-0102".value())),
+            );
+        let sql = query.debug_sql();
+        assert!(!sql.contains("555-"), "{sql}");
+        assert!(sql.contains("EXCLUDED.phone"), "{sql}");
+    }
+    #[cfg(not(feature = "debug-sql"))]
+    #[test]
+    fn values_are_not_copied() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query.filter(TestTableColumn::Phone.equals("555-0100".value()));
+        assert_eq!(query.holder().len(), 1);
+        assert!(query.holder().debug_values().is_empty());
+    }
+}
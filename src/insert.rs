@@ -1,22 +1,23 @@
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
 use crate::{
     expr::{
-        ArgumentHolder, ConflictQuery, Expr, ExprType, HasArguments, OnConflict, Returning,
-        SupportsReturning,
+        ArgumentHolder, ColumnTarget, ConflictQuery, Expr, ExprType, HasArguments, OnConflict,
+        Returning, SupportsReturning,
     },
-    prelude::{ColumnType, DynColumn},
-    table_layout::concat_columns_no_table_name,
+    prelude::DynColumn,
+    table_layout::{concat_columns_no_table_name, quote_table_reference},
     traits::{FormatSql, FormatSqlQuery, QueryTool, SpaceBefore},
 };
 use tracing::{debug, instrument};
 pub mod many;
+pub mod select;
 pub struct InsertQueryBuilder<'args> {
     columns: Vec<DynColumn>,
     insert: Vec<Expr>,
     sql: Option<String>,
     returning: Option<Returning>,
-    table: &'static str,
+    table: Cow<'static, str>,
     on_conflict: Option<OnConflict>,
     arguments: ArgumentHolder<'args>,
 }
@@ -40,7 +41,7 @@ impl<'args> ConflictQuery<'args> for InsertQueryBuilder<'args> {
 impl<'args> InsertQueryBuilder<'args> {
     pub fn new(table: &'static str) -> Self {
         Self {
-            table,
+            table: quote_table_reference(table),
             arguments: Default::default(),
             columns: Vec::new(),
             insert: Vec::new(),
@@ -51,14 +52,20 @@ impl<'args> InsertQueryBuilder<'args> {
     }
 
     /// Insert a value into the query
+    ///
+    /// If the column is a typed column constant. i.e. `UserColumn::NAME` the value must match the type of the field
+    ///
+    /// The query returns [Error::ColumnNotWritable](crate::Error::ColumnNotWritable) if the column is generated or read only
     pub fn insert<C, E>(&mut self, column: C, value: E) -> &mut Self
     where
-        C: ColumnType + 'static,
+        C: ColumnTarget<'args, E>,
         E: ExprType<'args> + 'args,
     {
         self.sql = None;
-        self.columns.push(column.dyn_column());
-        let expr = value.process_unboxed(&mut self.arguments);
+        let column = column.target_column();
+        self.arguments.check_insertable(&column);
+        let expr = self.arguments.process_for_column(&column, value);
+        self.columns.push(column);
         self.insert.push(expr);
         self
     }
@@ -69,7 +76,7 @@ impl<'args> InsertQueryBuilder<'args> {
     /// If you want to insert a NULL value use `insert` with `None`
     pub fn insert_option<C, E>(&mut self, column: C, value: Option<E>) -> &mut Self
     where
-        C: ColumnType + 'static,
+        C: ColumnTarget<'args, E>,
         E: ExprType<'args> + 'args,
     {
         if let Some(value) = value {
@@ -84,7 +91,14 @@ impl<'args> HasArguments<'args> for InsertQueryBuilder<'args> {
         &mut self.arguments
     }
 }
-impl<'args> QueryTool<'args> for InsertQueryBuilder<'args> {}
+impl<'args> QueryTool<'args> for InsertQueryBuilder<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("INSERT")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
 impl SupportsReturning for InsertQueryBuilder<'_> {
     fn returning(&mut self, returning: Returning) -> &mut Self {
         self.returning = Some(returning);
@@ -94,15 +108,21 @@ impl SupportsReturning for InsertQueryBuilder<'_> {
 impl FormatSqlQuery for InsertQueryBuilder<'_> {
     #[instrument(skip(self), fields(table = %self.table, statement.type = "INSERT"))]
     fn format_sql_query(&mut self) -> &str {
-        let columns = concat_columns_no_table_name(&self.columns);
-        let values = self
-            .insert
-            .iter()
-            .map(|expr| expr.format_sql())
-            .collect::<Vec<_>>()
-            .join(", ");
+        // A row where every column uses its default
+        let values = if self.columns.is_empty() {
+            "DEFAULT VALUES".to_owned()
+        } else {
+            let columns = concat_columns_no_table_name(&self.columns);
+            let values = self
+                .insert
+                .iter()
+                .map(|expr| expr.format_sql())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({columns}) VALUES ({values})")
+        };
         let sql = format!(
-            "INSERT INTO {table} ({columns}) VALUES ({values}){on_conflict}{returning};",
+            "INSERT INTO {table} {values}{on_conflict}{returning};",
             table = self.table,
             on_conflict = self.on_conflict.format_sql(),
             returning = SpaceBefore::from(self.returning.as_ref()),
@@ -124,10 +144,24 @@ mod tests {
     use sqlformat::QueryParams;
 
     use crate::{
+        Error,
         prelude::*,
-        testing::{AnotherTable, AnotherTableColumn, TestTable, TestTableColumn},
+        testing::{
+            AnotherTable, AnotherTableColumn, AuthUser, AuthUserColumn, TestTable, TestTableColumn,
+        },
     };
 
+    #[test]
+    pub fn test_default_values() {
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder
+            .insert_option(TestTableColumn::Email, Option::<DynEncode>::None)
+            .return_all();
+        assert_eq!(
+            builder.format_sql_query(),
+            "INSERT INTO test_table DEFAULT VALUES RETURNING *;"
+        );
+    }
     #[test]
     pub fn test_no_return() {
         let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
@@ -218,4 +252,126 @@ mod tests {
         );
         println!("{sql}");
     }
+    #[test]
+    pub fn insert_typed() {
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder
+            .insert(TestTableColumn::FIRST_NAME, "John")
+            .insert(TestTableColumn::AGE, 18)
+            .insert(TestTableColumn::ANOTHER_TABLE_ID, 1)
+            .insert(TestTableColumn::PHONE, TestTableColumn::EMAIL);
+
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "INSERT INTO test_table (first_name, age, another_table_id, phone) VALUES ($1, $2, $3, test_table.email);"
+        );
+    }
+    #[test]
+    pub fn on_conflict_with_schema() {
+        let mut builder = super::InsertQueryBuilder::new(AuthUser::qualified_table_name());
+        builder
+            .insert(AuthUserColumn::Id, 1)
+            .insert(AuthUserColumn::Username, "john")
+            .on_conflict_set_excluded(
+                ConflictTarget::columns(vec![AuthUserColumn::Id]),
+                vec![AuthUserColumn::Username],
+            );
+
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "INSERT INTO auth.users (id, username) VALUES ($1, $2) ON CONFLICT (id) DO UPDATE SET username = EXCLUDED.username;"
+        );
+    }
+    #[test]
+    pub fn on_conflict_partial_index_update_where() {
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder
+            .insert(TestTableColumn::Email, "john@example.com")
+            .insert(TestTableColumn::Age, 18)
+            .on_conflict(
+                ConflictTargetBuilder::columns(vec![TestTableColumn::Email])
+                    .filter(TestTableColumn::AnotherTableId.is_null()),
+                ConflictActionBuilder::do_update()
+                    .set(
+                        TestTableColumn::Age,
+                        TestTableColumn::Age.add(Excluded(TestTableColumn::Age)),
+                    )
+                    .set_column_to_excluded(TestTableColumn::UpdatedAt)
+                    .filter(
+                        TestTableColumn::UpdatedAt.less_than(Excluded(TestTableColumn::UpdatedAt)),
+                    ),
+            );
+
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "INSERT INTO test_table (email, age) VALUES ($1, $2) ON CONFLICT (email) WHERE test_table.another_table_id IS NULL DO UPDATE SET age = (test_table.age + EXCLUDED.age), updated_at = EXCLUDED.updated_at WHERE test_table.updated_at < EXCLUDED.updated_at;"
+        );
+    }
+    #[test]
+    pub fn on_conflict_expression_target() {
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder
+            .insert(TestTableColumn::Email, "john@example.com")
+            .on_conflict_set_excluded(
+                ConflictTargetBuilder::expression(
+                    SqlFunctionBuilder::lower().add_param(TestTableColumn::Email),
+                )
+                .column(TestTableColumn::Age),
+                vec![TestTableColumn::Email],
+            );
+
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "INSERT INTO test_table (email) VALUES ($1) ON CONFLICT (LOWER(test_table.email), age) DO UPDATE SET email = EXCLUDED.email;"
+        );
+    }
+    #[test]
+    pub fn on_conflict_update_requires_set() {
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder
+            .insert(TestTableColumn::Email, "john@example.com")
+            .on_conflict(
+                ConflictTarget::columns(vec![TestTableColumn::Email]),
+                ConflictActionBuilder::do_nothing().filter(TestTableColumn::Age.greater_than(18)),
+            );
+        let Err(err) = builder.try_query() else {
+            panic!("Expected an error");
+        };
+        assert_eq!(
+            err.to_string(),
+            "ON CONFLICT DO UPDATE is missing its SET clause"
+        );
+    }
+    #[test]
+    pub fn insert_rejects_unwritable_columns() {
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder.insert(TestTableColumn::Id, 1.value());
+        let Err(err) = builder.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::ColumnNotWritable { column: "id" }));
+
+        // Insert only columns can be inserted but not updated on conflict
+        let mut builder = super::InsertQueryBuilder::new(TestTable::table_name());
+        builder
+            .insert(TestTableColumn::Email, "john@example.com".value())
+            .insert(TestTableColumn::CreatedAt, SqlFunctionBuilder::now())
+            .on_conflict_set_excluded(
+                ConflictTarget::columns(vec![TestTableColumn::Email]),
+                vec![TestTableColumn::CreatedAt],
+            );
+        let Err(err) = builder.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::ColumnNotWritable {
+                column: "created_at"
+            }
+        ));
+    }
 }
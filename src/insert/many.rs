@@ -1,9 +1,9 @@
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
 use crate::{
     expr::{ArgumentHolder, ConflictQuery, HasArguments, OnConflict, Returning, SupportsReturning},
     prelude::ColumnType,
-    table_layout::concat_columns_no_table_name,
+    table_layout::{concat_columns_no_table_name, quote_table_reference},
     traits::{FormatSql, FormatSqlQuery, QueryTool, SpaceBefore},
 };
 
@@ -16,7 +16,7 @@ pub struct InsertManyBuilder<'args, C: ColumnType> {
     sql: Option<String>,
     returning: Option<Returning>,
     rows: Vec<InsertRow<C>>,
-    table: &'static str,
+    table: Cow<'static, str>,
     arguments: ArgumentHolder<'args>,
     on_conflict: Option<OnConflict>,
 }
@@ -49,11 +49,16 @@ where
     }
 }
 impl<'args, C: ColumnType> InsertManyBuilder<'args, C> {
+    /// Values are matched to `columns` in order. Columns without a value in a row use DEFAULT
+    ///
+    /// Generated and read only columns are removed from `columns`
     pub fn new(table: &'static str, columns: impl Into<Vec<C>>) -> Self {
+        let mut columns = columns.into();
+        columns.retain(|column| column.column_info().is_none_or(|info| info.is_insertable()));
         Self {
-            table,
+            table: quote_table_reference(table),
             arguments: Default::default(),
-            columns_to_insert: columns.into(),
+            columns_to_insert: columns,
             sql: None,
             rows: Vec::new(),
             returning: Default::default(),
@@ -100,7 +105,17 @@ impl<C: ColumnType> SupportsReturning for InsertManyBuilder<'_, C> {
         self
     }
 }
-impl<'args, C> QueryTool<'args> for InsertManyBuilder<'args, C> where C: ColumnType {}
+impl<'args, C> QueryTool<'args> for InsertManyBuilder<'args, C>
+where
+    C: ColumnType,
+{
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("INSERT")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
 impl<C: ColumnType> FormatSqlQuery for InsertManyBuilder<'_, C> {
     #[instrument(skip(self), fields(table = %self.table, statement.type = "INSERT"))]
     fn format_sql_query(&mut self) -> &str {
@@ -129,6 +144,7 @@ mod tests {
     use sqlformat::QueryParams;
 
     use crate::{
+        Error,
         prelude::*,
         testing::{TestTable, TestTableColumn},
     };
@@ -149,7 +165,7 @@ mod tests {
             .return_all();
         assert_eq!(
             query.format_sql_query(),
-            "INSERT INTO test_table (id, first_name, last_name, age, email, phone, another_table_id, updated_at, created_at) VALUES (DEFAULT, $1, $2, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT), (DEFAULT, $3, $4, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT) RETURNING *;"
+            "INSERT INTO test_table (first_name, last_name, age, email, phone, another_table_id, updated_at, created_at) VALUES ($1, $2, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT), ($3, $4, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT) RETURNING *;"
         );
 
         let formatted = sqlformat::format(
@@ -159,4 +175,58 @@ mod tests {
         );
         println!("{formatted}");
     }
+    #[test]
+    fn test_insert_many_ordered_skips_generated_columns() {
+        let mut query =
+            super::InsertManyBuilder::new(TestTable::table_name(), TestTable::columns());
+        query.insert_row_ordered(|row| {
+            row.insert("value1".value()).insert("value2".value());
+        });
+        assert_eq!(
+            query.format_sql_query(),
+            "INSERT INTO test_table (first_name, last_name, age, email, phone, another_table_id, updated_at, created_at) VALUES ($1, $2, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT, DEFAULT);"
+        );
+    }
+    #[test]
+    fn test_insert_many_rejects_unmatched_values() {
+        let mut query =
+            super::InsertManyBuilder::new(TestTable::table_name(), TestTable::columns());
+        query.insert_row(|row| {
+            row.insert(TestTableColumn::Id, 1.value());
+        });
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::ColumnNotWritable { column: "id" }));
+
+        let mut query = super::InsertManyBuilder::new(
+            TestTable::table_name(),
+            vec![TestTableColumn::FirstName],
+        );
+        query.insert_row(|row| {
+            row.insert(TestTableColumn::FirstName, "value1".value())
+                .insert(TestTableColumn::Age, 1.value());
+        });
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::UnmatchedValue {
+                column: Some("age")
+            }
+        ));
+
+        let mut query = super::InsertManyBuilder::new(
+            TestTable::table_name(),
+            vec![TestTableColumn::FirstName],
+        );
+        query.insert_row_ordered(|row| {
+            row.insert("value1".value()).insert("value2".value());
+        });
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::UnmatchedValue { column: None }));
+    }
 }
@@ -1,6 +1,7 @@
 use std::{borrow::Cow, fmt::Debug};
 
 use crate::{
+    error::Error,
     expr::{Expr, ExprType, Keywords},
     prelude::ColumnType,
     traits::FormatSql,
@@ -21,11 +22,14 @@ impl<'query, 'args, C: ColumnType + PartialEq + Clone> InsertRowBuilder<'query,
         }
     }
     /// Insert a value into the query
+    ///
+    /// The query returns an [Error] if the column is generated, read only or not one of the columns of the query
     pub fn insert<E>(&mut self, column: C, value: E) -> &mut Self
     where
         E: ExprType<'args> + 'args,
     {
-        let expr = value.process_unboxed(&mut self.query.arguments);
+        self.query.arguments.check_insertable(&column);
+        let expr = self.query.arguments.process_for_column(&column, value);
         self.columns_to_insert.push((column, expr));
         self
     }
@@ -40,9 +44,7 @@ impl<'query, 'args, C: ColumnType + PartialEq + Clone> InsertRowBuilder<'query,
         E: ExprType<'args> + 'args,
     {
         if let Some(value) = value {
-            let expr = value.process_unboxed(&mut self.query.arguments);
-
-            self.columns_to_insert.push((column, expr));
+            self.insert(column, value);
         } else {
             self.columns_to_insert
                 .push((column, Keywords::Default.into()));
@@ -59,6 +61,12 @@ impl<'query, 'args, C: ColumnType + PartialEq + Clone> InsertRowBuilder<'query,
                 .unwrap_or_else(|| Keywords::Default.into());
             values.push((column.clone(), value));
         }
+        // The values left over were bound but have no placeholder in the row
+        if let Some((column, _)) = self.columns_to_insert.first() {
+            self.query.arguments.push_error(Error::UnmatchedValue {
+                column: Some(column.column_name()),
+            });
+        }
         InsertRow(values)
     }
 }
@@ -104,10 +112,32 @@ impl<'query, 'args, C: ColumnType + PartialEq + Clone> InsertRowOrderedBuilder<'
     where
         E: ExprType<'args> + 'args,
     {
-        let expr = value.process_unboxed(&mut self.query.arguments);
+        let expr = self.process_next(value);
         self.columns_to_insert.push(expr);
         self
     }
+    /// Processes the value for the next column
+    fn process_next<E>(&mut self, value: E) -> Expr
+    where
+        E: ExprType<'args> + 'args,
+    {
+        match self
+            .query
+            .columns_to_insert
+            .get(self.columns_to_insert.len())
+        {
+            Some(column) => {
+                self.query.arguments.check_insertable(column);
+                self.query.arguments.process_for_column(column, value)
+            }
+            None => {
+                self.query
+                    .arguments
+                    .push_error(Error::UnmatchedValue { column: None });
+                value.process_unboxed(&mut self.query.arguments)
+            }
+        }
+    }
 
     /// Will check if option is Some and insert the value if it is
     ///
@@ -119,8 +149,8 @@ impl<'query, 'args, C: ColumnType + PartialEq + Clone> InsertRowOrderedBuilder<'
         E: ExprType<'args> + 'args,
     {
         if let Some(value) = value {
-            self.columns_to_insert
-                .push(value.process_unboxed(&mut self.query.arguments));
+            let expr = self.process_next(value);
+            self.columns_to_insert.push(expr);
         } else {
             self.columns_to_insert.push(Keywords::Default.into());
         }
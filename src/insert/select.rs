@@ -0,0 +1,232 @@
+use std::{borrow::Cow, fmt::Debug};
+
+use crate::{
+    expr::{
+        ArgumentHolder, ConflictQuery, Expr, ExprType, HasArguments, OnConflict, Returning,
+        SelectExprBuilder, SupportsReturning,
+    },
+    prelude::{ColumnType, DynColumn},
+    select::SelectQueryBuilder,
+    table_layout::{concat_columns_no_table_name, quote_ident, quote_table_reference},
+    traits::{FormatSql, FormatSqlQuery, QueryTool, SpaceBefore},
+};
+use tracing::{debug, instrument};
+
+/// `[WITH ...] INSERT INTO {table} ({columns}) SELECT ...`
+///
+/// The columns are mapped to the output of the select in order.
+/// The select's arguments are moved into the arguments of the insert
+pub struct InsertSelectBuilder<'args> {
+    columns: Vec<DynColumn>,
+    ctes: Vec<(Cow<'static, str>, String)>,
+    select: String,
+    sql: Option<String>,
+    returning: Option<Returning>,
+    table: Cow<'static, str>,
+    on_conflict: Option<OnConflict>,
+    arguments: ArgumentHolder<'args>,
+}
+impl Debug for InsertSelectBuilder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InsertSelectBuilder")
+            .field("columns", &self.columns)
+            .field("ctes", &self.ctes)
+            .field("select", &self.select)
+            .field("sql", &self.sql)
+            .field("returning", &self.returning)
+            .field("table", &self.table)
+            .finish()
+    }
+}
+impl<'args> InsertSelectBuilder<'args> {
+    /// Inserts the rows returned by the select query
+    pub fn new<C>(
+        table: &'static str,
+        columns: impl IntoIterator<Item = C>,
+        mut select: SelectQueryBuilder<'args>,
+    ) -> Self
+    where
+        C: ColumnType + 'static,
+    {
+        let select_sql = select.format_sql_query().to_owned();
+        let arguments = std::mem::take(select.holder());
+        Self {
+            table: quote_table_reference(table),
+            columns: columns
+                .into_iter()
+                .map(|column| column.dyn_column())
+                .collect(),
+            ctes: Vec::new(),
+            select: select_sql,
+            sql: None,
+            returning: None,
+            on_conflict: None,
+            arguments,
+        }
+    }
+    /// Inserts the rows returned by the select expression
+    pub fn from_expr<C>(
+        table: &'static str,
+        columns: impl IntoIterator<Item = C>,
+        select: SelectExprBuilder<'args>,
+    ) -> Self
+    where
+        C: ColumnType + 'static,
+    {
+        let mut arguments = ArgumentHolder::default();
+        let Expr::Select(select) = select.process_unboxed(&mut arguments) else {
+            unreachable!("SelectExprBuilder always processes into Expr::Select")
+        };
+        Self {
+            table: quote_table_reference(table),
+            columns: columns
+                .into_iter()
+                .map(|column| column.dyn_column())
+                .collect(),
+            ctes: Vec::new(),
+            select: select.format_as_source(),
+            sql: None,
+            returning: None,
+            on_conflict: None,
+            arguments,
+        }
+    }
+    /// Adds `{name} AS ({query})` to the `WITH` clause.
+    ///
+    /// The select can read from it with `SelectQueryBuilder::new("{name}")`
+    pub fn with(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        query: SelectExprBuilder<'args>,
+    ) -> &mut Self {
+        self.sql = None;
+        let Expr::Select(query) = query.process_unboxed(&mut self.arguments) else {
+            unreachable!("SelectExprBuilder always processes into Expr::Select")
+        };
+        self.ctes.push((name.into(), query.format_as_source()));
+        self
+    }
+}
+impl<'args> ConflictQuery<'args> for InsertSelectBuilder<'args> {
+    fn set_on_conflict(&mut self, on_conflict: OnConflict) -> &mut Self {
+        self.on_conflict = Some(on_conflict);
+        self
+    }
+}
+impl<'args> HasArguments<'args> for InsertSelectBuilder<'args> {
+    fn holder(&mut self) -> &mut ArgumentHolder<'args> {
+        &mut self.arguments
+    }
+}
+impl<'args> QueryTool<'args> for InsertSelectBuilder<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("INSERT")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
+impl SupportsReturning for InsertSelectBuilder<'_> {
+    fn returning(&mut self, returning: Returning) -> &mut Self {
+        self.returning = Some(returning);
+        self
+    }
+}
+impl FormatSqlQuery for InsertSelectBuilder<'_> {
+    #[instrument(skip(self), fields(table = %self.table, statement.type = "INSERT"))]
+    fn format_sql_query(&mut self) -> &str {
+        let mut sql = String::new();
+        if !self.ctes.is_empty() {
+            let ctes: Vec<_> = self
+                .ctes
+                .iter()
+                .map(|(name, query)| format!("{} AS {query}", quote_ident(name)))
+                .collect();
+            sql.push_str("WITH ");
+            sql.push_str(&ctes.join(", "));
+            sql.push(' ');
+        }
+        let columns = concat_columns_no_table_name(&self.columns);
+        sql.push_str(&format!(
+            "INSERT INTO {table} ({columns}) {select}{on_conflict}{returning};",
+            table = self.table,
+            select = self.select,
+            on_conflict = self.on_conflict.format_sql(),
+            returning = SpaceBefore::from(self.returning.as_ref()),
+        ));
+        debug!(?sql, "InsertSelectBuilder::gen_sql");
+        self.sql = Some(sql);
+        self.sql.as_ref().expect("BUG: SQL not generated")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        prelude::*,
+        testing::{AnotherTable, AnotherTableColumn, TestTable, TestTableColumn},
+    };
+
+    use super::InsertSelectBuilder;
+
+    #[test]
+    pub fn insert_from_select() {
+        let mut select = SelectQueryBuilder::with_columns(
+            AnotherTable::table_name(),
+            [AnotherTableColumn::Id, AnotherTableColumn::Phone],
+        );
+        select.filter(AnotherTableColumn::Id.greater_than(10.value()));
+        let mut builder = InsertSelectBuilder::new(
+            TestTable::table_name(),
+            [TestTableColumn::AnotherTableId, TestTableColumn::Phone],
+            select,
+        );
+        builder
+            .on_conflict_do_nothing(ConflictTarget::columns(vec![TestTableColumn::Phone]))
+            .return_columns(vec![TestTableColumn::Id]);
+        assert_eq!(builder.holder().len(), 1);
+
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "INSERT INTO test_table (another_table_id, phone) SELECT another_table.id, another_table.phone FROM another_table WHERE another_table.id > $1 ON CONFLICT (phone) DO NOTHING RETURNING id;"
+        );
+    }
+    #[test]
+    pub fn insert_from_cte() {
+        let mut select = SelectQueryBuilder::new("recent");
+        select.select(SourceColumn::new("recent", "id"));
+        let mut builder = InsertSelectBuilder::new(
+            TestTable::table_name(),
+            [TestTableColumn::AnotherTableId],
+            select,
+        );
+        builder.with(
+            "recent",
+            SelectExprBuilder::new(AnotherTable::table_name())
+                .column(AnotherTableColumn::Id)
+                .filter(AnotherTableColumn::Phone.equals("123".value())),
+        );
+
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "WITH recent AS (SELECT another_table.id FROM another_table WHERE another_table.phone = $1) INSERT INTO test_table (another_table_id) SELECT recent.id FROM recent;"
+        );
+    }
+    #[test]
+    pub fn insert_from_select_expr() {
+        let mut builder = InsertSelectBuilder::from_expr(
+            TestTable::table_name(),
+            [TestTableColumn::Phone],
+            SelectExprBuilder::new(AnotherTable::table_name())
+                .column(AnotherTableColumn::Phone)
+                .filter(AnotherTableColumn::Id.equals(1.value())),
+        );
+        let sql = builder.format_sql_query();
+        assert_eq!(
+            sql,
+            "INSERT INTO test_table (phone) (SELECT another_table.phone FROM another_table WHERE another_table.id = $1);"
+        );
+    }
+}
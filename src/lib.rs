@@ -1,16 +1,108 @@
 #![allow(clippy::wrong_self_convention)]
 
+pub mod copy;
+pub mod ddl;
 pub mod delete;
+pub mod error;
+#[cfg(feature = "explain")]
+pub mod explain;
 pub mod expr;
 #[doc(hidden)]
 pub mod fake;
 pub mod insert;
+pub mod merge;
 pub mod pagination;
+pub mod policy;
+pub mod schema;
 pub mod select;
 pub mod table_layout;
+pub mod telemetry;
+pub mod template;
 pub mod traits;
 pub mod update;
 
+pub use error::Error;
+/// Derive macro for partial updates. i.e. the body of a PATCH request
+///
+/// Generates `apply_to(&mut UpdateQueryBuilder)` and `has_changes()`
+///
+/// - `Option<T>` fields are only set if they are `Some`
+/// - `Option<Option<T>>` fields set NULL for `Some(None)`
+/// - Any other field is always set
+/// - `#[column(skip)]` and `#[column(column_variant_name = Email)]` work the same as [Insertable]
+///
+/// Check `has_changes()` before executing the update. If no column is set the `try_query` methods return [Error::MissingClause].
+/// The infallible `query()` logs the error and sends the invalid SQL to the database
+///
+/// ```
+/// use pg_extended_sqlx_queries::prelude::*;
+/// #[derive(TableType)]
+/// #[table(name = "users")]
+/// pub struct User {
+///     #[column(primary_key)]
+///     pub id: i32,
+///     pub name: String,
+///     pub bio: Option<String>,
+/// }
+/// #[derive(Changeset)]
+/// #[changeset(table = User)]
+/// pub struct UpdateUser {
+///     pub name: Option<String>,
+///     pub bio: Option<Option<String>>,
+/// }
+/// let changes = UpdateUser {
+///     name: Some("Wyatt".to_owned()),
+///     bio: Some(None),
+/// };
+/// assert!(changes.has_changes());
+/// let mut query = UpdateQueryBuilder::new(User::table_name());
+/// query.filter(UserColumn::Id.equals(1.value()));
+/// changes.apply_to(&mut query);
+/// assert_eq!(
+///     query.format_sql_query(),
+///     "UPDATE users SET name = $2, bio = NULL WHERE users.id = $1;"
+/// );
+/// ```
+pub use pg_extended_sqlx_queries_macros::Changeset;
+/// Derive macro for structs that represent a new row of a [TableType](crate::table_layout::TableType)
+///
+/// Generates `into_insert_builder()` and `insert_many(rows)`
+///
+/// - `Option` fields that are `None` use DEFAULT
+/// - `#[column(default)]` fields always use DEFAULT
+/// - `#[column(column_variant_name = Email)]` if the field name does not match the column variant
+/// - `#[insertable(conflict(email))]` makes the insert an upsert. The other inserted columns are set to `EXCLUDED`
+///
+/// ```
+/// use pg_extended_sqlx_queries::prelude::*;
+/// #[derive(TableType)]
+/// #[table(name = "users")]
+/// pub struct User {
+///     #[column(primary_key)]
+///     pub id: i32,
+///     pub name: String,
+///     pub email: String,
+///     pub bio: Option<String>,
+/// }
+/// #[derive(Insertable)]
+/// #[insertable(table = User, conflict(email))]
+/// pub struct NewUser {
+///     pub name: String,
+///     pub email: String,
+///     pub bio: Option<String>,
+/// }
+/// let mut query = NewUser {
+///     name: "Wyatt".to_owned(),
+///     email: "wyatt@example.com".to_owned(),
+///     bio: None,
+/// }
+/// .into_insert_builder();
+/// assert_eq!(
+///     query.format_sql_query(),
+///     "INSERT INTO users (name, email) VALUES ($1, $2) ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name;"
+/// );
+/// ```
+pub use pg_extended_sqlx_queries_macros::Insertable;
 /// Derive macro for generating table types
 /// ```
 /// use pg_extended_sqlx_queries::prelude::*;
@@ -22,6 +114,28 @@ pub mod update;
 ///     pub name: String,
 /// }
 /// ```
+///
+/// ## Write behavior
+/// - `#[column(generated)]` identity or computed columns. Never inserted or updated
+/// - `#[column(read_only)]` never inserted or updated
+/// - `#[column(insert_only)]` never updated. i.e. `created_at`
+///
+/// These are used by [AllColumns::insertable_columns](crate::table_layout::AllColumns::insertable_columns)
+/// and [AllColumns::updatable_columns](crate::table_layout::AllColumns::updatable_columns)
+///
+/// `#[column(sensitive)]` values are redacted by `QueryTool::debug_sql`. Any value written to
+/// or compared with the column is redacted
+///
+/// ## Column types
+/// [ColumnInfo](crate::table_layout::ColumnInfo) uses the [sqlx::Type] of the field.
+/// Fields with `#[sqlx(try_from = "T")]` use `T` and fields with `#[sqlx(json)]` use [sqlx::types::Json].
+/// Other fields that do not implement [sqlx::Type] must name a type with `#[column(type_info = T)]`
+///
+/// ## CRUD
+/// `#[table(crud)]` generates the async methods `find_all` and `count`.
+/// Tables with a primary key also get `find_by_id`, `exists_by_id` and `delete_by_id`.
+///
+/// The struct must implement [sqlx::FromRow]
 pub use pg_extended_sqlx_queries_macros::TableType;
 pub use pg_extended_sqlx_queries_macros::ValueExprType;
 use prelude::ColumnType;
@@ -30,19 +144,24 @@ use prelude::ColumnType;
 pub(crate) mod testing;
 
 pub mod prelude {
+    pub use crate::ddl::*;
     pub use crate::delete::DeleteQueryBuilder;
     pub use crate::expr::{ArgumentHolder, ArgumentIndex, HasArguments};
 
     pub use crate::expr::*;
-    pub use crate::insert::{InsertQueryBuilder, many::*};
+    pub use crate::insert::{InsertQueryBuilder, many::*, select::*};
+    pub use crate::merge::*;
     pub use crate::pagination::*;
     pub use crate::select::*;
     pub use crate::table_layout::{
-        AllColumns, ColumnType, DynColumn, HasPrimaryKey, TableQuery, TableType,
+        AllColumns, ColumnInfo, ColumnType, DynColumn, HasPrimaryKey, TableQuery, TableType,
     };
+    pub use crate::template::*;
     pub use crate::traits::*;
 
     pub use crate::update::UpdateQueryBuilder;
+    pub use pg_extended_sqlx_queries_macros::Changeset;
+    pub use pg_extended_sqlx_queries_macros::Insertable;
     pub use pg_extended_sqlx_queries_macros::TableType;
     pub use pg_extended_sqlx_queries_macros::ValueExprType;
 }
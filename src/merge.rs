@@ -0,0 +1,330 @@
+//! `MERGE` statements. Requires Postgres 15. `RETURNING` requires Postgres 17
+//!
+//! ```
+//! use pg_extended_sqlx_queries::prelude::*;
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     #[column(primary_key)]
+//!     pub id: i32,
+//!     pub name: String,
+//! }
+//! let mut query = MergeQueryBuilder::for_table::<User>();
+//! query
+//!     .using_values(ValuesList::new("v", ["id", "name"]).row([1.value(), "Wyatt".value()]))
+//!     .on(UserColumn::Id.equals(SourceColumn::new("v", "id")))
+//!     .when_matched(WhenMatchedBuilder::new().set(UserColumn::Name, SourceColumn::new("v", "name")))
+//!     .when_not_matched(
+//!         WhenNotMatchedBuilder::new()
+//!             .insert(UserColumn::Id, SourceColumn::new("v", "id"))
+//!             .insert(UserColumn::Name, SourceColumn::new("v", "name")),
+//!     );
+//! assert_eq!(
+//!     query.format_sql_query(),
+//!     "MERGE INTO users USING (VALUES ($1, $2)) AS v (id, name) ON users.id = v.id \
+//!      WHEN MATCHED THEN UPDATE SET name = v.name \
+//!      WHEN NOT MATCHED THEN INSERT (id, name) VALUES (v.id, v.name);"
+//! );
+//! ```
+use std::{borrow::Cow, fmt::Debug};
+
+use tracing::{debug, instrument};
+
+use crate::{error::Error, prelude::*, table_layout::quote_table_reference};
+mod when;
+pub use when::*;
+
+pub struct MergeQueryBuilder<'args> {
+    table: Cow<'static, str>,
+    source: Option<TableSource>,
+    on: Vec<SQLCondition>,
+    when: Vec<MergeWhen>,
+    returning: Option<Returning>,
+    sql: Option<String>,
+    arguments: ArgumentHolder<'args>,
+}
+impl Debug for MergeQueryBuilder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MergeQueryBuilder")
+            .field("table", &self.table)
+            .field("source", &self.source)
+            .field("on", &self.on)
+            .field("when", &self.when)
+            .field("returning", &self.returning)
+            .field("sql", &self.sql)
+            .finish()
+    }
+}
+impl<'args> MergeQueryBuilder<'args> {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table: quote_table_reference(table),
+            source: None,
+            on: Vec::new(),
+            when: Vec::new(),
+            returning: None,
+            sql: None,
+            arguments: Default::default(),
+        }
+    }
+    /// Merges into the table of `T`
+    pub fn for_table<T: TableType>() -> Self {
+        Self::new(T::qualified_table_name())
+    }
+    /// `USING {table}`
+    pub fn using_table(&mut self, table: &'args str) -> &mut Self {
+        self.sql = None;
+        self.source = Some(TableSource::table(table));
+        self
+    }
+    /// `USING {table} AS {alias}`. Use [SourceColumn] to reference its columns
+    pub fn using_table_as(
+        &mut self,
+        table: &'args str,
+        alias: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sql = None;
+        self.source = Some(TableSource::table_as(table, alias));
+        self
+    }
+    /// `USING ({query}) AS {alias}`
+    pub fn using_subquery(
+        &mut self,
+        query: SelectExprBuilder<'args>,
+        alias: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sql = None;
+        self.source = Some(TableSource::subquery(query, alias, &mut self.arguments));
+        self
+    }
+    /// `USING (VALUES ..) AS {alias} ({columns})`
+    pub fn using_values(&mut self, values: ValuesList<'args>) -> &mut Self {
+        self.sql = None;
+        self.source = Some(TableSource::values(values, &mut self.arguments));
+        self
+    }
+    /// Adds a join condition. Multiple conditions are joined with an AND
+    pub fn on<L, R>(&mut self, condition: FilterConditionBuilder<'args, L, R>) -> &mut Self
+    where
+        L: ExprType<'args> + 'args,
+        R: ExprType<'args> + 'args,
+    {
+        self.sql = None;
+        let condition = condition.process_inner(&mut self.arguments);
+        self.on.push(condition);
+        self
+    }
+    /// Adds a `WHEN MATCHED` clause. Clauses are checked in the order they are added
+    pub fn when_matched(&mut self, when: WhenMatchedBuilder<'args>) -> &mut Self {
+        self.sql = None;
+        let when = when.process(&mut self.arguments);
+        self.when.push(when);
+        self
+    }
+    /// Adds a `WHEN NOT MATCHED` clause. Clauses are checked in the order they are added
+    pub fn when_not_matched(&mut self, when: WhenNotMatchedBuilder<'args>) -> &mut Self {
+        self.sql = None;
+        let when = when.process(&mut self.arguments);
+        self.when.push(when);
+        self
+    }
+}
+impl<'args> HasArguments<'args> for MergeQueryBuilder<'args> {
+    fn holder(&mut self) -> &mut ArgumentHolder<'args> {
+        &mut self.arguments
+    }
+}
+impl SupportsReturning for MergeQueryBuilder<'_> {
+    fn returning(&mut self, returning: Returning) -> &mut Self {
+        self.sql = None;
+        self.returning = Some(returning);
+        self
+    }
+}
+impl FormatSqlQuery for MergeQueryBuilder<'_> {
+    #[instrument(skip(self), fields(table = %self.table, statement.type = "MERGE"))]
+    fn format_sql_query(&mut self) -> &str {
+        let mut sql = format!("MERGE INTO {}", self.table);
+        if let Some(source) = &self.source {
+            sql.push_str(" USING ");
+            sql.push_str(&source.format_sql());
+        }
+        if !self.on.is_empty() {
+            let on: Vec<_> = self
+                .on
+                .iter()
+                .map(|condition| condition.format_sql())
+                .collect();
+            sql.push_str(" ON ");
+            sql.push_str(&on.join(" AND "));
+        }
+        for when in &self.when {
+            sql.push(' ');
+            sql.push_str(&when.format_sql());
+        }
+        if let Some(returning) = &self.returning {
+            // The source can have columns with the same names
+            sql.push(' ');
+            sql.push_str(&returning.format_qualified(&self.table));
+        }
+        sql.push(';');
+        debug!(?sql, "MergeQueryBuilder::format_sql_query");
+        self.sql = Some(sql);
+
+        self.sql.as_ref().expect("SQL not set")
+    }
+}
+impl<'args> QueryTool<'args> for MergeQueryBuilder<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("MERGE")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+    fn validate(&self) -> Result<(), Error> {
+        let clause = if self.source.is_none() {
+            "USING"
+        } else if self.on.is_empty() {
+            "ON"
+        } else if self.when.is_empty() {
+            "WHEN"
+        } else {
+            return Ok(());
+        };
+        Err(Error::MissingClause {
+            statement: "MERGE",
+            clause,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Error,
+        prelude::*,
+        testing::{AnotherTable, AnotherTableColumn, TestTable, TestTableColumn},
+    };
+
+    #[test]
+    fn merge_from_table() {
+        let mut query = MergeQueryBuilder::for_table::<TestTable>();
+        query
+            .using_table_as(AnotherTable::table_name(), "a")
+            .on(TestTableColumn::AnotherTableId
+                .equals(SourceColumn::of("a", AnotherTableColumn::Id)))
+            .when_matched(
+                WhenMatchedBuilder::new()
+                    .filter(TestTableColumn::Age.greater_than(100.value()))
+                    .delete(),
+            )
+            .when_matched(
+                WhenMatchedBuilder::new()
+                    .filter(
+                        TestTableColumn::UpdatedAt
+                            .less_than(SourceColumn::of("a", AnotherTableColumn::UpdatedAt)),
+                    )
+                    .set(
+                        TestTableColumn::Email,
+                        SourceColumn::of("a", AnotherTableColumn::Email),
+                    )
+                    .set(TestTableColumn::Phone, "123".value()),
+            )
+            .when_not_matched(
+                WhenNotMatchedBuilder::new()
+                    .insert(
+                        TestTableColumn::AnotherTableId,
+                        SourceColumn::of("a", AnotherTableColumn::Id),
+                    )
+                    .insert(
+                        TestTableColumn::Email,
+                        SourceColumn::of("a", AnotherTableColumn::Email),
+                    ),
+            )
+            .return_columns(vec![TestTableColumn::Id]);
+
+        assert_eq!(
+            query.format_sql_query(),
+            "MERGE INTO test_table USING another_table AS a ON test_table.another_table_id = a.id \
+             WHEN MATCHED AND test_table.age > $1 THEN DELETE \
+             WHEN MATCHED AND test_table.updated_at < a.updated_at THEN UPDATE SET email = a.email, phone = $2 \
+             WHEN NOT MATCHED THEN INSERT (another_table_id, email) VALUES (a.id, a.email) RETURNING test_table.id;"
+        );
+        #[cfg(feature = "debug-sql")]
+        assert!(query.holder().debug_values()[1].is_sensitive());
+    }
+    #[test]
+    fn merge_from_subquery() {
+        let mut query = MergeQueryBuilder::new(TestTable::table_name());
+        query
+            .using_subquery(
+                SelectExprBuilder::new(AnotherTable::table_name())
+                    .column(AnotherTableColumn::Id)
+                    .filter(AnotherTableColumn::Age.greater_than(18.value())),
+                "s",
+            )
+            .on(TestTableColumn::AnotherTableId.equals(SourceColumn::new("s", "id")))
+            .when_matched(WhenMatchedBuilder::new().do_nothing())
+            .when_not_matched(WhenNotMatchedBuilder::new().insert(
+                TestTableColumn::AnotherTableId,
+                SourceColumn::new("s", "id"),
+            ));
+        assert_eq!(
+            query.format_sql_query(),
+            "MERGE INTO test_table USING (SELECT another_table.id FROM another_table WHERE another_table.age > $1) AS s \
+             ON test_table.another_table_id = s.id \
+             WHEN MATCHED THEN DO NOTHING \
+             WHEN NOT MATCHED THEN INSERT (another_table_id) VALUES (s.id);"
+        );
+    }
+    #[test]
+    fn merge_requires_clauses() {
+        let mut query = MergeQueryBuilder::new(TestTable::table_name());
+        query.using_table(AnotherTable::table_name());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::MissingClause {
+                statement: "MERGE",
+                clause: "ON"
+            }
+        ));
+    }
+    #[test]
+    fn merge_rejects_unwritable_columns() {
+        let mut query = MergeQueryBuilder::for_table::<TestTable>();
+        query
+            .using_table_as(AnotherTable::table_name(), "a")
+            .on(TestTableColumn::AnotherTableId
+                .equals(SourceColumn::of("a", AnotherTableColumn::Id)))
+            .when_matched(WhenMatchedBuilder::new().set(
+                TestTableColumn::CreatedAt,
+                SourceColumn::of("a", AnotherTableColumn::CreatedAt),
+            ));
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::ColumnNotWritable {
+                column: "created_at"
+            }
+        ));
+
+        let mut query = MergeQueryBuilder::for_table::<TestTable>();
+        query
+            .using_table_as(AnotherTable::table_name(), "a")
+            .on(TestTableColumn::AnotherTableId
+                .equals(SourceColumn::of("a", AnotherTableColumn::Id)))
+            .when_not_matched(WhenNotMatchedBuilder::new().insert(
+                TestTableColumn::Id,
+                SourceColumn::of("a", AnotherTableColumn::Id),
+            ));
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::ColumnNotWritable { column: "id" }));
+    }
+}
@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+
+use crate::{prelude::*, table_layout::concat_columns_no_table_name};
+
+type DynFilter<'args> = FilterConditionBuilder<'args, DynExpr<'args>, DynExpr<'args>>;
+
+/// `WHEN MATCHED [AND {conditions}] THEN ...`
+///
+/// Does nothing unless [set](Self::set) or [delete](Self::delete) is called
+pub struct WhenMatchedBuilder<'args> {
+    conditions: Vec<DynFilter<'args>>,
+    action: MatchedActionBuilder<'args>,
+}
+enum MatchedActionBuilder<'args> {
+    DoNothing,
+    Update(Vec<(DynColumn, DynExpr<'args>)>),
+    Delete,
+}
+impl<'args> WhenMatchedBuilder<'args> {
+    pub fn new() -> Self {
+        Self {
+            conditions: Vec::new(),
+            action: MatchedActionBuilder::DoNothing,
+        }
+    }
+    /// `THEN UPDATE SET {column} = {value}`
+    ///
+    /// If the column is a typed column constant. i.e. `UserColumn::NAME` the value must match the type of the field.
+    /// The query returns an [Error::ColumnNotWritable](crate::Error::ColumnNotWritable) if the column is generated, read only or insert only
+    pub fn set<C, V>(mut self, column: C, value: V) -> Self
+    where
+        C: ColumnTarget<'args, V>,
+        V: ExprType<'args> + 'args,
+    {
+        let set = (column.target_column(), DynExpr::new(value));
+        match &mut self.action {
+            MatchedActionBuilder::Update(columns) => columns.push(set),
+            action => *action = MatchedActionBuilder::Update(vec![set]),
+        }
+        self
+    }
+    /// `THEN DELETE`
+    pub fn delete(mut self) -> Self {
+        self.action = MatchedActionBuilder::Delete;
+        self
+    }
+    /// `THEN DO NOTHING`
+    pub fn do_nothing(mut self) -> Self {
+        self.action = MatchedActionBuilder::DoNothing;
+        self
+    }
+    pub(crate) fn process(self, args: &mut ArgumentHolder<'args>) -> MergeWhen {
+        let conditions = conditions_process(self.conditions, args);
+        let action = match self.action {
+            MatchedActionBuilder::DoNothing => MergeAction::DoNothing,
+            MatchedActionBuilder::Delete => MergeAction::Delete,
+            MatchedActionBuilder::Update(columns) => MergeAction::Update(
+                columns
+                    .into_iter()
+                    .map(|(column, value)| {
+                        args.check_updatable(&column);
+                        let value = args.process_for_column(&column, value);
+                        (column, value)
+                    })
+                    .collect(),
+            ),
+        };
+        MergeWhen {
+            matched: true,
+            conditions,
+            action,
+        }
+    }
+}
+impl Default for WhenMatchedBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<'args> ExpressionWhereable<'args> for WhenMatchedBuilder<'args> {
+    fn push_where_comparison<L: ExprType<'args> + 'args, R: ExprType<'args> + 'args>(
+        &mut self,
+        comparison: FilterConditionBuilder<'args, L, R>,
+    ) {
+        self.conditions.push(comparison.dyn_expression());
+    }
+}
+/// `WHEN NOT MATCHED [AND {conditions}] THEN ...`
+///
+/// Does nothing unless [insert](Self::insert) is called
+pub struct WhenNotMatchedBuilder<'args> {
+    conditions: Vec<DynFilter<'args>>,
+    insert: Vec<(DynColumn, DynExpr<'args>)>,
+}
+impl<'args> WhenNotMatchedBuilder<'args> {
+    pub fn new() -> Self {
+        Self {
+            conditions: Vec::new(),
+            insert: Vec::new(),
+        }
+    }
+    /// `THEN INSERT ({column}, ..) VALUES ({value}, ..)`
+    ///
+    /// Use [SourceColumn] to insert the values of the source row.
+    /// The query returns an [Error::ColumnNotWritable](crate::Error::ColumnNotWritable) if the column is generated or read only
+    pub fn insert<C, V>(mut self, column: C, value: V) -> Self
+    where
+        C: ColumnTarget<'args, V>,
+        V: ExprType<'args> + 'args,
+    {
+        self.insert
+            .push((column.target_column(), DynExpr::new(value)));
+        self
+    }
+    pub(crate) fn process(self, args: &mut ArgumentHolder<'args>) -> MergeWhen {
+        let conditions = conditions_process(self.conditions, args);
+        let action = if self.insert.is_empty() {
+            MergeAction::DoNothing
+        } else {
+            let (columns, values) = self
+                .insert
+                .into_iter()
+                .map(|(column, value)| {
+                    args.check_insertable(&column);
+                    let value = args.process_for_column(&column, value);
+                    (column, value)
+                })
+                .unzip();
+            MergeAction::Insert { columns, values }
+        };
+        MergeWhen {
+            matched: false,
+            conditions,
+            action,
+        }
+    }
+}
+impl Default for WhenNotMatchedBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<'args> ExpressionWhereable<'args> for WhenNotMatchedBuilder<'args> {
+    fn push_where_comparison<L: ExprType<'args> + 'args, R: ExprType<'args> + 'args>(
+        &mut self,
+        comparison: FilterConditionBuilder<'args, L, R>,
+    ) {
+        self.conditions.push(comparison.dyn_expression());
+    }
+}
+fn conditions_process<'args>(
+    conditions: Vec<DynFilter<'args>>,
+    args: &mut ArgumentHolder<'args>,
+) -> Vec<SQLCondition> {
+    conditions
+        .into_iter()
+        .map(|condition| condition.process_inner(args))
+        .collect()
+}
+#[derive(Debug)]
+pub enum MergeAction {
+    DoNothing,
+    Delete,
+    Update(Vec<(DynColumn, Expr)>),
+    Insert {
+        columns: Vec<DynColumn>,
+        values: Vec<Expr>,
+    },
+}
+impl FormatSql for MergeAction {
+    fn format_sql(&self) -> Cow<'_, str> {
+        match self {
+            Self::DoNothing => Cow::Borrowed("DO NOTHING"),
+            Self::Delete => Cow::Borrowed("DELETE"),
+            Self::Update(columns) => {
+                let columns = columns
+                    .iter()
+                    .map(|(column, value)| {
+                        format!("{} = {}", column.quoted_column_name(), value.format_sql())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Cow::Owned(format!("UPDATE SET {columns}"))
+            }
+            Self::Insert { columns, values } => {
+                let values = values
+                    .iter()
+                    .map(|value| value.format_sql())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Cow::Owned(format!(
+                    "INSERT ({}) VALUES ({values})",
+                    concat_columns_no_table_name(columns)
+                ))
+            }
+        }
+    }
+}
+/// A processed `WHEN` clause of a `MERGE`
+#[derive(Debug)]
+pub struct MergeWhen {
+    pub matched: bool,
+    pub conditions: Vec<SQLCondition>,
+    pub action: MergeAction,
+}
+impl FormatSql for MergeWhen {
+    fn format_sql(&self) -> Cow<'_, str> {
+        let mut sql = String::from(if self.matched {
+            "WHEN MATCHED"
+        } else {
+            "WHEN NOT MATCHED"
+        });
+        for condition in &self.conditions {
+            sql.push_str(" AND ");
+            sql.push_str(&condition.format_sql());
+        }
+        sql.push_str(" THEN ");
+        sql.push_str(&self.action.format_sql());
+        Cow::Owned(sql)
+    }
+}
@@ -0,0 +1,49 @@
+//! Crate wide policies
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use tracing::warn;
+
+use crate::error::Error;
+
+static UNFILTERED_WRITES: AtomicU8 = AtomicU8::new(UnfilteredWrites::Deny as u8);
+
+/// What happens to an `UPDATE` or `DELETE` without a `WHERE` that did not call `all_rows()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum UnfilteredWrites {
+    /// The `try_query*` methods return [Error::UnfilteredWrite].
+    /// The infallible `query()` logs it and executes the query
+    #[default]
+    Deny,
+    /// Logs a warning and executes the query
+    Warn,
+    Allow,
+}
+impl UnfilteredWrites {
+    /// Sets the policy for the whole process
+    pub fn set(self) {
+        UNFILTERED_WRITES.store(self as u8, Ordering::Relaxed);
+    }
+    /// The current policy. Defaults to [UnfilteredWrites::Deny]
+    pub fn current() -> Self {
+        match UNFILTERED_WRITES.load(Ordering::Relaxed) {
+            1 => Self::Warn,
+            2 => Self::Allow,
+            _ => Self::Deny,
+        }
+    }
+    /// Checks a write without a `WHERE` against the current policy
+    pub(crate) fn check(statement: &'static str, table: &str) -> Result<(), Error> {
+        match Self::current() {
+            Self::Deny => Err(Error::UnfilteredWrite {
+                statement,
+                table: table.to_owned(),
+            }),
+            Self::Warn => {
+                warn!(statement, table, "Executing a write without a WHERE clause");
+                Ok(())
+            }
+            Self::Allow => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Schema snapshots and diffs
+//!
+//! A [SchemaSnapshot] can be built from [TableType](crate::table_layout::TableType) definitions,
+//! read from `information_schema` or loaded from JSON (requires the `schema-json` feature).
+//!
+//! Two snapshots can be compared with [SchemaDiff] to generate an ordered migration script.
+//!
+//! [verify_schema] checks a single table against the live database. i.e. at application startup
+//!
+//! ```rust
+//! use pg_extended_sqlx_queries::{prelude::*, schema::*};
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     #[column(primary_key)]
+//!     pub id: i32,
+//!     pub name: String,
+//! }
+//! let mut desired = SchemaSnapshot::default();
+//! desired.add_table::<User>()?;
+//!
+//! let diff = SchemaDiff::between(&SchemaSnapshot::default(), &desired);
+//! assert_eq!(
+//!     diff.to_script(),
+//!     "-- Create table users\nCREATE TABLE users (id INT4 PRIMARY KEY, name TEXT NOT NULL);\n"
+//! );
+//! # Ok::<(), pg_extended_sqlx_queries::Error>(())
+//! ```
+mod diff;
+mod snapshot;
+mod verify;
+pub use diff::*;
+pub use snapshot::*;
+pub use verify::*;
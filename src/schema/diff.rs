@@ -0,0 +1,305 @@
+use crate::table_layout::quote_ident;
+
+use super::{ColumnSnapshot, IndexSnapshot, SchemaSnapshot, TableSnapshot, normalize_sql_type};
+
+/// A single step of a migration.
+///
+/// Steps are ordered by their variant. Tables are created before columns are changed and indexes are created last.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationStep {
+    CreateTable(TableSnapshot),
+    AddColumn {
+        table: String,
+        column: ColumnSnapshot,
+    },
+    /// Destructive. The existing values might not convert to the new type
+    AlterColumnType {
+        table: String,
+        column: String,
+        from: String,
+        to: String,
+    },
+    DropNotNull {
+        table: String,
+        column: String,
+    },
+    SetNotNull {
+        table: String,
+        column: String,
+    },
+    /// Destructive. The data in the column is lost
+    DropColumn {
+        table: String,
+        column: String,
+    },
+    CreateIndex {
+        table: String,
+        index: IndexSnapshot,
+    },
+}
+impl MigrationStep {
+    fn order(&self) -> u8 {
+        match self {
+            Self::CreateTable(_) => 0,
+            Self::AddColumn { .. } => 1,
+            Self::AlterColumnType { .. } => 2,
+            Self::DropNotNull { .. } => 3,
+            Self::SetNotNull { .. } => 4,
+            Self::DropColumn { .. } => 5,
+            Self::CreateIndex { .. } => 6,
+        }
+    }
+    /// If the step can lose data
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, Self::AlterColumnType { .. } | Self::DropColumn { .. })
+    }
+    /// Adding a `NOT NULL` column without a default fails if the table has rows
+    pub fn requires_empty_table(&self) -> bool {
+        matches!(self, Self::AddColumn { column, .. } if !column.nullable && column.default.is_none())
+    }
+    /// A short description of the step. Used as the comment in [SchemaDiff::to_script]
+    pub fn description(&self) -> String {
+        match self {
+            Self::CreateTable(table) => format!("Create table {}", table.qualified_name()),
+            Self::AddColumn { table, column } => {
+                format!("Add column {} to {table}", column.name)
+            }
+            Self::AlterColumnType {
+                table,
+                column,
+                from,
+                to,
+            } => format!("Change type of {table}.{column} from {from} to {to}"),
+            Self::DropNotNull { table, column } => format!("Make {table}.{column} nullable"),
+            Self::SetNotNull { table, column } => format!("Make {table}.{column} not null"),
+            Self::DropColumn { table, column } => format!("Drop column {column} from {table}"),
+            Self::CreateIndex { table, index } => format!("Create index {} on {table}", index.name),
+        }
+    }
+    pub fn sql(&self) -> String {
+        match self {
+            Self::CreateTable(table) => table.create_table_sql(),
+            Self::AddColumn { table, column } => {
+                format!("ALTER TABLE {table} ADD COLUMN {};", column.definition())
+            }
+            Self::AlterColumnType {
+                table, column, to, ..
+            } => format!(
+                "ALTER TABLE {table} ALTER COLUMN {} TYPE {to};",
+                quote_ident(column)
+            ),
+            Self::DropNotNull { table, column } => format!(
+                "ALTER TABLE {table} ALTER COLUMN {} DROP NOT NULL;",
+                quote_ident(column)
+            ),
+            Self::SetNotNull { table, column } => format!(
+                "ALTER TABLE {table} ALTER COLUMN {} SET NOT NULL;",
+                quote_ident(column)
+            ),
+            Self::DropColumn { table, column } => {
+                format!("ALTER TABLE {table} DROP COLUMN {};", quote_ident(column))
+            }
+            Self::CreateIndex { index, .. } => format!("{};", index.definition),
+        }
+    }
+}
+/// The steps required to move from one [SchemaSnapshot] to another
+///
+/// Tables that only exist in the current snapshot are ignored.
+/// Indexes are compared by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaDiff {
+    pub steps: Vec<MigrationStep>,
+}
+impl SchemaDiff {
+    pub fn between(current: &SchemaSnapshot, desired: &SchemaSnapshot) -> Self {
+        let mut steps = Vec::new();
+        for desired_table in &desired.tables {
+            let Some(current_table) = current.table(&desired_table.schema, &desired_table.name)
+            else {
+                steps.push(MigrationStep::CreateTable(desired_table.clone()));
+                for index in &desired_table.indexes {
+                    steps.push(MigrationStep::CreateIndex {
+                        table: desired_table.qualified_name(),
+                        index: index.clone(),
+                    });
+                }
+                continue;
+            };
+            diff_table(current_table, desired_table, &mut steps);
+        }
+        // Stable so steps of the same kind keep the order of the tables and columns
+        steps.sort_by_key(MigrationStep::order);
+        Self { steps }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+    pub fn has_destructive_steps(&self) -> bool {
+        self.steps.iter().any(MigrationStep::is_destructive)
+    }
+    /// See [MigrationStep::requires_empty_table]
+    pub fn has_steps_requiring_empty_tables(&self) -> bool {
+        self.steps.iter().any(MigrationStep::requires_empty_table)
+    }
+    /// The migration as a SQL script.
+    ///
+    /// Each step is preceded by a comment. Destructive steps are marked with `-- DESTRUCTIVE:`
+    /// and steps that fail on tables with rows with `-- REQUIRES EMPTY TABLE:`
+    pub fn to_script(&self) -> String {
+        let mut script = String::new();
+        for step in &self.steps {
+            if step.is_destructive() {
+                script.push_str("-- DESTRUCTIVE: ");
+            } else if step.requires_empty_table() {
+                script.push_str("-- REQUIRES EMPTY TABLE: ");
+            } else {
+                script.push_str("-- ");
+            }
+            script.push_str(&step.description());
+            script.push('\n');
+            script.push_str(&step.sql());
+            script.push('\n');
+        }
+        script
+    }
+}
+fn diff_table(current: &TableSnapshot, desired: &TableSnapshot, steps: &mut Vec<MigrationStep>) {
+    let table = desired.qualified_name();
+    for column in &desired.columns {
+        let Some(current_column) = current.column(&column.name) else {
+            steps.push(MigrationStep::AddColumn {
+                table: table.clone(),
+                column: column.clone(),
+            });
+            continue;
+        };
+        let from = normalize_sql_type(&current_column.data_type);
+        let to = normalize_sql_type(&column.data_type);
+        if from != to {
+            steps.push(MigrationStep::AlterColumnType {
+                table: table.clone(),
+                column: column.name.clone(),
+                from,
+                to,
+            });
+        }
+        match (current_column.nullable, column.nullable) {
+            (false, true) => steps.push(MigrationStep::DropNotNull {
+                table: table.clone(),
+                column: column.name.clone(),
+            }),
+            (true, false) => steps.push(MigrationStep::SetNotNull {
+                table: table.clone(),
+                column: column.name.clone(),
+            }),
+            _ => {}
+        }
+    }
+    for column in &current.columns {
+        if desired.column(&column.name).is_none() {
+            steps.push(MigrationStep::DropColumn {
+                table: table.clone(),
+                column: column.name.clone(),
+            });
+        }
+    }
+    for index in &desired.indexes {
+        if !current
+            .indexes
+            .iter()
+            .any(|current| current.name == index.name)
+        {
+            steps.push(MigrationStep::CreateIndex {
+                table: table.clone(),
+                index: index.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ddl::CreateIndex,
+        testing::{AuthUser, TestTable, TestTableColumn},
+    };
+
+    #[test]
+    fn create_missing_tables() {
+        let mut desired = SchemaSnapshot::default();
+        desired.add_table::<AuthUser>().unwrap();
+        desired.add_table::<TestTable>().unwrap().add_index(
+            CreateIndex::for_table::<TestTable>("test_table_email_idx")
+                .column(TestTableColumn::Email),
+        );
+
+        let diff = SchemaDiff::between(&SchemaSnapshot::default(), &desired);
+        assert!(!diff.has_destructive_steps());
+        let steps: Vec<_> = diff.steps.iter().map(MigrationStep::description).collect();
+        assert_eq!(
+            steps,
+            vec![
+                "Create table auth.users",
+                "Create table test_table",
+                "Create index test_table_email_idx on test_table"
+            ]
+        );
+    }
+    #[test]
+    fn alter_existing_table() {
+        let mut current = SchemaSnapshot::default();
+        let table = current.add_table::<AuthUser>().unwrap();
+        // Simulate what information_schema returns for the table
+        table.columns[0].data_type = "INT4".to_owned();
+        table.columns[1].data_type = "VARCHAR(16)".to_owned();
+        table.columns[2].nullable = false;
+        table.columns.remove(3);
+        table.columns.push(ColumnSnapshot {
+            name: "legacy".to_owned(),
+            data_type: "TEXT".to_owned(),
+            nullable: true,
+            default: None,
+            unique: false,
+            check: None,
+        });
+
+        let mut desired = SchemaSnapshot::default();
+        desired.add_table::<AuthUser>().unwrap();
+
+        let diff = SchemaDiff::between(&current, &desired);
+        assert!(diff.has_destructive_steps());
+        assert!(!diff.has_steps_requiring_empty_tables());
+        assert_eq!(
+            diff.to_script(),
+            "-- Add column created_at to auth.users\n\
+            ALTER TABLE auth.users ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();\n\
+            -- DESTRUCTIVE: Change type of auth.users.username from VARCHAR(16) to VARCHAR(32)\n\
+            ALTER TABLE auth.users ALTER COLUMN username TYPE VARCHAR(32);\n\
+            -- Make auth.users.test_table_id nullable\n\
+            ALTER TABLE auth.users ALTER COLUMN test_table_id DROP NOT NULL;\n\
+            -- DESTRUCTIVE: Drop column legacy from auth.users\n\
+            ALTER TABLE auth.users DROP COLUMN legacy;\n"
+        );
+
+        assert!(SchemaDiff::between(&desired, &desired).is_empty());
+    }
+    #[test]
+    fn flag_not_null_columns_without_default() {
+        let mut current = SchemaSnapshot::default();
+        current.add_table::<AuthUser>().unwrap().columns.remove(1);
+
+        let mut desired = SchemaSnapshot::default();
+        desired.add_table::<AuthUser>().unwrap();
+
+        let diff = SchemaDiff::between(&current, &desired);
+        assert!(!diff.has_destructive_steps());
+        assert!(diff.has_steps_requiring_empty_tables());
+        assert_eq!(
+            diff.to_script(),
+            "-- REQUIRES EMPTY TABLE: Add column username to auth.users\n\
+            ALTER TABLE auth.users ADD COLUMN username VARCHAR(32) NOT NULL UNIQUE CHECK (username <> '');\n"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use std::borrow::Cow;
+
+use sqlx::{Acquire, Postgres, Row};
+
+use crate::{
+    ddl::{ColumnDefinition, CreateIndex, create_table_sql},
+    error::Error,
+    prelude::*,
+    table_layout::quote_ident,
+};
+
+/// The schema used when a table does not define one
+pub const DEFAULT_SCHEMA: &str = "public";
+
+/// A snapshot of a set of tables
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema-json", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchemaSnapshot {
+    pub tables: Vec<TableSnapshot>,
+}
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema-json", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableSnapshot {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<ColumnSnapshot>,
+    /// The columns of the primary key in order
+    #[cfg_attr(feature = "schema-json", serde(default))]
+    pub primary_key: Vec<String>,
+    #[cfg_attr(feature = "schema-json", serde(default))]
+    pub indexes: Vec<IndexSnapshot>,
+}
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema-json", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnSnapshot {
+    pub name: String,
+    /// The type as it would be written in SQL. i.e. `INT4` or `VARCHAR(32)`
+    pub data_type: String,
+    pub nullable: bool,
+    #[cfg_attr(feature = "schema-json", serde(default))]
+    pub default: Option<String>,
+    /// Only used when creating the column. Not compared
+    #[cfg_attr(feature = "schema-json", serde(default))]
+    pub unique: bool,
+    /// Only used when creating the column. Not compared
+    #[cfg_attr(feature = "schema-json", serde(default))]
+    pub check: Option<String>,
+}
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema-json", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexSnapshot {
+    pub name: String,
+    /// The full `CREATE INDEX` statement
+    pub definition: String,
+}
+impl SchemaSnapshot {
+    /// Adds a table from its [TableType] definition. See [TableSnapshot::from_table]
+    pub fn add_table<T>(&mut self) -> Result<&mut TableSnapshot, Error>
+    where
+        T: TableType,
+        T::Columns: AllColumns,
+    {
+        self.tables.push(TableSnapshot::from_table::<T>()?);
+        Ok(self.tables.last_mut().expect("Table was just pushed"))
+    }
+    pub fn table(&self, schema: &str, name: &str) -> Option<&TableSnapshot> {
+        self.tables
+            .iter()
+            .find(|table| table.schema == schema && table.name == name)
+    }
+    /// Reads the tables of the given schemas from `information_schema` and `pg_indexes`
+    pub async fn load<'c, A>(conn: A, schemas: &[&str]) -> Result<Self, sqlx::Error>
+    where
+        A: Acquire<'c, Database = Postgres>,
+    {
+        let mut conn = conn.acquire().await?;
+        let schemas: Vec<&str> = schemas.to_vec();
+
+        let mut snapshot = Self::default();
+        let columns = sqlx::query(
+            "SELECT c.table_schema::TEXT AS table_schema, c.table_name::TEXT AS table_name, \
+            c.column_name::TEXT AS column_name, format_type(a.atttypid, a.atttypmod) AS data_type, \
+            (c.is_nullable = 'YES') AS nullable, c.column_default::TEXT AS column_default \
+            FROM information_schema.columns c \
+            JOIN information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+            JOIN pg_attribute a ON a.attrelid = format('%I.%I', c.table_schema, c.table_name)::regclass \
+            AND a.attname = c.column_name \
+            WHERE t.table_type = 'BASE TABLE' AND c.table_schema = ANY($1) \
+            ORDER BY c.table_schema, c.table_name, c.ordinal_position",
+        )
+        .bind(&schemas)
+        .fetch_all(&mut *conn)
+        .await?;
+        for row in columns {
+            let schema: String = row.try_get("table_schema")?;
+            let name: String = row.try_get("table_name")?;
+            let data_type: String = row.try_get("data_type")?;
+            let column = ColumnSnapshot {
+                name: row.try_get("column_name")?,
+                // Includes the modifiers. i.e. `numeric(10,2)` or `timestamp(3) with time zone`
+                data_type: normalize_sql_type(&data_type),
+                nullable: row.try_get("nullable")?,
+                default: row.try_get("column_default")?,
+                unique: false,
+                check: None,
+            };
+            snapshot.table_or_insert(schema, name).columns.push(column);
+        }
+
+        let primary_keys = sqlx::query(
+            "SELECT tc.table_schema::TEXT AS table_schema, tc.table_name::TEXT AS table_name, \
+            kcu.column_name::TEXT AS column_name \
+            FROM information_schema.table_constraints tc \
+            JOIN information_schema.key_column_usage kcu ON tc.constraint_name = kcu.constraint_name \
+            AND tc.table_schema = kcu.table_schema AND tc.table_name = kcu.table_name \
+            WHERE tc.constraint_type = 'PRIMARY KEY' AND tc.table_schema = ANY($1) \
+            ORDER BY kcu.ordinal_position",
+        )
+        .bind(&schemas)
+        .fetch_all(&mut *conn)
+        .await?;
+        for row in primary_keys {
+            let schema: String = row.try_get("table_schema")?;
+            let name: String = row.try_get("table_name")?;
+            let column: String = row.try_get("column_name")?;
+            snapshot
+                .table_or_insert(schema, name)
+                .primary_key
+                .push(column);
+        }
+
+        let indexes = sqlx::query(
+            "SELECT schemaname::TEXT AS schema_name, tablename::TEXT AS table_name, \
+            indexname::TEXT AS index_name, indexdef \
+            FROM pg_indexes WHERE schemaname = ANY($1) ORDER BY indexname",
+        )
+        .bind(&schemas)
+        .fetch_all(&mut *conn)
+        .await?;
+        for row in indexes {
+            let schema: String = row.try_get("schema_name")?;
+            let name: String = row.try_get("table_name")?;
+            let index = IndexSnapshot {
+                name: row.try_get("index_name")?,
+                definition: row.try_get("indexdef")?,
+            };
+            snapshot.table_or_insert(schema, name).indexes.push(index);
+        }
+        Ok(snapshot)
+    }
+    fn table_or_insert(&mut self, schema: String, name: String) -> &mut TableSnapshot {
+        let index = match self
+            .tables
+            .iter()
+            .position(|table| table.schema == schema && table.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.tables.push(TableSnapshot {
+                    schema,
+                    name,
+                    columns: Vec::new(),
+                    primary_key: Vec::new(),
+                    indexes: Vec::new(),
+                });
+                self.tables.len() - 1
+            }
+        };
+        &mut self.tables[index]
+    }
+    #[cfg(feature = "schema-json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+    #[cfg(feature = "schema-json")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+impl TableSnapshot {
+    /// Builds the snapshot from the [ColumnInfo] of the table
+    ///
+    /// Returns [Error::MissingColumnInfo] if a column does not provide [ColumnType::column_info]
+    pub fn from_table<T>() -> Result<Self, Error>
+    where
+        T: TableType,
+        T::Columns: AllColumns,
+    {
+        let mut columns = Vec::new();
+        let mut primary_key = Vec::new();
+        for column in T::Columns::all() {
+            let info = column.column_info().ok_or(Error::MissingColumnInfo {
+                column: column.column_name(),
+            })?;
+            if info.primary_key {
+                primary_key.push(info.name.to_owned());
+            }
+            columns.push(ColumnSnapshot {
+                name: info.name.to_owned(),
+                data_type: info.type_name().to_owned(),
+                nullable: info.nullable && !info.primary_key,
+                default: info.default.map(ToOwned::to_owned),
+                unique: info.unique,
+                check: info.check.map(ToOwned::to_owned),
+            });
+        }
+        Ok(Self {
+            schema: T::schema_name().unwrap_or(DEFAULT_SCHEMA).to_owned(),
+            name: T::table_name().to_owned(),
+            columns,
+            primary_key,
+            indexes: Vec::new(),
+        })
+    }
+    /// Adds an index that should exist on the table
+    pub fn add_index(&mut self, index: &mut CreateIndex<'_>) -> &mut Self {
+        let definition = index.format_sql_query().trim_end_matches(';').to_owned();
+        self.indexes.push(IndexSnapshot {
+            name: index.index_name().to_owned(),
+            definition,
+        });
+        self
+    }
+    pub fn column(&self, name: &str) -> Option<&ColumnSnapshot> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+    /// The quoted table reference. The schema is left out if it is [DEFAULT_SCHEMA]
+    pub fn qualified_name(&self) -> String {
+        if self.schema == DEFAULT_SCHEMA {
+            quote_ident(&self.name).into_owned()
+        } else {
+            format!("{}.{}", quote_ident(&self.schema), quote_ident(&self.name))
+        }
+    }
+    /// The same SQL as [CreateTable](crate::ddl::CreateTable) without foreign keys and table checks
+    pub fn create_table_sql(&self) -> String {
+        let columns: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let mut definition = column.column_definition();
+                definition.primary_key =
+                    matches!(self.primary_key.as_slice(), [key] if *key == column.name);
+                definition
+            })
+            .collect();
+        // Listed in the order of the primary key instead of the columns
+        let mut constraints = Vec::new();
+        if self.primary_key.len() > 1 {
+            constraints.push(format!(
+                "PRIMARY KEY ({})",
+                self.primary_key
+                    .iter()
+                    .map(|column| quote_ident(column))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        create_table_sql(&self.qualified_name(), false, &columns, constraints)
+    }
+}
+impl ColumnSnapshot {
+    /// `{name} {type} [NOT NULL] [UNIQUE] [DEFAULT {default}] [CHECK ({check})]`
+    pub fn definition(&self) -> String {
+        self.column_definition().to_sql(true)
+    }
+    fn column_definition(&self) -> ColumnDefinition<'_> {
+        ColumnDefinition {
+            name: quote_ident(&self.name),
+            data_type: Cow::Borrowed(&self.data_type),
+            nullable: self.nullable,
+            primary_key: false,
+            unique: self.unique,
+            default: self.default.as_deref(),
+            check: self.check.as_deref(),
+        }
+    }
+}
+/// Normalizes a SQL type so aliases compare as equal.
+///
+/// `INTEGER`, `INT` and `SERIAL` all become `INT4`. `CHARACTER VARYING(32)` becomes `VARCHAR(32)`.
+/// Also accepts the output of `format_type`. i.e. `timestamp(3) with time zone` becomes `TIMESTAMPTZ(3)`
+pub fn normalize_sql_type(sql_type: &str) -> String {
+    let sql_type = sql_type.trim().to_uppercase();
+    let (sql_type, array) = match sql_type.strip_suffix("[]") {
+        Some(sql_type) => (sql_type.trim_end(), "[]"),
+        None => (sql_type.as_str(), ""),
+    };
+    // The modifier can be in the middle of the name. `TIMESTAMP(3) WITH TIME ZONE`
+    let (base, modifier) = match (sql_type.find('('), sql_type.find(')')) {
+        (Some(start), Some(end)) if start < end => {
+            let base = format!(
+                "{} {}",
+                sql_type[..start].trim_end(),
+                sql_type[end + 1..].trim_start()
+            );
+            let modifier: String = sql_type[start..=end]
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            (Cow::Owned(base.trim_end().to_owned()), modifier)
+        }
+        _ => (Cow::Borrowed(sql_type), String::new()),
+    };
+    let base = match base.as_ref() {
+        "SMALLINT" | "SMALLSERIAL" | "SERIAL2" => "INT2",
+        "INTEGER" | "INT" | "SERIAL" | "SERIAL4" => "INT4",
+        "BIGINT" | "BIGSERIAL" | "SERIAL8" => "INT8",
+        "REAL" => "FLOAT4",
+        "DOUBLE PRECISION" => "FLOAT8",
+        "BOOLEAN" => "BOOL",
+        "DECIMAL" => "NUMERIC",
+        "CHARACTER VARYING" => "VARCHAR",
+        "CHARACTER" | "CHAR" => "BPCHAR",
+        "TIMESTAMP WITHOUT TIME ZONE" => "TIMESTAMP",
+        "TIMESTAMP WITH TIME ZONE" => "TIMESTAMPTZ",
+        "TIME WITHOUT TIME ZONE" => "TIME",
+        "TIME WITH TIME ZONE" => "TIMETZ",
+        other => other,
+    };
+    format!("{base}{modifier}{array}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{AuthUser, Nicknames, TestTable, TestTableColumn, UserRole};
+
+    #[test]
+    fn snapshot_from_table() {
+        let table = TableSnapshot::from_table::<AuthUser>().unwrap();
+        assert_eq!(table.schema, "auth");
+        assert_eq!(table.primary_key, vec!["id".to_owned()]);
+        assert_eq!(
+            table.create_table_sql(),
+            "CREATE TABLE auth.users (id SERIAL PRIMARY KEY, username VARCHAR(32) NOT NULL UNIQUE CHECK (username <> ''), test_table_id INT4, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());"
+        );
+        assert_eq!(
+            table.create_table_sql(),
+            CreateTable::<AuthUser>::new().format_sql_query()
+        );
+
+        let mut table = TableSnapshot::from_table::<UserRole>().unwrap();
+        assert_eq!(
+            table.create_table_sql(),
+            CreateTable::<UserRole>::new().format_sql_query()
+        );
+        table.primary_key.reverse();
+        assert_eq!(
+            table.create_table_sql(),
+            "CREATE TABLE user_roles (user_id INT4 NOT NULL, role TEXT NOT NULL, granted_by INT4, PRIMARY KEY (role, user_id));"
+        );
+
+        let mut table = TableSnapshot::from_table::<TestTable>().unwrap();
+        table.add_index(
+            CreateIndex::for_table::<TestTable>("test_table_email_idx")
+                .column(TestTableColumn::Email),
+        );
+        assert_eq!(table.qualified_name(), "test_table");
+        assert_eq!(
+            table.indexes[0].definition,
+            "CREATE INDEX test_table_email_idx ON test_table (email)"
+        );
+    }
+    #[test]
+    fn snapshot_requires_column_info() {
+        assert!(matches!(
+            TableSnapshot::from_table::<Nicknames>(),
+            Err(Error::MissingColumnInfo { column: "nickname" })
+        ));
+        assert!(SchemaSnapshot::default().add_table::<Nicknames>().is_err());
+    }
+    #[test]
+    fn normalize_types() {
+        assert_eq!(normalize_sql_type("SERIAL"), "INT4");
+        assert_eq!(normalize_sql_type("integer"), "INT4");
+        assert_eq!(normalize_sql_type("character varying(32)"), "VARCHAR(32)");
+        assert_eq!(normalize_sql_type("int4[]"), "INT4[]");
+        // Output of format_type
+        assert_eq!(normalize_sql_type("integer[]"), "INT4[]");
+        assert_eq!(normalize_sql_type("numeric(10,2)"), "NUMERIC(10,2)");
+        assert_eq!(normalize_sql_type("DECIMAL(10, 2)"), "NUMERIC(10,2)");
+        assert_eq!(
+            normalize_sql_type("timestamp(3) with time zone"),
+            "TIMESTAMPTZ(3)"
+        );
+        assert_eq!(normalize_sql_type("time(0) without time zone"), "TIME(0)");
+        assert_eq!(normalize_sql_type("bit(8)"), "BIT(8)");
+        assert_eq!(
+            normalize_sql_type("character varying(32)[]"),
+            "VARCHAR(32)[]"
+        );
+    }
+    /// Run with `DATABASE_URL=postgres://... cargo test --features cli -- --ignored`
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn load_from_database() {
+        use sqlx::{Connection, Executor, PgConnection};
+
+        use crate::testing::AuthUserColumn;
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        // DDL is transactional. Nothing is left behind
+        let mut tx = Connection::begin(&mut conn).await.unwrap();
+        let mut index = CreateIndex::for_table::<AuthUser>("users_created_at_idx");
+        index.column(AuthUserColumn::CreatedAt);
+        for sql in [
+            "CREATE SCHEMA IF NOT EXISTS auth",
+            CreateTable::<AuthUser>::new().format_sql_query(),
+            index.format_sql_query(),
+        ] {
+            tx.execute(sql).await.unwrap();
+        }
+
+        let loaded = SchemaSnapshot::load(&mut *tx, &["auth"]).await.unwrap();
+        let table = loaded.table("auth", "users").unwrap();
+        assert_eq!(table.primary_key, vec!["id".to_owned()]);
+        let username = table.column("username").unwrap();
+        assert_eq!(username.data_type, "VARCHAR(32)");
+        assert!(!username.nullable);
+        assert!(table.column("test_table_id").unwrap().nullable);
+        assert_eq!(
+            table.column("created_at").unwrap().default.as_deref(),
+            Some("now()")
+        );
+        assert!(
+            table
+                .indexes
+                .iter()
+                .any(|index| index.name == "users_created_at_idx")
+        );
+
+        let mut desired = SchemaSnapshot::default();
+        desired
+            .add_table::<AuthUser>()
+            .unwrap()
+            .add_index(&mut index);
+        assert!(crate::schema::SchemaDiff::between(&loaded, &desired).is_empty());
+
+        // Type modifiers are loaded so they do not show up as type changes
+        tx.execute(
+            "ALTER TABLE auth.users ADD COLUMN balance NUMERIC(10, 2), \
+            ADD COLUMN seen_at TIMESTAMPTZ(3), ADD COLUMN flags BIT(8)",
+        )
+        .await
+        .unwrap();
+        let loaded = SchemaSnapshot::load(&mut *tx, &["auth"]).await.unwrap();
+        let table = loaded.table("auth", "users").unwrap();
+        assert_eq!(table.column("balance").unwrap().data_type, "NUMERIC(10,2)");
+        assert_eq!(table.column("seen_at").unwrap().data_type, "TIMESTAMPTZ(3)");
+        assert_eq!(table.column("flags").unwrap().data_type, "BIT(8)");
+        assert_eq!(table.column("created_at").unwrap().data_type, "TIMESTAMPTZ");
+
+        tx.rollback().await.unwrap();
+    }
+    #[cfg(feature = "schema-json")]
+    #[test]
+    fn json_round_trip() {
+        let mut snapshot = SchemaSnapshot::default();
+        snapshot.add_table::<AuthUser>().unwrap();
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(SchemaSnapshot::from_json(&json).unwrap(), snapshot);
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    sync::{LazyLock, Mutex},
+};
+
+use sqlx::{
+    Executor, Postgres, Row,
+    postgres::{PgTypeInfo, types::Oid},
+};
+
+use crate::{error::Error, prelude::*};
+
+/// OIDs below this are builtin types. `FirstNormalObjectId` in the Postgres source
+const FIRST_NORMAL_OID: u32 = 16384;
+
+/// A difference between a [TableType] and the table in the database
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaMismatch {
+    MissingTable,
+    MissingColumn {
+        column: &'static str,
+    },
+    /// The database type can not be decoded into the Rust type
+    TypeMismatch {
+        column: &'static str,
+        rust_type: &'static str,
+        database_type: String,
+    },
+    /// The column is nullable in the database but the Rust type is not an `Option`
+    NullabilityMismatch {
+        column: &'static str,
+    },
+    PrimaryKeyMismatch {
+        expected: Vec<&'static str>,
+        actual: Vec<String>,
+    },
+}
+impl Display for SchemaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTable => write!(f, "table does not exist"),
+            Self::MissingColumn { column } => write!(f, "column {column} does not exist"),
+            Self::TypeMismatch {
+                column,
+                rust_type,
+                database_type,
+            } => write!(
+                f,
+                "column {column} has type {database_type} which is not compatible with {rust_type}"
+            ),
+            Self::NullabilityMismatch { column } => write!(
+                f,
+                "column {column} is nullable but the field is not an Option"
+            ),
+            Self::PrimaryKeyMismatch { expected, actual } => write!(
+                f,
+                "primary key is ({}) expected ({})",
+                actual.join(", "),
+                expected.join(", ")
+            ),
+        }
+    }
+}
+/// The result of [verify_schema]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaReport {
+    pub table: &'static str,
+    pub mismatches: Vec<SchemaMismatch>,
+}
+impl SchemaReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+    /// Turns the report into an error if there are any mismatches
+    pub fn into_result(self) -> Result<(), SchemaReport> {
+        if self.is_ok() { Ok(()) } else { Err(self) }
+    }
+}
+impl Display for SchemaReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.table)?;
+        if self.is_ok() {
+            return write!(f, " matches the database");
+        }
+        for mismatch in &self.mismatches {
+            write!(f, "\n  - {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for SchemaReport {}
+
+/// A column as read from `pg_attribute`
+#[derive(Debug, Clone)]
+struct DatabaseColumn {
+    name: String,
+    pg_type: PgTypeInfo,
+    type_name: String,
+    nullable: bool,
+    primary_key: bool,
+}
+/// Checks that the table of `T` matches the database.
+///
+/// - The table exists
+/// - Every column from [AllColumns::all] exists
+/// - The column type is compatible with the Rust type. Using [sqlx::Type::compatible]
+/// - Columns that are not an `Option` are `NOT NULL`
+/// - The primary key matches the `#[column(primary_key)]` columns ([HasPrimaryKey]).
+///   Skipped if the table does not declare a primary key
+///
+/// ```no_run
+/// # use pg_extended_sqlx_queries::{prelude::*, schema::verify_schema};
+/// # #[derive(TableType)]
+/// # #[table(name = "users")]
+/// # pub struct User {
+/// #     #[column(primary_key)]
+/// #     pub id: i32,
+/// # }
+/// # async fn startup(pool: sqlx::PgPool) -> Result<(), Box<dyn std::error::Error>> {
+/// verify_schema::<User, _>(&pool).await?.into_result()?;
+/// # Ok(())
+/// # }
+/// ```
+/// Returns an error if a column does not provide [ColumnType::column_info]
+pub async fn verify_schema<'c, T, E>(executor: E) -> Result<SchemaReport, sqlx::Error>
+where
+    T: TableType,
+    T::Columns: AllColumns,
+    E: Executor<'c, Database = Postgres>,
+{
+    let rows = sqlx::query(
+        "SELECT a.attname::TEXT AS column_name, a.atttypid AS type_oid, t.typname::TEXT AS type_oid_name, \
+        format_type(a.atttypid, a.atttypmod) AS type_name, NOT a.attnotnull AS nullable, \
+        COALESCE(a.attnum = ANY(i.indkey), FALSE) AS primary_key \
+        FROM pg_attribute a \
+        JOIN pg_type t ON t.oid = a.atttypid \
+        LEFT JOIN pg_index i ON i.indrelid = a.attrelid AND i.indisprimary \
+        WHERE a.attrelid = to_regclass($1) AND a.attnum > 0 AND NOT a.attisdropped \
+        ORDER BY a.attnum",
+    )
+    .bind(T::qualified_table_name())
+    .fetch_all(executor)
+    .await?;
+    let columns = rows
+        .into_iter()
+        .map(|row| {
+            Ok(DatabaseColumn {
+                name: row.try_get("column_name")?,
+                pg_type: database_type_info(
+                    row.try_get("type_oid")?,
+                    row.try_get("type_oid_name")?,
+                ),
+                type_name: row.try_get("type_name")?,
+                nullable: row.try_get("nullable")?,
+                primary_key: row.try_get("primary_key")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    Ok(compare_columns::<T>(&columns)?)
+}
+/// Builtin types are compared by OID. The OIDs of user defined types, such as enums,
+/// differ between databases so they are compared by name like `#[derive(sqlx::Type)]` declares them
+fn database_type_info(oid: Oid, name: String) -> PgTypeInfo {
+    if oid.0 < FIRST_NORMAL_OID {
+        return PgTypeInfo::with_oid(oid);
+    }
+    /// [PgTypeInfo::with_name] requires a static name. Each name is only leaked once
+    static NAMES: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Default::default);
+    let mut names = NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    let name = match names.get(name.as_str()) {
+        Some(name) => *name,
+        None => {
+            let name: &'static str = Box::leak(name.into_boxed_str());
+            names.insert(name);
+            name
+        }
+    };
+    PgTypeInfo::with_name(name)
+}
+fn compare_columns<T>(columns: &[DatabaseColumn]) -> Result<SchemaReport, Error>
+where
+    T: TableType,
+    T::Columns: AllColumns,
+{
+    let mut report = SchemaReport {
+        table: T::qualified_table_name(),
+        mismatches: Vec::new(),
+    };
+    if columns.is_empty() {
+        report.mismatches.push(SchemaMismatch::MissingTable);
+        return Ok(report);
+    }
+    let mut expected_primary_key = Vec::new();
+    for column in T::Columns::all() {
+        let info = column.column_info().ok_or(Error::MissingColumnInfo {
+            column: column.column_name(),
+        })?;
+        if info.primary_key {
+            expected_primary_key.push(info.name);
+        }
+        let Some(database_column) = columns.iter().find(|c| c.name == info.name) else {
+            report
+                .mismatches
+                .push(SchemaMismatch::MissingColumn { column: info.name });
+            continue;
+        };
+        if !info.is_compatible(&database_column.pg_type) {
+            report.mismatches.push(SchemaMismatch::TypeMismatch {
+                column: info.name,
+                rust_type: info.rust_type,
+                database_type: database_column.type_name.clone(),
+            });
+        }
+        if database_column.nullable && !info.nullable {
+            report
+                .mismatches
+                .push(SchemaMismatch::NullabilityMismatch { column: info.name });
+        }
+    }
+    if !expected_primary_key.is_empty() {
+        let actual: Vec<String> = columns
+            .iter()
+            .filter(|column| column.primary_key)
+            .map(|column| column.name.clone())
+            .collect();
+        let mut sorted_actual: Vec<&str> = actual.iter().map(String::as_str).collect();
+        sorted_actual.sort_unstable();
+        let mut sorted_expected = expected_primary_key.clone();
+        sorted_expected.sort_unstable();
+        if sorted_actual != sorted_expected {
+            report.mismatches.push(SchemaMismatch::PrimaryKeyMismatch {
+                expected: expected_primary_key,
+                actual,
+            });
+        }
+    }
+    Ok(report)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{AuthUser, Role};
+
+    impl DatabaseColumn {
+        fn new(
+            name: &str,
+            (oid, type_name): (u32, &str),
+            nullable: bool,
+            primary_key: bool,
+        ) -> Self {
+            Self {
+                name: name.to_owned(),
+                pg_type: PgTypeInfo::with_oid(Oid(oid)),
+                type_name: type_name.to_owned(),
+                nullable,
+                primary_key,
+            }
+        }
+    }
+
+    const INT4: (u32, &str) = (23, "integer");
+    const INT8: (u32, &str) = (20, "bigint");
+    const VARCHAR: (u32, &str) = (1043, "character varying(32)");
+    const TIMESTAMPTZ: (u32, &str) = (1184, "timestamp with time zone");
+
+    #[test]
+    fn matching_table() {
+        let columns = [
+            DatabaseColumn::new("id", INT4, false, true),
+            DatabaseColumn::new("username", VARCHAR, false, false),
+            DatabaseColumn::new("test_table_id", INT4, true, false),
+            DatabaseColumn::new("created_at", TIMESTAMPTZ, false, false),
+            DatabaseColumn::new("extra", INT4, true, false),
+        ];
+        let report = compare_columns::<AuthUser>(&columns).unwrap();
+        assert!(report.is_ok(), "{report}");
+    }
+    #[test]
+    fn report_mismatches() {
+        assert_eq!(
+            compare_columns::<AuthUser>(&[]).unwrap().mismatches,
+            vec![SchemaMismatch::MissingTable]
+        );
+
+        let columns = [
+            DatabaseColumn::new("id", INT4, false, false),
+            DatabaseColumn::new("user_name", VARCHAR, false, true),
+            DatabaseColumn::new("test_table_id", INT8, true, false),
+            DatabaseColumn::new("created_at", TIMESTAMPTZ, true, false),
+        ];
+        let report = compare_columns::<AuthUser>(&columns).unwrap();
+        assert_eq!(
+            report.mismatches,
+            vec![
+                SchemaMismatch::MissingColumn { column: "username" },
+                SchemaMismatch::TypeMismatch {
+                    column: "test_table_id",
+                    rust_type: "Option<i32>",
+                    database_type: "bigint".to_owned()
+                },
+                SchemaMismatch::NullabilityMismatch {
+                    column: "created_at"
+                },
+                SchemaMismatch::PrimaryKeyMismatch {
+                    expected: vec!["id"],
+                    actual: vec!["user_name".to_owned()]
+                },
+            ]
+        );
+        assert!(report.into_result().is_err());
+    }
+    #[test]
+    fn user_defined_types() {
+        let columns = |type_name: &str| {
+            [
+                DatabaseColumn::new("id", INT4, false, true),
+                DatabaseColumn {
+                    name: "role".to_owned(),
+                    pg_type: database_type_info(Oid(16500), type_name.to_owned()),
+                    type_name: type_name.to_owned(),
+                    nullable: false,
+                    primary_key: false,
+                },
+            ]
+        };
+        let report = compare_columns::<Role>(&columns("role_kind")).unwrap();
+        assert!(report.is_ok(), "{report}");
+
+        let report = compare_columns::<Role>(&columns("account_status")).unwrap();
+        assert_eq!(
+            report.mismatches,
+            vec![SchemaMismatch::TypeMismatch {
+                column: "role",
+                rust_type: "RoleKind",
+                database_type: "account_status".to_owned()
+            }]
+        );
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     expr::{ArgumentHolder, Expr, ExprType, HasArguments, SQLCondition, SQLOrder},
     pagination::PaginationSupportingTool,
     prelude::{ColumnType, DynColumn},
+    table_layout::quote_table_reference,
     traits::*,
 };
 
@@ -42,7 +43,7 @@ impl PaginationSupportingTool for SelectQueryBuilder<'_> {
 impl<'args> SelectQueryBuilder<'args> {
     pub fn new(table: &'args str) -> Self {
         Self {
-            table: Cow::Borrowed(table),
+            table: quote_table_reference(table),
             select: Vec::new(),
             where_comparisons: vec![],
             sql: None,
@@ -168,7 +169,14 @@ impl<'args> FormatSqlQuery for SelectQueryBuilder<'args> {
         self.sql.as_ref().expect("SQL not set")
     }
 }
-impl<'args> QueryTool<'args> for SelectQueryBuilder<'args> {}
+impl<'args> QueryTool<'args> for SelectQueryBuilder<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("SELECT")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
 impl<'args> Debug for SelectQueryBuilder<'args> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SelectQueryBuilder")
@@ -205,7 +213,10 @@ mod tests {
         expr::Collate,
         pagination::PaginationSupportingTool,
         prelude::*,
-        testing::{AnotherTable, AnotherTableColumn, TestTable, TestTableColumn, print_query},
+        testing::{
+            AnotherTable, AnotherTableColumn, AuthUser, AuthUserColumn, TestTable, TestTableColumn,
+            print_query,
+        },
     };
     #[test]
     fn basic_select() {
@@ -273,6 +284,36 @@ mod tests {
         print_query(sql, "Select Join");
     }
     #[test]
+    fn select_join_with_schema() {
+        let mut select = SelectQueryBuilder::new(AuthUser::qualified_table_name());
+        select
+            .select(AuthUserColumn::Username)
+            .join(TestTable::qualified_table_name(), JoinType::Left, |join| {
+                join.select(TestTableColumn::Age)
+                    .on(AuthUserColumn::TestTableId.equals(TestTableColumn::Id))
+            })
+            .filter(AuthUserColumn::Id.equals(1));
+
+        let sql = select.format_sql_query();
+        assert_eq!(
+            sql,
+            "SELECT users.username, test_table.age FROM auth.users LEFT JOIN test_table ON users.test_table_id = test_table.id WHERE users.id = $1"
+        );
+    }
+    #[test]
+    fn select_join_quotes_table() {
+        let mut select = SelectQueryBuilder::new(TestTable::table_name());
+        select
+            .select(TestTableColumn::Id)
+            .join("billing.Orders", JoinType::Inner, |join| {
+                join.on(TestTableColumn::Id.equals(1.value()))
+            });
+        assert_eq!(
+            select.format_sql_query(),
+            "SELECT test_table.id FROM test_table INNER JOIN billing.\"Orders\" ON test_table.id = $1"
+        );
+    }
+    #[test]
     fn select_any() {
         let mut select = SelectQueryBuilder::new(TestTable::table_name());
         select.select(TestTableColumn::Id.alias("user_id"));
@@ -1,10 +1,10 @@
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
-use crate::prelude::*;
+use crate::{prelude::*, table_layout::quote_table_reference};
 
 /// Counts the number of rows in a table based on the given where comparisons.
 pub struct SelectCount<'args> {
-    table: &'static str,
+    table: Cow<'static, str>,
     where_comparisons: Vec<SQLCondition>,
     sql: Option<String>,
     arguments: ArgumentHolder<'args>,
@@ -22,7 +22,7 @@ impl FormatWhere for SelectCount<'_> {
 impl SelectCount<'_> {
     pub fn new(table: &'static str) -> Self {
         Self {
-            table,
+            table: quote_table_reference(table),
             where_comparisons: Vec::new(),
             sql: None,
             arguments: Default::default(),
@@ -44,7 +44,14 @@ impl<'args> Debug for SelectCount<'args> {
     }
 }
 
-impl<'args> QueryTool<'args> for SelectCount<'args> {}
+impl<'args> QueryTool<'args> for SelectCount<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("SELECT")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
 impl FormatSqlQuery for SelectCount<'_> {
     fn format_sql_query(&mut self) -> &str {
         let mut sql = format!("SELECT COUNT(1) FROM {}", self.table);
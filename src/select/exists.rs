@@ -1,9 +1,9 @@
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
-use crate::prelude::*;
+use crate::{prelude::*, table_layout::quote_table_reference};
 
 pub struct SelectExists<'args> {
-    table: &'static str,
+    table: Cow<'static, str>,
     where_comparisons: Vec<SQLCondition>,
     sql: Option<String>,
     arguments: ArgumentHolder<'args>,
@@ -21,7 +21,7 @@ impl FormatWhere for SelectExists<'_> {
 impl SelectExists<'_> {
     pub fn new(table: &'static str) -> Self {
         Self {
-            table,
+            table: quote_table_reference(table),
             where_comparisons: Vec::new(),
             sql: None,
             arguments: Default::default(),
@@ -42,7 +42,14 @@ impl<'args> Debug for SelectExists<'args> {
             .finish()
     }
 }
-impl<'args> QueryTool<'args> for SelectExists<'args> {}
+impl<'args> QueryTool<'args> for SelectExists<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("SELECT")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+}
 impl<'args> QueryScalarTool<'args> for SelectExists<'args> {
     type Output = bool;
 }
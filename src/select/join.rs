@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use crate::expr::{Expr, ExprType, HasArguments, SQLCondition};
 
-use crate::prelude::*;
+use crate::{prelude::*, table_layout::quote_table_reference};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JoinType {
@@ -56,7 +56,11 @@ pub struct Join {
 }
 impl FormatSql for Join {
     fn format_sql(&self) -> std::borrow::Cow<'_, str> {
-        let mut sql = format!("{} {}", self.join_type.format_sql(), self.table);
+        let mut sql = format!(
+            "{} {}",
+            self.join_type.format_sql(),
+            quote_table_reference(self.table)
+        );
         sql.push_str(" ON ");
         sql.push_str(&self.on.format_sql());
 
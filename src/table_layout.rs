@@ -1,5 +1,7 @@
 mod columns;
+mod ident;
 pub use columns::*;
+pub use ident::*;
 
 /// The primary definition of a table.
 pub trait TableType {
@@ -9,6 +11,24 @@ pub trait TableType {
     fn table_name() -> &'static str
     where
         Self: Sized;
+    /// The schema of the table if it is not in the default search path
+    fn schema_name() -> Option<&'static str>
+    where
+        Self: Sized,
+    {
+        None
+    }
+    /// The table name prefixed with the schema. `{schema}.{table}`
+    ///
+    /// This is what should be passed to the query builders
+    ///
+    /// Defaults to [TableType::table_name]
+    fn qualified_table_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        Self::table_name()
+    }
 }
 pub trait HasPrimaryKey: TableType {
     fn primary_key() -> Self::Columns
@@ -1,5 +1,8 @@
-use std::{borrow::Cow, fmt::Debug};
+use std::{borrow::Cow, fmt::Debug, sync::Arc};
 
+use sqlx::{Postgres, Type, TypeInfo, postgres::PgTypeInfo};
+
+use super::quote_ident;
 use crate::expr::{Aliasable, ArgumentHolder, CastableExprType, Expr, ExprType, WrapInFunction};
 
 #[derive(Debug)]
@@ -19,6 +22,12 @@ impl ColumnType for DynColumn {
     fn table_name(&self) -> &'static str {
         self.0.table_name()
     }
+    fn schema_name(&self) -> Option<&'static str> {
+        self.0.schema_name()
+    }
+    fn quoted_column_name(&self) -> Cow<'static, str> {
+        self.0.quoted_column_name()
+    }
     fn dyn_column(self) -> DynColumn
     where
         Self: Sized + Send + Sync + 'static,
@@ -32,13 +41,57 @@ impl ColumnType for DynColumn {
     fn full_name(&self) -> Cow<'static, str> {
         self.0.full_name()
     }
+    fn column_info(&self) -> Option<ColumnInfo> {
+        self.0.column_info()
+    }
+}
+/// A column written with a different prefix. i.e. `EXCLUDED.{column}` or `{alias}.{column}`
+///
+/// Keeps the [ColumnInfo] of the column so `#[column(sensitive)]` values are still redacted
+#[derive(Debug, Clone)]
+pub(crate) struct PrefixedColumn {
+    prefix: Cow<'static, str>,
+    column: Arc<dyn ColumnType>,
+}
+impl PrefixedColumn {
+    pub(crate) fn new<C>(prefix: impl Into<Cow<'static, str>>, column: C) -> Self
+    where
+        C: ColumnType + 'static,
+    {
+        Self {
+            prefix: prefix.into(),
+            column: Arc::new(column),
+        }
+    }
+}
+impl ColumnType for PrefixedColumn {
+    fn column_name(&self) -> &'static str {
+        self.column.column_name()
+    }
+    fn table_name(&self) -> &'static str {
+        self.column.table_name()
+    }
+    fn schema_name(&self) -> Option<&'static str> {
+        self.column.schema_name()
+    }
+    fn quoted_column_name(&self) -> Cow<'static, str> {
+        self.column.quoted_column_name()
+    }
+    fn full_name(&self) -> Cow<'static, str> {
+        self.format_column_with_prefix(Some(&self.prefix))
+    }
+    fn column_info(&self) -> Option<ColumnInfo> {
+        self.column.column_info()
+    }
 }
 impl<C> PartialEq<C> for DynColumn
 where
     C: ColumnType,
 {
     fn eq(&self, other: &C) -> bool {
-        self.column_name() == other.column_name() && self.table_name() == other.table_name()
+        self.column_name() == other.column_name()
+            && self.table_name() == other.table_name()
+            && self.schema_name() == other.schema_name()
     }
 }
 impl<'args> ExprType<'args> for DynColumn {
@@ -56,19 +109,44 @@ pub trait ColumnType: Debug + Send + Sync {
     fn column_name(&self) -> &'static str;
     /// Returns the table name of the column
     fn table_name(&self) -> &'static str;
+    /// Returns the schema of the table if it has one
+    fn schema_name(&self) -> Option<&'static str> {
+        None
+    }
+    /// The column name as it should appear in SQL.
+    ///
+    /// Quoted if the name is a reserved keyword or is not all lower case. See [quote_ident]
+    fn quoted_column_name(&self) -> Cow<'static, str> {
+        quote_ident(self.column_name())
+    }
     /// Should return the `{table_name}.{column_name}` format
+    ///
+    /// The schema is left out so the column works with both `FROM {table}` and `FROM {schema}.{table}`
+    ///
+    /// Each part is quoted if required
     fn full_name(&self) -> Cow<'static, str> {
-        Cow::Owned(format!("{}.{}", self.table_name(), self.column_name()))
+        Cow::Owned(format!(
+            "{}.{}",
+            quote_ident(self.table_name()),
+            self.quoted_column_name()
+        ))
     }
     /// Should return the `{prefix}.{column_name}` format
     fn format_column_with_prefix(&self, prefix: Option<&str>) -> Cow<'static, str> {
         if let Some(prefix) = prefix {
-            Cow::Owned(format!("{}.{}", prefix, self.column_name()))
+            Cow::Owned(format!("{}.{}", prefix, self.quoted_column_name()))
         } else {
-            Cow::Borrowed(self.column_name())
+            self.quoted_column_name()
         }
     }
 
+    /// Information about the column. Such as the Rust type and the Postgres type
+    ///
+    /// The [TableType](crate::table_layout::TableType) derive will always return Some
+    fn column_info(&self) -> Option<ColumnInfo> {
+        None
+    }
+
     fn dyn_column(self) -> DynColumn
     where
         Self: Sized + Send + Sync + 'static,
@@ -76,6 +154,126 @@ pub trait ColumnType: Debug + Send + Sync {
         DynColumn::new(self)
     }
 }
+/// Runtime information about a column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnInfo {
+    /// The name of the column
+    pub name: &'static str,
+    /// The Rust type of the field. i.e. `Option<String>`
+    pub rust_type: &'static str,
+    /// If the field is an `Option<T>`
+    pub nullable: bool,
+    pub primary_key: bool,
+    /// The Postgres type from [sqlx::Type::type_info]
+    pub pg_type: PgTypeInfo,
+    /// Overrides the type used in DDL. i.e. `VARCHAR(255)`
+    pub sql_type: Option<&'static str>,
+    /// The default value expression. i.e. `NOW()`
+    pub default: Option<&'static str>,
+    pub unique: bool,
+    /// A check constraint expression. i.e. `age >= 0`
+    pub check: Option<&'static str>,
+    /// Identity or computed column. `#[column(generated)]`
+    pub generated: bool,
+    /// `#[column(read_only)]`
+    pub read_only: bool,
+    /// `#[column(insert_only)]`
+    pub insert_only: bool,
+    /// Values are redacted in `QueryTool::debug_sql`. `#[column(sensitive)]`
+    pub sensitive: bool,
+    compatible: CompatibleFn,
+}
+/// [sqlx::Type::compatible] of the Rust type.
+///
+/// Ignored by the Debug and PartialEq of [ColumnInfo]. [ColumnInfo::pg_type] is compared instead
+#[derive(Clone, Copy)]
+struct CompatibleFn(fn(&PgTypeInfo) -> bool);
+impl std::fmt::Debug for CompatibleFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CompatibleFn")
+    }
+}
+impl PartialEq for CompatibleFn {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+impl ColumnInfo {
+    pub fn new<T>(
+        name: &'static str,
+        rust_type: &'static str,
+        nullable: bool,
+        primary_key: bool,
+    ) -> Self
+    where
+        T: Type<Postgres> + ?Sized,
+    {
+        Self {
+            name,
+            rust_type,
+            nullable,
+            primary_key,
+            pg_type: T::type_info(),
+            sql_type: None,
+            default: None,
+            unique: false,
+            check: None,
+            generated: false,
+            read_only: false,
+            insert_only: false,
+            sensitive: false,
+            compatible: CompatibleFn(T::compatible),
+        }
+    }
+    pub fn with_sql_type(mut self, sql_type: &'static str) -> Self {
+        self.sql_type = Some(sql_type);
+        self
+    }
+    pub fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+    pub fn with_unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+    pub fn with_check(mut self, check: &'static str) -> Self {
+        self.check = Some(check);
+        self
+    }
+    pub fn with_generated(mut self) -> Self {
+        self.generated = true;
+        self
+    }
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+    pub fn with_insert_only(mut self) -> Self {
+        self.insert_only = true;
+        self
+    }
+    pub fn with_sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+    /// Generated and read only columns can not be inserted
+    pub fn is_insertable(&self) -> bool {
+        !self.generated && !self.read_only
+    }
+    /// Insert only columns can not be updated
+    pub fn is_updatable(&self) -> bool {
+        self.is_insertable() && !self.insert_only
+    }
+    /// If a value of the Postgres type can be decoded into the Rust type
+    pub fn is_compatible(&self, pg_type: &PgTypeInfo) -> bool {
+        (self.compatible.0)(pg_type)
+    }
+    /// The type used in DDL. The [Self::sql_type] override or the name of [Self::pg_type]
+    pub fn type_name(&self) -> &str {
+        self.sql_type.unwrap_or_else(|| self.pg_type.name())
+    }
+}
 
 pub trait AllColumns {
     fn all() -> Vec<Self>
@@ -88,6 +286,24 @@ pub trait AllColumns {
     {
         Self::all().into_iter().map(|c| c.dyn_column()).collect()
     }
+    /// Columns that can be written by an `INSERT`. Excludes generated and read only columns
+    ///
+    /// Defaults to [AllColumns::all]
+    fn insertable_columns() -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        Self::all()
+    }
+    /// Columns that can be written by an `UPDATE`. Also excludes insert only columns
+    ///
+    /// Defaults to [AllColumns::all]
+    fn updatable_columns() -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        Self::all()
+    }
 }
 impl<'args, C> Aliasable<'args> for C where C: ColumnType + ExprType<'args> + 'static {}
 impl<'args, C> WrapInFunction<'args> for C where C: ColumnType + ExprType<'args> + 'static {}
@@ -120,7 +336,7 @@ where
 {
     columns
         .into_iter()
-        .map(|column| column.column_name())
+        .map(|column| column.quoted_column_name())
         .collect::<Vec<_>>()
         .join(", ")
 }
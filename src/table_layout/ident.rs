@@ -0,0 +1,256 @@
+//! Quoting of identifiers (table and column names)
+//!
+//! Identifiers are only quoted when required. i.e. `user`, `order` or `createdAt`
+//!
+//! Reference: https://www.postgresql.org/docs/current/sql-syntax-lexical.html#SQL-SYNTAX-IDENTIFIERS
+use std::borrow::Cow;
+
+/// Keywords that are reserved in Postgres and can not be used as an unquoted column or table name
+///
+/// Reference: https://www.postgresql.org/docs/current/sql-keywords-appendix.html
+///
+/// The macros crate has a copy of this list. `keywords_match_macros` checks that they are equal
+pub const RESERVED_KEYWORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+/// Returns true if the identifier has to be quoted to be used as is.
+///
+/// Identifiers need quoting if they are a reserved keyword, contain upper case characters or
+/// contain anything other than `a-z`, `0-9`, `_` and `$`
+pub fn needs_quoting(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    let Some(first) = chars.next() else {
+        return true;
+    };
+    if !(first.is_ascii_lowercase() || first == '_') {
+        return true;
+    }
+    if !chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$') {
+        return true;
+    }
+    RESERVED_KEYWORDS.binary_search(&ident).is_ok()
+}
+/// Wraps the identifier in double quotes. Any double quotes inside the identifier are escaped
+pub fn force_quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+/// Quotes the identifier if [needs_quoting] returns true
+pub fn quote_ident(ident: &str) -> Cow<'_, str> {
+    if needs_quoting(ident) {
+        Cow::Owned(force_quote_ident(ident))
+    } else {
+        Cow::Borrowed(ident)
+    }
+}
+/// Makes a table reference passed as a `&str` safe to put into a query.
+///
+/// The reference is split on `.` into `{schema}.{table}`.
+/// Each part that is already a valid quoted identifier is kept, every other part is passed through [quote_ident]
+///
+/// This means a table name can never inject SQL into the query.
+pub fn quote_table_reference(table: &str) -> Cow<'_, str> {
+    let parts = split_table_reference(table);
+    if parts
+        .iter()
+        .all(|part| is_quoted_ident(part) || !needs_quoting(part))
+    {
+        return Cow::Borrowed(table);
+    }
+    let quoted: Vec<_> = parts
+        .into_iter()
+        .map(|part| {
+            if is_quoted_ident(part) {
+                Cow::Borrowed(part)
+            } else {
+                quote_ident(part)
+            }
+        })
+        .collect();
+    Cow::Owned(quoted.join("."))
+}
+/// Returns true if the table reference can be used without any changes.
+///
+/// i.e. `users`, `auth.users` or `auth."Users"`
+pub fn is_valid_table_reference(table: &str) -> bool {
+    matches!(quote_table_reference(table), Cow::Borrowed(_))
+}
+/// Splits on `.` that are not inside a quoted identifier
+fn split_table_reference(table: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (index, c) in table.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '.' if !in_quotes => {
+                parts.push(&table[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&table[start..]);
+    parts
+}
+/// `"..."` where any inner quotes are doubled
+fn is_quoted_ident(part: &str) -> bool {
+    let Some(inner) = part
+        .strip_prefix('"')
+        .and_then(|part| part.strip_suffix('"'))
+    else {
+        return false;
+    };
+    !inner.is_empty() && !inner.replace("\"\"", "").contains('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_are_sorted() {
+        let mut sorted = RESERVED_KEYWORDS.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, RESERVED_KEYWORDS);
+    }
+    #[test]
+    fn keywords_match_macros() {
+        let source = include_str!("../../macros/src/utils.rs");
+        let (_, list) = source
+            .split_once("const RESERVED_KEYWORDS: &[&str] = &[")
+            .expect("RESERVED_KEYWORDS not found in the macros crate");
+        let (list, _) = list.split_once("];").expect("Unterminated list");
+        let macros: Vec<_> = list
+            .split(',')
+            .map(|keyword| keyword.trim().trim_matches('"'))
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        assert_eq!(macros, RESERVED_KEYWORDS);
+    }
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("first_name"), "first_name");
+        assert_eq!(quote_ident("user"), "\"user\"");
+        assert_eq!(quote_ident("order"), "\"order\"");
+        assert_eq!(quote_ident("createdAt"), "\"createdAt\"");
+        assert_eq!(quote_ident("1st"), "\"1st\"");
+        assert_eq!(quote_ident("bad\"name"), "\"bad\"\"name\"");
+    }
+    #[test]
+    fn test_quote_table_reference() {
+        assert_eq!(quote_table_reference("users"), "users");
+        assert_eq!(quote_table_reference("auth.users"), "auth.users");
+        assert_eq!(quote_table_reference("auth.\"Users\""), "auth.\"Users\"");
+        assert_eq!(quote_table_reference("public.user"), "public.\"user\"");
+        assert_eq!(
+            quote_table_reference("users; DROP TABLE users"),
+            "\"users; DROP TABLE users\""
+        );
+        assert_eq!(
+            quote_table_reference("users\" ; DROP TABLE users; --"),
+            "\"users\"\" ; DROP TABLE users; --\""
+        );
+        assert!(is_valid_table_reference("auth.users"));
+        assert!(!is_valid_table_reference("order"));
+    }
+}
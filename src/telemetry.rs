@@ -0,0 +1,274 @@
+//! Spans and metrics recorded by the `_traced` methods of [QueryTool](crate::traits::QueryTool)
+//!
+//! The span is named `db.query` and uses the OpenTelemetry field names
+//! `db.system`, `db.operation`, `db.sql.table` and `db.statement`.
+//!
+//! Register a [QueryMetrics] with [set_query_metrics] to export histograms
+use std::{
+    future::{Future, ready},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use tracing::{Instrument, field::Empty, info_span};
+
+use crate::error::Error;
+
+static QUERY_METRICS: OnceLock<Box<dyn QueryMetrics>> = OnceLock::new();
+
+/// Called after every traced query
+pub trait QueryMetrics: Send + Sync + 'static {
+    fn record(&self, event: &QueryEvent<'_>);
+}
+impl<F> QueryMetrics for F
+where
+    F: Fn(&QueryEvent<'_>) + Send + Sync + 'static,
+{
+    fn record(&self, event: &QueryEvent<'_>) {
+        self(event)
+    }
+}
+/// Sets the global [QueryMetrics]. Returns false if one was already set
+pub fn set_query_metrics(metrics: impl QueryMetrics) -> bool {
+    QUERY_METRICS.set(Box::new(metrics)).is_ok()
+}
+/// A finished query
+#[derive(Debug)]
+pub struct QueryEvent<'a> {
+    /// The SQL with placeholders. Queries with the same shape have the same SQL
+    pub sql: &'a str,
+    /// Hash of [Self::sql]. The same for every process of a build
+    pub fingerprint: u64,
+    /// i.e. `SELECT`
+    pub operation: Option<&'static str>,
+    pub table: Option<&'a str>,
+    pub duration: Duration,
+    /// Rows returned or affected. None if the query failed
+    pub rows: Option<u64>,
+    pub error: Option<&'a sqlx::Error>,
+    /// The `error.type` recorded in the span.
+    ///
+    /// [Error::kind] if the query failed before it was executed. Otherwise [error_kind]
+    pub error_type: Option<&'static str>,
+}
+/// Hash of the SQL with fixed seeds. Used as [QueryEvent::fingerprint]
+pub fn fingerprint(sql: &str) -> u64 {
+    ahash::RandomState::with_seeds(0, 0, 0, 0).hash_one(sql)
+}
+/// Short name of the error for the `error.type` field
+pub fn error_kind(error: &sqlx::Error) -> &'static str {
+    match error {
+        sqlx::Error::Database(_) => "database",
+        sqlx::Error::RowNotFound => "row_not_found",
+        sqlx::Error::PoolTimedOut => "pool_timed_out",
+        sqlx::Error::PoolClosed => "pool_closed",
+        sqlx::Error::Io(_) => "io",
+        sqlx::Error::Tls(_) => "tls",
+        sqlx::Error::Protocol(_) => "protocol",
+        sqlx::Error::Encode(_) => "encode",
+        sqlx::Error::InvalidArgument(_) => "invalid_argument",
+        sqlx::Error::Decode(_) | sqlx::Error::ColumnDecode { .. } => "decode",
+        sqlx::Error::ColumnNotFound(_) | sqlx::Error::ColumnIndexOutOfBounds { .. } => "column",
+        sqlx::Error::TypeNotFound { .. } => "type_not_found",
+        _ => "other",
+    }
+}
+/// Runs the query inside of a `db.query` span and records it
+pub(crate) async fn trace_query<F, T>(
+    operation: Option<&'static str>,
+    table: Option<&str>,
+    sql: &str,
+    future: F,
+    rows: fn(&T) -> u64,
+) -> Result<T, sqlx::Error>
+where
+    F: Future<Output = Result<T, sqlx::Error>>,
+{
+    record_query(operation, table, sql, future, rows, None).await
+}
+/// `build_error` is the kind of the [Error] that stopped the query from being executed
+async fn record_query<F, T>(
+    operation: Option<&'static str>,
+    table: Option<&str>,
+    sql: &str,
+    future: F,
+    rows: fn(&T) -> u64,
+    build_error: Option<&'static str>,
+) -> Result<T, sqlx::Error>
+where
+    F: Future<Output = Result<T, sqlx::Error>>,
+{
+    let span = info_span!(
+        "db.query",
+        otel.kind = "client",
+        db.system = "postgresql",
+        db.operation = operation,
+        db.sql.table = table,
+        db.statement = sql,
+        db.rows = Empty,
+        db.duration_ms = Empty,
+        error.type = Empty,
+    );
+    let start = Instant::now();
+    let result = future.instrument(span.clone()).await;
+    let duration = start.elapsed();
+
+    span.record("db.duration_ms", duration.as_secs_f64() * 1000.0);
+    let row_count = result.as_ref().ok().map(rows);
+    let error_type = result
+        .as_ref()
+        .err()
+        .map(|err| build_error.unwrap_or_else(|| error_kind(err)));
+    match error_type {
+        None => {
+            span.record("db.rows", row_count);
+        }
+        Some(error_type) => {
+            span.record("error.type", error_type);
+        }
+    }
+    if let Some(metrics) = QUERY_METRICS.get() {
+        metrics.record(&QueryEvent {
+            sql,
+            fingerprint: fingerprint(sql),
+            operation,
+            table,
+            duration,
+            rows: row_count,
+            error: result.as_ref().err(),
+            error_type,
+        });
+    }
+    result
+}
+/// Records a query that failed before it was executed. i.e. a value failed to encode.
+///
+/// `error.type` is the [Error::kind]
+pub(crate) async fn trace_error<T>(
+    operation: Option<&'static str>,
+    table: Option<&str>,
+    sql: &str,
+    error: Error,
+) -> Result<T, sqlx::Error> {
+    let kind = error.kind();
+    record_query(
+        operation,
+        table,
+        sql,
+        ready(Err(error.into())),
+        |_| 0,
+        Some(kind),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::pin,
+        sync::Mutex,
+        task::{Context, Poll, Waker},
+    };
+
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    #[allow(dead_code)]
+    fn traced_futures_are_send(pool: &PgPool) {
+        fn is_send<T: Send>(_: T) {}
+        let mut query =
+            SelectQueryBuilder::with_columns(TestTable::table_name(), [TestTableColumn::Id]);
+        is_send(query.fetch_all_traced::<(i32,), _>(pool));
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        is_send(query.execute_traced(pool));
+    }
+    #[test]
+    fn statement_info() {
+        let query = UpdateQueryBuilder::new(TestTable::table_name());
+        assert_eq!(query.statement_type(), Some("UPDATE"));
+        assert_eq!(query.statement_table(), Some("test_table"));
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        assert_eq!(
+            fingerprint("SELECT * FROM users WHERE id = $1"),
+            fingerprint("SELECT * FROM users WHERE id = $1")
+        );
+        assert_ne!(
+            fingerprint("SELECT * FROM users WHERE id = $1"),
+            fingerprint("SELECT * FROM users WHERE id = $2")
+        );
+    }
+    #[test]
+    fn error_kinds() {
+        assert_eq!(error_kind(&sqlx::Error::RowNotFound), "row_not_found");
+        assert_eq!(error_kind(&sqlx::Error::PoolTimedOut), "pool_timed_out");
+    }
+    /// Polls a future that does not wait on anything
+    fn poll_ready<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("Future is not ready"),
+        }
+    }
+    /// `(sql, operation, rows, error kind)` of the recorded events
+    type RecordedEvent = (
+        String,
+        Option<&'static str>,
+        Option<u64>,
+        Option<&'static str>,
+    );
+    static EVENTS: Mutex<Vec<RecordedEvent>> = Mutex::new(Vec::new());
+
+    #[test]
+    fn records_metrics() {
+        assert!(set_query_metrics(|event: &QueryEvent<'_>| {
+            EVENTS.lock().unwrap().push((
+                event.sql.to_owned(),
+                event.operation,
+                event.rows,
+                event.error_type,
+            ));
+        }));
+        let result = poll_ready(trace_query(
+            Some("SELECT"),
+            Some("test_table"),
+            "SELECT 1 FROM test_table",
+            ready(Ok(vec![1, 2, 3])),
+            |rows| rows.len() as u64,
+        ));
+        assert_eq!(result.unwrap(), vec![1, 2, 3]);
+
+        let mut query = DeleteQueryBuilder::new(TestTable::table_name());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        let result = poll_ready(trace_error::<()>(
+            query.statement_type(),
+            query.statement_table(),
+            "DELETE FROM test_table",
+            err,
+        ));
+        assert!(matches!(result, Err(sqlx::Error::InvalidArgument(_))));
+
+        let events = EVENTS.lock().unwrap();
+        assert!(events.contains(&(
+            "SELECT 1 FROM test_table".to_owned(),
+            Some("SELECT"),
+            Some(3),
+            None
+        )));
+        assert!(events.contains(&(
+            "DELETE FROM test_table".to_owned(),
+            Some("DELETE"),
+            None,
+            Some("unfiltered_write")
+        )));
+    }
+}
@@ -0,0 +1,228 @@
+//! Reusable queries with named parameters
+//!
+//! Build the query once with [Param](crate::expr::Param)s, turn it into a [QueryTemplate] and bind the values on every execution.
+//!
+//! ```
+//! use pg_extended_sqlx_queries::prelude::*;
+//! #[derive(TableType)]
+//! #[table(name = "users")]
+//! pub struct User {
+//!     #[column(primary_key)]
+//!     pub id: i32,
+//!     pub age: i32,
+//! }
+//! let mut query = SelectQueryBuilder::with_columns(User::table_name(), UserColumn::all());
+//! query.filter(UserColumn::Age.greater_than(Param::named("age")));
+//! let template = QueryTemplate::new(&mut query).unwrap();
+//! assert_eq!(
+//!     template.sql(),
+//!     "SELECT users.id, users.age FROM users WHERE users.age > $1"
+//! );
+//! let query = template.query(ParamMap::new().with("age", 18)).unwrap();
+//! ```
+use std::{borrow::Cow, fmt::Debug, sync::Arc};
+
+use ahash::AHashMap;
+use sqlx::{
+    Arguments, Encode, FromRow, Postgres, Type,
+    postgres::{PgArguments, PgRow},
+    query::{Query, QueryAs, QueryScalar},
+};
+use tracing::trace;
+
+use crate::{error::Error, expr::DynEncode, traits::QueryTool};
+
+/// A query with the SQL generated once.
+///
+/// Cloning is cheap. The values of the [Param](crate::expr::Param)s are bound in placeholder order on every execution
+#[derive(Debug, Clone)]
+pub struct QueryTemplate {
+    sql: Arc<str>,
+    params: Arc<[Cow<'static, str>]>,
+}
+impl QueryTemplate {
+    /// Generates the SQL of the query.
+    ///
+    /// The query can not contain bound values and can not be executed afterwards
+    pub fn new<'args, Q>(query: &mut Q) -> Result<Self, Error>
+    where
+        Q: QueryTool<'args>,
+    {
+        query.validate()?;
+        let params = query.holder().try_take_named_params()?;
+        let sql = query.format_sql_query();
+        Ok(Self {
+            sql: Arc::from(sql),
+            params: params.into(),
+        })
+    }
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+    /// The parameter names in placeholder order
+    pub fn params(&self) -> &[Cow<'static, str>] {
+        &self.params
+    }
+    fn arguments<'p, P>(&self, mut params: P) -> Result<PgArguments, Error>
+    where
+        P: TemplateParams<'p>,
+    {
+        let mut arguments = PgArguments::default();
+        for name in self.params.iter() {
+            let value = params
+                .take_param(name)
+                .ok_or_else(|| Error::MissingParameter(name.to_string()))?;
+            arguments.add(value).map_err(Error::Encode)?;
+        }
+        Ok(arguments)
+    }
+    /// See [QueryTool::query]
+    pub fn query<'p, P>(&self, params: P) -> Result<Query<'_, Postgres, PgArguments>, Error>
+    where
+        P: TemplateParams<'p>,
+    {
+        let arguments = self.arguments(params)?;
+        trace!(sql = ?self.sql, "Executing Template");
+        Ok(sqlx::query_with(&self.sql, arguments))
+    }
+    /// See [QueryTool::query_as]
+    pub fn query_as<'p, T, P>(
+        &self,
+        params: P,
+    ) -> Result<QueryAs<'_, Postgres, T, PgArguments>, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow>,
+        P: TemplateParams<'p>,
+    {
+        let arguments = self.arguments(params)?;
+        trace!(sql = ?self.sql, "Executing Template");
+        Ok(sqlx::query_as_with(&self.sql, arguments))
+    }
+    /// See [QueryTool::query_scalar]
+    pub fn query_scalar<'p, O, P>(
+        &self,
+        params: P,
+    ) -> Result<QueryScalar<'_, Postgres, O, PgArguments>, Error>
+    where
+        (O,): for<'r> FromRow<'r, PgRow>,
+        P: TemplateParams<'p>,
+    {
+        let arguments = self.arguments(params)?;
+        trace!(sql = ?self.sql, "Executing Template");
+        Ok(sqlx::query_scalar_with(&self.sql, arguments))
+    }
+}
+/// Provides the values for the [Param](crate::expr::Param)s of a [QueryTemplate]
+///
+/// Implement this for a struct to bind its fields by name
+/// ```
+/// use pg_extended_sqlx_queries::prelude::*;
+/// pub struct AgeFilter {
+///     pub age: i32,
+/// }
+/// impl<'args> TemplateParams<'args> for AgeFilter {
+///     fn take_param(&mut self, name: &str) -> Option<DynEncode<'args>> {
+///         match name {
+///             "age" => Some(self.age.value()),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait TemplateParams<'args> {
+    /// Returns the value for the parameter. Called once per parameter
+    fn take_param(&mut self, name: &str) -> Option<DynEncode<'args>>;
+}
+/// Parameter values by name
+#[derive(Default)]
+pub struct ParamMap<'args>(AHashMap<Cow<'static, str>, DynEncode<'args>>);
+impl<'args> ParamMap<'args> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set<T>(&mut self, name: impl Into<Cow<'static, str>>, value: T) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        self.0.insert(name.into(), DynEncode::new(value));
+        self
+    }
+    pub fn with<T>(mut self, name: impl Into<Cow<'static, str>>, value: T) -> Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        self.set(name, value);
+        self
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+impl Debug for ParamMap<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+impl<'args> TemplateParams<'args> for ParamMap<'args> {
+    fn take_param(&mut self, name: &str) -> Option<DynEncode<'args>> {
+        self.0.remove(name)
+    }
+}
+impl<'args> TemplateParams<'args> for &mut ParamMap<'args> {
+    fn take_param(&mut self, name: &str) -> Option<DynEncode<'args>> {
+        self.0.remove(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Error,
+        prelude::*,
+        testing::{TestTable, TestTableColumn},
+    };
+
+    #[test]
+    fn reuses_placeholders() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query
+            .filter(TestTableColumn::Age.greater_than(Param::named("age")))
+            .filter(TestTableColumn::FirstName.equals(Param::named("name")))
+            .filter(TestTableColumn::Id.not_equals(Param::named("age")));
+        let template = QueryTemplate::new(&mut query).unwrap();
+        assert_eq!(template.params(), ["age", "name"]);
+        assert!(template.sql().contains("test_table.age > $1"));
+        assert!(template.sql().contains("test_table.first_name = $2"));
+        assert!(template.sql().contains("test_table.id != $1"));
+
+        let cloned = template.clone();
+        assert_eq!(cloned.sql(), template.sql());
+        let query = cloned.query(ParamMap::new().with("age", 18).with("name", "Wyatt"));
+        assert!(query.is_ok());
+        let Err(err) = template.query(ParamMap::new().with("age", 18)) else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::MissingParameter(name) if name == "name"));
+    }
+    #[test]
+    fn params_can_not_be_mixed() {
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query
+            .filter(TestTableColumn::Age.greater_than(Param::named("age")))
+            .filter(TestTableColumn::Id.equals(1.value()));
+        assert!(matches!(
+            QueryTemplate::new(&mut query),
+            Err(Error::MixedParameters)
+        ));
+
+        let mut query = SelectQueryBuilder::new(TestTable::table_name());
+        query.filter(TestTableColumn::Age.greater_than(Param::named("age")));
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::NamedParameters));
+    }
+}
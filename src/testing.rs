@@ -6,18 +6,20 @@ use chrono::{DateTime, FixedOffset};
 use sqlformat::{FormatOptions, QueryParams};
 
 use crate::{prelude::*, table_layout::Relation};
-#[derive(Debug, Clone, TableType)]
+#[derive(Debug, Clone, TableType, sqlx::FromRow)]
 #[table(name = "test_table")]
 pub struct TestTable {
-    #[column(primary_key)]
+    #[column(primary_key, generated)]
     pub id: i32,
     pub first_name: String,
     pub last_name: String,
     pub age: i32,
     pub email: String,
+    #[column(sensitive)]
     pub phone: String,
     pub another_table_id: Option<i32>,
     pub updated_at: DateTime<FixedOffset>,
+    #[column(insert_only)]
     pub created_at: DateTime<FixedOffset>,
 }
 
@@ -32,6 +34,70 @@ pub struct AnotherTable {
     pub updated_at: DateTime<FixedOffset>,
     pub created_at: DateTime<FixedOffset>,
 }
+#[derive(Debug, Clone, TableType)]
+#[table(name = "users", schema = "auth")]
+pub struct AuthUser {
+    #[column(primary_key, sql_type = "SERIAL")]
+    pub id: i32,
+    #[column(unique, sql_type = "VARCHAR(32)", check = "username <> ''")]
+    pub username: String,
+    pub test_table_id: Option<i32>,
+    #[column(default = "NOW()")]
+    pub created_at: DateTime<FixedOffset>,
+}
+#[derive(Debug, Clone, TableType)]
+#[table(name = "user_roles")]
+pub struct UserRole {
+    #[column(primary_key)]
+    pub user_id: i32,
+    #[column(primary_key)]
+    pub role: String,
+    pub granted_by: Option<i32>,
+}
+#[derive(Debug, Clone, Copy, sqlx::Type)]
+#[sqlx(type_name = "role_kind")]
+pub enum RoleKind {
+    Admin,
+    Member,
+}
+#[derive(Debug, Clone, TableType)]
+#[table(name = "roles")]
+pub struct Role {
+    #[column(primary_key)]
+    pub id: i32,
+    pub role: RoleKind,
+}
+/// A hand written column without [ColumnType::column_info]
+#[derive(Debug, Clone, Copy)]
+pub struct Nickname;
+impl ColumnType for Nickname {
+    fn column_name(&self) -> &'static str {
+        "nickname"
+    }
+    fn table_name(&self) -> &'static str {
+        "nicknames"
+    }
+}
+impl AllColumns for Nickname {
+    fn all() -> Vec<Self> {
+        vec![Nickname]
+    }
+}
+pub struct Nicknames;
+impl TableType for Nicknames {
+    type Columns = Nickname;
+    fn table_name() -> &'static str {
+        "nicknames"
+    }
+}
+impl Relation<TestTable> for AuthUser {
+    fn from_column() -> Self::Columns {
+        AuthUserColumn::TestTableId
+    }
+    fn to_column() -> <TestTable as TableType>::Columns {
+        TestTableColumn::Id
+    }
+}
 impl Relation<AnotherTable> for TestTable {
     fn from_column() -> Self::Columns {
         TestTableColumn::AnotherTableId
@@ -42,12 +108,66 @@ impl Relation<AnotherTable> for TestTable {
 }
 #[cfg(test)]
 mod tests {
-    use crate::{ColumnType, testing::TestTableColumn};
+    use sqlx::postgres::PgTypeInfo;
+
+    use crate::{
+        ColumnType,
+        prelude::{AllColumns, TableType},
+        testing::{AuthUser, AuthUserColumn, TestTable, TestTableColumn},
+    };
     #[test]
     fn test_table_columns() {
         assert!(TestTableColumn::Id.column_name() == "id");
         assert!(TestTableColumn::Id.full_name() == "test_table.id");
     }
+    #[test]
+    fn test_schema_names() {
+        assert_eq!(TestTable::schema_name(), None);
+        assert_eq!(TestTable::qualified_table_name(), "test_table");
+
+        assert_eq!(AuthUser::table_name(), "users");
+        assert_eq!(AuthUser::schema_name(), Some("auth"));
+        assert_eq!(AuthUser::qualified_table_name(), "auth.users");
+        assert_eq!(AuthUserColumn::Id.full_name(), "users.id");
+        assert_eq!(AuthUserColumn::Id.dyn_column().full_name(), "users.id");
+        assert_eq!(AuthUserColumn::Id.schema_name(), Some("auth"));
+    }
+    #[test]
+    fn test_column_info() {
+        let id = TestTableColumn::Id.column_info().unwrap();
+        assert_eq!(id.name, "id");
+        assert_eq!(id.rust_type, "i32");
+        assert!(!id.nullable);
+        assert!(id.primary_key);
+        assert_eq!(id.pg_type, PgTypeInfo::with_name("INT4"));
+
+        let another_table_id = TestTableColumn::AnotherTableId
+            .dyn_column()
+            .column_info()
+            .unwrap();
+        assert_eq!(another_table_id.rust_type, "Option<i32>");
+        assert!(another_table_id.nullable);
+        assert!(!another_table_id.primary_key);
+        assert_eq!(another_table_id.pg_type, PgTypeInfo::with_name("INT4"));
+
+        let created_at = TestTableColumn::CreatedAt.column_info().unwrap();
+        assert_eq!(created_at.pg_type, PgTypeInfo::with_name("TIMESTAMPTZ"));
+        assert!(created_at.is_insertable());
+        assert!(!created_at.is_updatable());
+        assert!(!id.is_insertable());
+    }
+    #[test]
+    fn test_writable_columns() {
+        let insertable = TestTableColumn::insertable_columns();
+        assert!(!insertable.contains(&TestTableColumn::Id));
+        assert!(insertable.contains(&TestTableColumn::CreatedAt));
+
+        let updatable = TestTableColumn::updatable_columns();
+        assert!(!updatable.contains(&TestTableColumn::Id));
+        assert!(!updatable.contains(&TestTableColumn::CreatedAt));
+        assert!(updatable.contains(&TestTableColumn::UpdatedAt));
+        assert_eq!(updatable.len(), TestTableColumn::all().len() - 2);
+    }
 }
 
 pub fn print_query(query: &str, test_name: &'static str) {
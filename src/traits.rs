@@ -2,13 +2,17 @@ use std::borrow::Cow;
 use std::fmt::{Debug, Display};
 use std::future::Future;
 
-use sqlx::postgres::PgRow;
+use sqlx::postgres::{PgQueryResult, PgRow};
 use sqlx::query::{Query, QueryAs, QueryScalar};
-use sqlx::{Database, Postgres};
+use sqlx::{Database, Execute, Postgres};
 use sqlx::{Decode, FromRow, Type};
-use tracing::trace;
+use tracing::{trace, warn};
 
+use crate::error::Error;
+#[cfg(feature = "debug-sql")]
+use crate::expr::{DebugSqlOptions, render_debug_sql};
 use crate::expr::{ExprType, FilterConditionBuilder, HasArguments, SQLCondition};
+use crate::telemetry::{trace_error, trace_query};
 
 pub trait FormatSql: Debug {
     fn format_sql(&self) -> Cow<'_, str>;
@@ -42,6 +46,13 @@ impl<'sql, Sql: FormatSql> From<&'sql Sql> for SpaceBefore<'sql, Sql> {
     }
 }
 
+/// The infallible `query` methods only log a failed [QueryTool::validate].
+/// The `try_` variants return it
+fn warn_if_invalid(result: Result<(), Error>) {
+    if let Err(err) = result {
+        warn!(%err, "Executing a query that failed validation. Use the try_ variant to get the error");
+    }
+}
 pub trait FormatSqlQuery {
     fn format_sql_query(&mut self) -> &str;
 
@@ -62,10 +73,30 @@ pub trait FormatSqlQuery {
 ///
 /// They are great for the simple cases but the more you need. The more you should consider using sql directly.
 pub trait QueryTool<'args>: HasArguments<'args> + FormatSqlQuery + Debug {
+    /// The kind of statement. i.e. `SELECT`. Recorded as `db.operation`
+    fn statement_type(&self) -> Option<&'static str> {
+        None
+    }
+    /// The table the statement targets. Recorded as `db.sql.table`
+    fn statement_table(&self) -> Option<&str> {
+        None
+    }
+    /// Checks the query before it is executed. i.e. an `UPDATE` without a `WHERE`
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
     /// Builds a query that can be executed.
     ///
     /// See [sqlx::query_with] for more information.
+    ///
+    /// If [QueryTool::validate] fails the error is logged and the query is built anyway.
+    ///
+    /// # Panics
+    /// If the arguments were already taken or a value failed to encode.
+    ///
+    /// Use [QueryTool::try_query] to get an [Error] instead
     fn query(&mut self) -> Query<'_, Postgres, <Postgres as Database>::Arguments<'args>> {
+        warn_if_invalid(self.validate());
         let args = self.holder().take_arguments_or_error();
         let sql = self.format_sql_query();
         trace!(?sql, "Generated SQL");
@@ -75,10 +106,18 @@ pub trait QueryTool<'args>: HasArguments<'args> + FormatSqlQuery + Debug {
     /// Builds a query that can be executed and returns the results as a type.
     ///
     /// See [sqlx::query_as_with] for more information.
+    ///
+    /// If [QueryTool::validate] fails the error is logged and the query is built anyway.
+    ///
+    /// # Panics
+    /// If the arguments were already taken or a value failed to encode.
+    ///
+    /// Use [QueryTool::try_query_as] to get an [Error] instead
     fn query_as<T>(&mut self) -> QueryAs<'_, Postgres, T, <Postgres as Database>::Arguments<'args>>
     where
         T: for<'r> FromRow<'r, PgRow>,
     {
+        warn_if_invalid(self.validate());
         let args = self.holder().take_arguments_or_error();
 
         let sql = self.format_sql_query();
@@ -88,18 +127,201 @@ pub trait QueryTool<'args>: HasArguments<'args> + FormatSqlQuery + Debug {
     /// Builds a query that can be executed and returns the results as a scalar.
     ///
     /// See [sqlx::query_scalar_with] for more information.
+    ///
+    /// If [QueryTool::validate] fails the error is logged and the query is built anyway.
+    ///
+    /// # Panics
+    /// If the arguments were already taken or a value failed to encode.
+    ///
+    /// Use [QueryTool::try_query_scalar] to get an [Error] instead
     fn query_scalar<O>(
         &mut self,
     ) -> QueryScalar<'_, Postgres, O, <Postgres as Database>::Arguments<'args>>
     where
         (O,): for<'r> FromRow<'r, PgRow>,
     {
+        warn_if_invalid(self.validate());
         let args = self.holder().take_arguments_or_error();
 
         let sql = self.format_sql_query();
         trace!(?sql, "Generated SQL");
         sqlx::query_scalar_with(sql, args)
     }
+    /// [QueryTool::query] that returns an [Error] if the arguments were already taken or failed to encode
+    fn try_query(
+        &mut self,
+    ) -> Result<Query<'_, Postgres, <Postgres as Database>::Arguments<'args>>, Error> {
+        self.validate()?;
+        let args = self.try_take_arguments()?;
+        let sql = self.format_sql_query();
+        trace!(?sql, "Generated SQL");
+
+        Ok(sqlx::query_with(sql, args))
+    }
+    /// [QueryTool::query_as] that returns an [Error] if the arguments were already taken or failed to encode
+    fn try_query_as<T>(
+        &mut self,
+    ) -> Result<QueryAs<'_, Postgres, T, <Postgres as Database>::Arguments<'args>>, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow>,
+    {
+        self.validate()?;
+        let args = self.try_take_arguments()?;
+        let sql = self.format_sql_query();
+        trace!(?sql, "Generated SQL");
+        Ok(sqlx::query_as_with(sql, args))
+    }
+    /// [QueryTool::query_scalar] that returns an [Error] if the arguments were already taken or failed to encode
+    fn try_query_scalar<O>(
+        &mut self,
+    ) -> Result<QueryScalar<'_, Postgres, O, <Postgres as Database>::Arguments<'args>>, Error>
+    where
+        (O,): for<'r> FromRow<'r, PgRow>,
+    {
+        self.validate()?;
+        let args = self.try_take_arguments()?;
+        let sql = self.format_sql_query();
+        trace!(?sql, "Generated SQL");
+        Ok(sqlx::query_scalar_with(sql, args))
+    }
+    /// Executes the query inside of a `db.query` span. See [telemetry](crate::telemetry)
+    fn execute_traced<'c, E>(
+        &mut self,
+        conn: E,
+    ) -> impl Future<Output = Result<PgQueryResult, sqlx::Error>> + Send
+    where
+        E: sqlx::Executor<'c, Database = Postgres> + Send,
+        Self: Send,
+    {
+        async move {
+            let operation = self.statement_type();
+            let table = self.statement_table().map(str::to_owned);
+            let query = match self.try_query() {
+                Ok(query) => query,
+                Err(err) => {
+                    let sql = self.format_sql_query().to_owned();
+                    return trace_error(operation, table.as_deref(), &sql, err).await;
+                }
+            };
+            let sql = query.sql();
+            trace_query(
+                operation,
+                table.as_deref(),
+                sql,
+                query.execute(conn),
+                PgQueryResult::rows_affected,
+            )
+            .await
+        }
+    }
+    /// [sqlx::query::QueryAs::fetch_all] inside of a `db.query` span. See [telemetry](crate::telemetry)
+    fn fetch_all_traced<'c, T, E>(
+        &mut self,
+        conn: E,
+    ) -> impl Future<Output = Result<Vec<T>, sqlx::Error>> + Send
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: sqlx::Executor<'c, Database = Postgres> + Send,
+        Self: Send,
+    {
+        async move {
+            let operation = self.statement_type();
+            let table = self.statement_table().map(str::to_owned);
+            let query = match self.try_query_as::<T>() {
+                Ok(query) => query,
+                Err(err) => {
+                    let sql = self.format_sql_query().to_owned();
+                    return trace_error(operation, table.as_deref(), &sql, err).await;
+                }
+            };
+            let sql = query.sql();
+            trace_query(
+                operation,
+                table.as_deref(),
+                sql,
+                query.fetch_all(conn),
+                |rows| rows.len() as u64,
+            )
+            .await
+        }
+    }
+    /// [sqlx::query::QueryAs::fetch_one] inside of a `db.query` span. See [telemetry](crate::telemetry)
+    fn fetch_one_traced<'c, T, E>(
+        &mut self,
+        conn: E,
+    ) -> impl Future<Output = Result<T, sqlx::Error>> + Send
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: sqlx::Executor<'c, Database = Postgres> + Send,
+        Self: Send,
+    {
+        async move {
+            let operation = self.statement_type();
+            let table = self.statement_table().map(str::to_owned);
+            let query = match self.try_query_as::<T>() {
+                Ok(query) => query,
+                Err(err) => {
+                    let sql = self.format_sql_query().to_owned();
+                    return trace_error(operation, table.as_deref(), &sql, err).await;
+                }
+            };
+            let sql = query.sql();
+            trace_query(
+                operation,
+                table.as_deref(),
+                sql,
+                query.fetch_one(conn),
+                |_| 1,
+            )
+            .await
+        }
+    }
+    /// [sqlx::query::QueryAs::fetch_optional] inside of a `db.query` span. See [telemetry](crate::telemetry)
+    fn fetch_optional_traced<'c, T, E>(
+        &mut self,
+        conn: E,
+    ) -> impl Future<Output = Result<Option<T>, sqlx::Error>> + Send
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: sqlx::Executor<'c, Database = Postgres> + Send,
+        Self: Send,
+    {
+        async move {
+            let operation = self.statement_type();
+            let table = self.statement_table().map(str::to_owned);
+            let query = match self.try_query_as::<T>() {
+                Ok(query) => query,
+                Err(err) => {
+                    let sql = self.format_sql_query().to_owned();
+                    return trace_error(operation, table.as_deref(), &sql, err).await;
+                }
+            };
+            let sql = query.sql();
+            trace_query(
+                operation,
+                table.as_deref(),
+                sql,
+                query.fetch_optional(conn),
+                |row| row.is_some() as u64,
+            )
+            .await
+        }
+    }
+    /// The SQL with the bound values inlined as literals. Values of sensitive columns are redacted.
+    ///
+    /// For logging and pasting into psql only. Never execute the result.
+    ///
+    /// Requires the `debug-sql` feature. It keeps a copy of every bound value
+    #[cfg(feature = "debug-sql")]
+    fn debug_sql(&mut self) -> String {
+        self.debug_sql_with(&DebugSqlOptions::default())
+    }
+    /// [QueryTool::debug_sql] with the redaction configured
+    #[cfg(feature = "debug-sql")]
+    fn debug_sql_with(&mut self, options: &DebugSqlOptions) -> String {
+        let sql = self.format_sql_query().to_owned();
+        render_debug_sql(&sql, self.holder().debug_values(), options)
+    }
 }
 /// Tools such as [SelectExists](super::SelectExists) and [SelectCount](super::SelectCount)
 /// that can be used to build queries that return a single value.
@@ -116,7 +338,7 @@ pub trait QueryScalarTool<'args>: QueryTool<'args> + Send {
         E: sqlx::Executor<'c, Database = Postgres> + Send,
     {
         async move {
-            let query = self.query_scalar();
+            let query = self.try_query_scalar()?;
             let result = query.fetch_one(conn).await?;
             Ok(result)
         }
@@ -162,7 +384,11 @@ where
     F: FormatWhere,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, " WHERE {}", self.0.format_where())
+        let filter = self.0.format_where();
+        if filter.is_empty() {
+            return Ok(());
+        }
+        write!(f, " WHERE {filter}")
     }
 }
 pub trait ExpressionWhereable<'args>: Sized {
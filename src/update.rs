@@ -2,14 +2,19 @@ use std::{borrow::Cow, fmt::Debug};
 
 use tracing::{debug, instrument};
 
-use crate::prelude::*;
+use crate::{
+    error::Error, policy::UnfilteredWrites, prelude::*, table_layout::quote_table_reference,
+};
 
 pub struct UpdateQueryBuilder<'args> {
     table: Cow<'args, str>,
     columns_to_update: Vec<(DynColumn, Expr)>,
+    from: Vec<TableSource>,
     where_comparisons: Vec<SQLCondition>,
     sql: Option<String>,
     returning: Option<Returning>,
+    /// Allows the query to not have a `WHERE`
+    all_rows: bool,
     arguments: ArgumentHolder<'args>,
 }
 
@@ -35,15 +40,28 @@ impl<'args> FormatSqlQuery for UpdateQueryBuilder<'args> {
         let columns_to_update = self
             .columns_to_update
             .iter()
-            .map(|(column, value)| format!("{} = {}", column.column_name(), value.format_sql()))
+            .map(|(column, value)| {
+                format!("{} = {}", column.quoted_column_name(), value.format_sql())
+            })
             .collect::<Vec<_>>()
             .join(", ");
 
+        let from = if self.from.is_empty() {
+            String::new()
+        } else {
+            let sources: Vec<_> = self.from.iter().map(|source| source.format_sql()).collect();
+            format!(" FROM {}", sources.join(", "))
+        };
+        let returning = match &self.returning {
+            Some(returning) if !self.from.is_empty() => {
+                format!(" {}", returning.format_qualified(&self.table))
+            }
+            returning => SpaceBefore::from(returning.as_ref()).to_string(),
+        };
         let sql = format!(
-            "UPDATE {table} SET {columns_to_update}{filter}{returning};",
+            "UPDATE {table} SET {columns_to_update}{from}{filter}{returning};",
             table = self.table,
             filter = FormatWhereItem(self),
-            returning = SpaceBefore::from(self.returning.as_ref())
         );
         debug!(?sql, "UpdateQueryBuilder::format_sql_query");
         self.sql = Some(sql);
@@ -52,14 +70,35 @@ impl<'args> FormatSqlQuery for UpdateQueryBuilder<'args> {
     }
 }
 
-impl<'args> QueryTool<'args> for UpdateQueryBuilder<'args> {}
+impl<'args> QueryTool<'args> for UpdateQueryBuilder<'args> {
+    fn statement_type(&self) -> Option<&'static str> {
+        Some("UPDATE")
+    }
+    fn statement_table(&self) -> Option<&str> {
+        Some(&self.table)
+    }
+    fn validate(&self) -> Result<(), Error> {
+        if self.columns_to_update.is_empty() {
+            return Err(Error::MissingClause {
+                statement: "UPDATE",
+                clause: "SET",
+            });
+        }
+        if self.where_comparisons.is_empty() && !self.all_rows {
+            return UnfilteredWrites::check("UPDATE", &self.table);
+        }
+        Ok(())
+    }
+}
 impl<'args> Debug for UpdateQueryBuilder<'args> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("UpdateQueryBuilder")
             .field("table", &self.table)
             .field("columns_to_update", &self.columns_to_update)
+            .field("from", &self.from)
             .field("where_comparisons", &self.where_comparisons)
             .field("sql", &self.sql)
+            .field("all_rows", &self.all_rows)
             .field("returning", &self.returning)
             .finish()
     }
@@ -67,24 +106,80 @@ impl<'args> Debug for UpdateQueryBuilder<'args> {
 impl<'args> UpdateQueryBuilder<'args> {
     pub fn new(table: &'args str) -> Self {
         Self {
-            table: Cow::Borrowed(table),
+            table: quote_table_reference(table),
             columns_to_update: Vec::new(),
+            from: Vec::new(),
             where_comparisons: Vec::new(),
             sql: None,
+            all_rows: false,
             returning: None,
             arguments: Default::default(),
         }
     }
+    /// Updates every row if no filter is added.
+    ///
+    /// Without this an `UPDATE` without a `WHERE` is an [Error::UnfilteredWrite]
+    pub fn all_rows(&mut self) -> &mut Self {
+        self.all_rows = true;
+        self
+    }
+    /// `FROM {table}`. Join conditions go in the filter
+    pub fn from_table(&mut self, table: &'args str) -> &mut Self {
+        self.sql = None;
+        self.from.push(TableSource::table(table));
+        self
+    }
+    /// `FROM {table} AS {alias}`. Use [SourceColumn] to reference its columns
+    pub fn from_table_as(
+        &mut self,
+        table: &'args str,
+        alias: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sql = None;
+        self.from.push(TableSource::table_as(table, alias));
+        self
+    }
+    /// `FROM ({query}) AS {alias}`
+    pub fn from_subquery(
+        &mut self,
+        query: SelectExprBuilder<'args>,
+        alias: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sql = None;
+        let source = TableSource::subquery(query, alias, &mut self.arguments);
+        self.from.push(source);
+        self
+    }
+    /// `FROM (VALUES ..) AS {alias} ({columns})`
+    pub fn from_values(&mut self, values: ValuesList<'args>) -> &mut Self {
+        self.sql = None;
+        let source = TableSource::values(values, &mut self.arguments);
+        self.from.push(source);
+        self
+    }
+    /// Sets the column to the value
+    ///
+    /// If the column is a typed column constant. i.e. `UserColumn::NAME` the value must match the type of the field
+    ///
+    /// The query returns [Error::ColumnNotWritable] if the column is generated, read only or insert only
     pub fn set<C, V>(&mut self, column: C, value: V) -> &mut Self
     where
-        C: ColumnType + 'static,
+        C: ColumnTarget<'args, V>,
         V: ExprType<'args> + 'args,
     {
-        let value = value.process_unboxed(&mut self.arguments);
-        self.columns_to_update.push((column.dyn_column(), value));
+        let column = column.target_column();
+        self.arguments.check_updatable(&column);
+        let value = self.arguments.process_for_column(&column, value);
+        self.columns_to_update.push((column, value));
         self
     }
 
+    /// If any column will be set.
+    ///
+    /// An `UPDATE` without any columns is invalid SQL. `try_query` returns [Error::MissingClause]
+    pub fn has_changes(&self) -> bool {
+        !self.columns_to_update.is_empty()
+    }
     /// Sets the column to NULL
     ///
     /// Shortcut for `set(column, SqlNull)`
@@ -92,6 +187,7 @@ impl<'args> UpdateQueryBuilder<'args> {
     where
         C: ColumnType + 'static,
     {
+        self.arguments.check_updatable(&column);
         self.columns_to_update
             .push((column.dyn_column(), Keywords::Null.into()));
         self
@@ -108,8 +204,11 @@ mod tests {
     use sqlformat::{FormatOptions, QueryParams};
 
     use crate::{
+        Error,
         prelude::*,
-        testing::{AnotherTable, AnotherTableColumn, TestTable, TestTableColumn},
+        testing::{
+            AnotherTable, AnotherTableColumn, AuthUser, AuthUserColumn, TestTable, TestTableColumn,
+        },
     };
 
     #[test]
@@ -174,4 +273,169 @@ mod tests {
 
         println!("{sql}");
     }
+
+    #[test]
+    pub fn test_builder_typed() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query.filter(TestTableColumn::ID.equals(1));
+        query
+            .set(TestTableColumn::AGE, TestTableColumn::AGE.add(1))
+            .set(TestTableColumn::ANOTHER_TABLE_ID, None);
+        let sql = query.format_sql_query();
+        assert_eq!(
+            sql,
+            "UPDATE test_table SET age = (test_table.age + $2), another_table_id = $3 WHERE test_table.id = $1;"
+        );
+    }
+    #[test]
+    pub fn test_builder_with_schema() {
+        let mut query = UpdateQueryBuilder::new(AuthUser::qualified_table_name());
+        query.filter(AuthUserColumn::Id.equals(1));
+        query.set(AuthUserColumn::Username, "john");
+        let sql = query.format_sql_query();
+        assert_eq!(
+            sql,
+            "UPDATE auth.users SET username = $2 WHERE users.id = $1;"
+        );
+    }
+    #[test]
+    fn unfiltered_update_is_denied() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query.set(TestTableColumn::Age, 50.value());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert_eq!(
+            err.to_string(),
+            "UPDATE on test_table has no WHERE clause. Call all_rows() to write every row"
+        );
+        assert!(matches!(err, Error::UnfilteredWrite { .. }));
+
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query.set(TestTableColumn::Age, 50.value()).all_rows();
+        assert!(query.try_query().is_ok());
+        assert_eq!(query.format_sql_query(), "UPDATE test_table SET age = $1;");
+    }
+    #[test]
+    fn set_rejects_unwritable_columns() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .filter(TestTableColumn::Id.equals(1.value()))
+            .set(TestTableColumn::Id, 2.value());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(err, Error::ColumnNotWritable { column: "id" }));
+
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .filter(TestTableColumn::Id.equals(1.value()))
+            .set(TestTableColumn::CreatedAt, SqlFunctionBuilder::now());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::ColumnNotWritable {
+                column: "created_at"
+            }
+        ));
+    }
+    #[test]
+    fn empty_set_is_an_error() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query.filter(TestTableColumn::Id.equals(1.value()));
+        assert!(!query.has_changes());
+        let Err(err) = query.try_query() else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Error::MissingClause {
+                statement: "UPDATE",
+                clause: "SET"
+            }
+        ));
+    }
+    #[test]
+    fn test_update_from() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .set(
+                TestTableColumn::Email,
+                SourceColumn::of("s", AnotherTableColumn::Email),
+            )
+            .from_table_as(AnotherTable::table_name(), "s")
+            .filter(
+                TestTableColumn::AnotherTableId
+                    .equals(SourceColumn::of("s", AnotherTableColumn::Id)),
+            );
+        assert_eq!(
+            query.format_sql_query(),
+            "UPDATE test_table SET email = s.email FROM another_table AS s WHERE test_table.another_table_id = s.id;"
+        );
+        query.return_columns(vec![TestTableColumn::Id, TestTableColumn::Email]);
+        assert!(
+            query
+                .format_sql_query()
+                .ends_with(" RETURNING test_table.id, test_table.email;")
+        );
+
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .set(TestTableColumn::Age, SourceColumn::new("old", "age"))
+            .from_subquery(
+                SelectExprBuilder::new(AnotherTable::table_name())
+                    .column(AnotherTableColumn::Id)
+                    .column(AnotherTableColumn::Age)
+                    .filter(AnotherTableColumn::Age.greater_than(50.value())),
+                "old",
+            )
+            .filter(TestTableColumn::AnotherTableId.equals(SourceColumn::new("old", "id")));
+        assert_eq!(
+            query.format_sql_query(),
+            "UPDATE test_table SET age = old.age FROM (SELECT another_table.id, another_table.age FROM another_table WHERE another_table.age > $1) AS old WHERE test_table.another_table_id = old.id;"
+        );
+    }
+    #[test]
+    fn test_update_from_values() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .set(
+                TestTableColumn::FirstName,
+                SourceColumn::new("v", "first_name"),
+            )
+            .from_values(
+                ValuesList::new("v", ["id", "first_name"])
+                    .row([1.value(), "Wyatt".value()])
+                    .row([2.value(), "Jacob".value()]),
+            )
+            .filter(TestTableColumn::Id.equals(SourceColumn::new("v", "id")));
+        assert_eq!(
+            query.format_sql_query(),
+            "UPDATE test_table SET first_name = v.first_name FROM (VALUES ($1, $2), ($3, $4)) AS v (id, first_name) WHERE test_table.id = v.id;"
+        );
+        assert_eq!(query.holder().len(), 4);
+    }
+    #[test]
+    fn test_update_from_quotes_aliases() {
+        let mut query = UpdateQueryBuilder::new(TestTable::table_name());
+        query
+            .set(
+                TestTableColumn::Email,
+                SourceColumn::of("user", AnotherTableColumn::Email),
+            )
+            .from_table_as(AnotherTable::table_name(), "user")
+            .from_values(ValuesList::new("Order", ["id"]).row([1.value()]))
+            .filter(
+                TestTableColumn::AnotherTableId
+                    .equals(SourceColumn::of("user", AnotherTableColumn::Id)),
+            )
+            .filter(TestTableColumn::Id.equals(SourceColumn::new("Order", "id")));
+        assert_eq!(
+            query.format_sql_query(),
+            "UPDATE test_table SET email = \"user\".email FROM another_table AS \"user\", (VALUES ($1)) AS \"Order\" (id) \
+             WHERE test_table.another_table_id = \"user\".id AND test_table.id = \"Order\".id;"
+        );
+    }
 }
@@ -44,8 +44,251 @@ pub struct CustomColumnName {
     pub updated_at: DateTime<FixedOffset>,
     pub created_at: DateTime<FixedOffset>,
 }
+#[derive(Debug, Clone, TableType)]
+#[table(name = "invoices", schema = "billing")]
+pub struct Invoice {
+    pub id: i32,
+    pub total: i64,
+}
+#[test]
+fn test_schema() {
+    assert_eq!(Invoice::schema_name(), Some("billing"));
+    assert_eq!(Invoice::qualified_table_name(), "billing.invoices");
+    assert_eq!(InvoiceColumn::Total.full_name(), "invoices.total");
+    assert_eq!(TestTable::qualified_table_name(), "test_table");
+}
+#[derive(Debug, Clone, TableType)]
+#[table(name = "Orders", schema = "billing")]
+pub struct Order {
+    pub id: i32,
+    #[column(name = "user")]
+    pub user_id: i32,
+    #[column(name = "createdAt")]
+    pub created_at: DateTime<FixedOffset>,
+    #[column(quoted)]
+    pub total: i64,
+}
+#[test]
+fn test_quoted_identifiers() {
+    assert_eq!(Order::table_name(), "Orders");
+    assert_eq!(Order::qualified_table_name(), "billing.\"Orders\"");
+    assert_eq!(OrderColumn::Id.full_name(), "\"Orders\".id");
+    assert_eq!(OrderColumn::UserId.column_name(), "user");
+    assert_eq!(OrderColumn::UserId.quoted_column_name(), "\"user\"");
+    assert_eq!(
+        OrderColumn::CreatedAt.full_name(),
+        "\"Orders\".\"createdAt\""
+    );
+    assert_eq!(OrderColumn::Total.quoted_column_name(), "\"total\"");
+
+    let mut query = InsertQueryBuilder::new(Order::qualified_table_name());
+    query
+        .insert(OrderColumn::UserId, 1)
+        .insert(OrderColumn::Total, 100i64)
+        .return_columns(vec![OrderColumn::Id, OrderColumn::CreatedAt]);
+    assert_eq!(
+        query.format_sql_query(),
+        "INSERT INTO billing.\"Orders\" (\"user\", \"total\") VALUES ($1, $2) RETURNING id, \"createdAt\";"
+    );
+}
 #[derive(Type, ValueExprType)]
 pub enum SqlxTypeTest {
     Variant1,
     Variant2,
 }
+#[derive(Debug, Clone, TableType, sqlx::FromRow)]
+#[table(name = "accounts", crud)]
+pub struct Account {
+    #[column(primary_key)]
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub bio: Option<String>,
+}
+/// Only checks that the generated CRUD methods compile and can be used from multithreaded runtimes
+#[allow(dead_code)]
+fn crud_futures_are_send(pool: &sqlx::PgPool) {
+    fn assert_send<F: Send>(_: F) {}
+    assert_send(Account::find_all(pool));
+    assert_send(Account::count(pool));
+    assert_send(Account::find_by_id(pool, 1));
+    assert_send(Account::exists_by_id(pool, 1));
+    assert_send(Account::delete_by_id(pool, 1));
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UserId(i64);
+impl TryFrom<i64> for UserId {
+    type Error = std::num::TryFromIntError;
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Ok(Self(value))
+    }
+}
+/// Not a `sqlx::Type`. The type info is given with `#[column(type_info = ...)]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tags(Vec<String>);
+
+#[derive(Debug, Clone, TableType, sqlx::FromRow)]
+#[table(name = "profiles")]
+pub struct Profile {
+    #[column(primary_key)]
+    #[sqlx(try_from = "i64")]
+    pub id: UserId,
+    #[sqlx(skip)]
+    #[column(type_info = Vec<String>)]
+    pub tags: Tags,
+}
+#[test]
+fn test_column_type_info() {
+    let id = ProfileColumn::Id.column_info().unwrap();
+    assert_eq!(id.type_name(), "INT8");
+    assert_eq!(id.rust_type, "UserId");
+    let tags = ProfileColumn::Tags.column_info().unwrap();
+    assert_eq!(tags.type_name(), "TEXT[]");
+}
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Settings {
+    pub theme: String,
+}
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, TableType, sqlx::FromRow)]
+#[table(name = "user_settings")]
+pub struct UserSettings {
+    #[column(primary_key)]
+    pub id: i32,
+    #[sqlx(json)]
+    pub settings: Settings,
+}
+#[cfg(feature = "json")]
+#[test]
+fn test_json_column_type_info() {
+    let settings = UserSettingsColumn::Settings.column_info().unwrap();
+    assert_eq!(settings.type_name(), "JSONB");
+}
+#[derive(Debug, Clone, Insertable)]
+#[insertable(table = Account, conflict(email_address))]
+pub struct NewAccount {
+    #[column(default)]
+    pub id: i32,
+    pub name: String,
+    #[column(column_variant_name = Email)]
+    pub email_address: String,
+    pub bio: Option<String>,
+}
+#[test]
+fn test_insertable() {
+    let new_account = NewAccount {
+        id: 0,
+        name: "Wyatt".to_owned(),
+        email_address: "wyatt@example.com".to_owned(),
+        bio: None,
+    };
+    let mut query = new_account.clone().into_insert_builder();
+    assert_eq!(
+        query.format_sql_query(),
+        "INSERT INTO accounts (name, email) VALUES ($1, $2) ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name;"
+    );
+
+    let mut query = NewAccount::insert_many([
+        new_account,
+        NewAccount {
+            id: 0,
+            name: "John".to_owned(),
+            email_address: "john@example.com".to_owned(),
+            bio: Some("Hello".to_owned()),
+        },
+    ]);
+    query.return_all();
+    assert_eq!(
+        query.format_sql_query(),
+        "INSERT INTO accounts (name, email, bio) VALUES ($1, $2, DEFAULT), ($3, $4, $5) ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name RETURNING *;"
+    );
+
+    let mut query = NewAccount::insert_many([NewAccount {
+        id: 0,
+        name: "John".to_owned(),
+        email_address: "john@example.com".to_owned(),
+        bio: Some("Hello".to_owned()),
+    }]);
+    assert_eq!(
+        query.format_sql_query(),
+        "INSERT INTO accounts (name, email, bio) VALUES ($1, $2, $3) ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name, bio = EXCLUDED.bio;"
+    );
+}
+#[derive(Debug, Default, Changeset)]
+#[changeset(table = Account)]
+pub struct UpdateAccount {
+    pub name: Option<String>,
+    #[column(column_variant_name = Email)]
+    pub email_address: Option<String>,
+    pub bio: Option<Option<String>>,
+    #[column(skip)]
+    pub reason: Option<String>,
+}
+#[test]
+fn test_changeset() {
+    let changes = UpdateAccount {
+        reason: Some("Not a column".to_owned()),
+        ..Default::default()
+    };
+    assert!(!changes.has_changes());
+    let mut query = UpdateQueryBuilder::new(Account::table_name());
+    query.filter(AccountColumn::Id.equals(1.value()));
+    changes.apply_to(&mut query);
+    assert!(!query.has_changes());
+    assert!(matches!(
+        query.try_query(),
+        Err(pg_extended_sqlx_queries::Error::MissingClause { clause: "SET", .. })
+    ));
+    // The infallible query() does not panic
+    let mut query = UpdateQueryBuilder::new(Account::table_name());
+    query.filter(AccountColumn::Id.equals(1.value()));
+    UpdateAccount::default().apply_to(&mut query);
+    let _ = query.query();
+
+    let changes = UpdateAccount {
+        email_address: Some("wyatt@example.com".to_owned()),
+        bio: Some(None),
+        ..Default::default()
+    };
+    assert!(changes.has_changes());
+    let mut query = UpdateQueryBuilder::new(Account::table_name());
+    query.filter(AccountColumn::Id.equals(1.value()));
+    changes.apply_to(&mut query);
+    assert!(query.has_changes());
+    assert_eq!(
+        query.format_sql_query(),
+        "UPDATE accounts SET email = $2, bio = NULL WHERE accounts.id = $1;"
+    );
+}
+#[derive(Debug, Clone, TableType)]
+#[table(name = "audit_logs")]
+pub struct AuditLog {
+    #[column(primary_key, generated)]
+    pub id: i32,
+    pub message: String,
+    #[column(insert_only)]
+    pub created_at: DateTime<FixedOffset>,
+}
+#[derive(Debug, Default, Changeset)]
+#[changeset(table = AuditLog)]
+pub struct UpdateAuditLog {
+    pub message: Option<String>,
+    pub created_at: Option<DateTime<FixedOffset>>,
+}
+#[test]
+fn test_changeset_rejects_unwritable_columns() {
+    let changes = UpdateAuditLog {
+        created_at: Some(DateTime::default()),
+        ..Default::default()
+    };
+    let mut query = UpdateQueryBuilder::new(AuditLog::table_name());
+    query.filter(AuditLogColumn::Id.equals(1.value()));
+    changes.apply_to(&mut query);
+    assert!(matches!(
+        query.try_query(),
+        Err(pg_extended_sqlx_queries::Error::ColumnNotWritable {
+            column: "created_at"
+        })
+    ));
+}
@@ -0,0 +1,57 @@
+//! The policy is process wide. This file has a single test so no other test sees the changes
+use pg_extended_sqlx_queries::{Error, policy::UnfilteredWrites, prelude::*};
+use sqlx::Execute;
+
+#[derive(Debug, Clone, TableType)]
+#[table(name = "users")]
+pub struct User {
+    #[column(primary_key)]
+    pub id: i32,
+    pub name: String,
+}
+
+fn unfiltered_update() -> Result<(), Error> {
+    let mut query = UpdateQueryBuilder::new(User::table_name());
+    query.set(UserColumn::Name, "Wyatt".value());
+    query.try_query().map(|_| ())
+}
+fn unfiltered_delete() -> Result<(), Error> {
+    let mut query = DeleteQueryBuilder::new(User::table_name());
+    query.try_query().map(|_| ())
+}
+
+#[test]
+fn unfiltered_writes_policy() {
+    assert_eq!(UnfilteredWrites::current(), UnfilteredWrites::Deny);
+    // The infallible query() does not panic. It logs the error
+    let mut query = UpdateQueryBuilder::new(User::table_name());
+    query.set(UserColumn::Name, "Wyatt".value());
+    assert_eq!(query.query().sql(), "UPDATE users SET name = $1;");
+    assert!(matches!(
+        unfiltered_update(),
+        Err(Error::UnfilteredWrite {
+            statement: "UPDATE",
+            ..
+        })
+    ));
+    assert!(matches!(
+        unfiltered_delete(),
+        Err(Error::UnfilteredWrite {
+            statement: "DELETE",
+            ..
+        })
+    ));
+
+    UnfilteredWrites::Warn.set();
+    assert_eq!(UnfilteredWrites::current(), UnfilteredWrites::Warn);
+    assert!(unfiltered_update().is_ok());
+    assert!(unfiltered_delete().is_ok());
+
+    UnfilteredWrites::Allow.set();
+    assert_eq!(UnfilteredWrites::current(), UnfilteredWrites::Allow);
+    assert!(unfiltered_update().is_ok());
+    assert!(unfiltered_delete().is_ok());
+
+    UnfilteredWrites::Deny.set();
+    assert!(unfiltered_update().is_err());
+}